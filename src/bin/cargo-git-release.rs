@@ -1,14 +1,26 @@
 use std::process;
 
-use cargo_git_release::{Cli, ReleaseTool};
-use clap::Parser as _;
+use cargo_git_release::{Cli, ReleaseError, ReleaseTool, error, logger};
 
 fn main() {
-    let args = Cli::parse();
+    let args = match Cli::parse_with_config() {
+        Ok(args) => args,
+        Err(error) => {
+            logger::print_error(&error);
+            process::exit(error::EXIT_FAILURE);
+        }
+    };
+    if args.print_completions() {
+        return;
+    }
+    logger::init(args.log_level(), args.color());
     let mut tool = ReleaseTool::new(args);
 
-    if let Err(error) = tool.run() {
-        println!("error: {}", error);
-        process::exit(1);
+    if let Err(err) = tool.run() {
+        // 没有需要发布的内容不是错误，提示已经输出过，只需要与发布成功区分退出码
+        if !matches!(err, ReleaseError::NothingToRelease(_)) {
+            logger::print_error(&err);
+        }
+        process::exit(err.exit_code());
     }
 }
@@ -0,0 +1,251 @@
+//! 根据提交记录生成更新日志
+
+use regex::Regex;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::conventional;
+use crate::git::CommitInfo;
+
+const HEADER: &str = "# Changelog";
+
+/// 提交链接模板中的占位符
+const HASH_PLACEHOLDER: &str = "{hash}";
+
+/// 更新日志中的分组，按输出顺序排列
+const GROUPS: [&str; 3] = ["Features", "Fixes", "Other"];
+
+/// 生成一个版本的更新日志正文（不含版本标题）
+///
+/// 提交按 Conventional Commits 类型分组：`feat` 归入 Features，`fix` 归入 Fixes，
+/// 其余归入 Other。所有提交都属于 Other 时不输出分组标题。
+/// `commit_link` 为提交链接模板，其中的 `{hash}` 会被替换为完整的提交 ID。
+pub fn render_notes(commits: &[CommitInfo], commit_link: Option<&str>) -> String {
+    let mut groups: [Vec<String>; 3] = Default::default();
+    for commit in commits {
+        let group = match conventional::commit_type(&commit.message).as_deref() {
+            Some("feat") => 0,
+            Some("fix") => 1,
+            _ => 2,
+        };
+        groups[group].push(render_entry(commit, commit_link));
+    }
+
+    if groups[0].is_empty() && groups[1].is_empty() {
+        return groups[2].concat();
+    }
+
+    let mut notes = String::new();
+    for (title, entries) in GROUPS.iter().zip(&groups) {
+        if entries.is_empty() {
+            continue;
+        }
+        if !notes.is_empty() {
+            notes.push('\n');
+        }
+        notes.push_str(&format!("### {}\n\n", title));
+        notes.push_str(&entries.concat());
+    }
+    notes
+}
+
+fn render_entry(commit: &CommitInfo, commit_link: Option<&str>) -> String {
+    let short = &commit.id[..7.min(commit.id.len())];
+    match commit_link {
+        Some(template) => format!(
+            "- {} ([{}]({}))\n",
+            commit.summary(),
+            short,
+            template.replace(HASH_PLACEHOLDER, &commit.id)
+        ),
+        None => format!("- {} ({})\n", commit.summary(), short),
+    }
+}
+
+/// 在更新日志正文末尾附上比较两个版本的链接
+pub fn append_compare_link(notes: &str, url: &str) -> String {
+    format!("{}\n\n**Full Changelog**: {}\n", notes.trim_end(), url)
+}
+
+/// 根据远程仓库地址生成 `base...head` 的比较页面地址，只支持 GitHub 和 GitLab
+///
+/// 远程仓库地址可以是 `git@host:owner/repo.git`、`ssh://git@host/owner/repo`
+/// 或 `https://host/owner/repo.git` 等形式，无法识别的主机返回 `None`
+pub fn compare_url(remote_url: &str, base: &str, head: &str) -> Option<String> {
+    let (host, path) = match remote_url.split_once("://") {
+        Some((_, rest)) => {
+            let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+            let (host, path) = rest.split_once('/')?;
+            // 去掉 ssh 地址中的端口
+            (host.split(':').next()?, path)
+        }
+        None => {
+            let rest = remote_url
+                .split_once('@')
+                .map_or(remote_url, |(_, host)| host);
+            rest.split_once(':')?
+        }
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+
+    if host == "github.com" {
+        Some(format!(
+            "https://{}/{}/compare/{}...{}",
+            host, path, base, head
+        ))
+    } else if host.split('.').any(|label| label == "gitlab") {
+        // GitLab 的仓库路径可以包含多级群组
+        Some(format!(
+            "https://{}/{}/-/compare/{}...{}",
+            host, path, base, head
+        ))
+    } else {
+        None
+    }
+}
+
+/// 将一个版本的更新日志插入到现有 CHANGELOG 内容中，新版本位于最前面
+pub fn prepend(content: &str, version: &str, notes: &str) -> String {
+    let section = format!("## {} ({})\n\n{}", version, today(), notes);
+
+    // 保留文件开头的一级标题
+    if let Some(rest) = content.strip_prefix(HEADER) {
+        let rest = rest.trim_start_matches(['\r', '\n']);
+        return format!("{}\n\n{}\n{}", HEADER, section, rest);
+    }
+    if content.is_empty() {
+        return format!("{}\n\n{}", HEADER, section);
+    }
+    format!("{}\n{}", section, content)
+}
+
+/// Keep a Changelog 格式中的 `## [Unreleased]` 部分
+pub struct Unreleased {
+    /// 标题行（包括换行）的位置
+    heading: Range<usize>,
+    /// 正文的位置，到下一个二级标题或文末的链接定义为止
+    body: Range<usize>,
+    /// 标题是否写成 `[Unreleased]`，新版本的标题沿用同样的写法
+    bracketed: bool,
+}
+
+impl Unreleased {
+    /// 去掉首尾空行的正文
+    pub fn notes<'a>(&self, content: &'a str) -> &'a str {
+        content[self.body.clone()].trim()
+    }
+}
+
+/// 查找 `## [Unreleased]`（或 `## Unreleased`，不区分大小写）部分
+pub fn find_unreleased(content: &str) -> Option<Unreleased> {
+    let mut offset = 0;
+    let mut section: Option<Unreleased> = None;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end();
+
+        if let Some(section) = &mut section {
+            if text.starts_with("## ") || is_link_definition(text) {
+                section.body.end = start;
+                break;
+            }
+            continue;
+        }
+
+        let Some(title) = text.strip_prefix("## ") else {
+            continue;
+        };
+        let title = title.trim();
+        let bracketed = title.starts_with('[');
+        let name = title.trim_start_matches('[');
+        let name = name.split(']').next().unwrap_or_default().trim();
+        if name.eq_ignore_ascii_case("unreleased") {
+            section = Some(Unreleased {
+                heading: start..offset,
+                body: offset..content.len(),
+                bracketed,
+            });
+        }
+    }
+    section
+}
+
+/// 文末的 `[name]: url` 链接定义
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .split_once("]:")
+            .is_some_and(|(name, _)| !name.contains(']'))
+}
+
+/// 将 Unreleased 部分改为新版本（标题为 `## [version] - 日期`），并在其上方插入新的空 Unreleased 部分
+///
+/// 文末有 `[Unreleased]: .../compare/v1.0.0...HEAD` 形式的链接时，将其改为从新标签开始比较，
+/// 并为新版本添加比较上一个标签的链接
+pub fn release_unreleased(content: &str, section: &Unreleased, version: &str, tag: &str) -> String {
+    let title = match section.bracketed {
+        true => format!("[{}]", version),
+        false => version.to_string(),
+    };
+    let rest = &content[section.body.end..];
+
+    let mut updated = String::with_capacity(content.len() + 64);
+    updated.push_str(&content[..section.heading.start]);
+    updated.push_str(content[section.heading.clone()].trim_end());
+    updated.push_str("\n\n");
+    updated.push_str(&format!(
+        "## {} - {}\n\n{}\n",
+        title,
+        today(),
+        section.notes(content)
+    ));
+    if !rest.is_empty() {
+        updated.push('\n');
+        updated.push_str(rest);
+    }
+
+    // 链接定义的格式由 Keep a Changelog 约定，无法识别时保持不变
+    let Ok(link) = Regex::new(
+        r"(?im)^\[unreleased\]:[ \t]*(?P<base>\S+/compare/)(?P<from>\S+?)\.\.\.(?P<head>[^\s]+)[ \t]*$",
+    ) else {
+        return updated;
+    };
+    link.replace(&updated, |captures: &regex::Captures| {
+        format!(
+            "[Unreleased]: {base}{tag}...{head}\n[{version}]: {base}{from}...{tag}",
+            base = &captures["base"],
+            from = &captures["from"],
+            head = &captures["head"],
+        )
+    })
+    .into_owned()
+}
+
+/// 当前 UTC 日期，格式为 YYYY-MM-DD
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 将 1970-01-01 以来的天数换算为公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
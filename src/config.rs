@@ -0,0 +1,595 @@
+//! `.git-release.toml` 配置文件
+//!
+//! 配置文件中的键与命令行长选项一致（`tag-prefix` 或 `tag_prefix` 均可），
+//! 加载时会被转换为命令行参数插在用户参数之前。优先级为：
+//! 命令行参数 > 配置文件 > 选项默认值。命令行中出现过的选项会完全忽略配置文件中的对应值，
+//! 列表类选项（如 `exclude`）也不会与配置文件合并。
+//!
+//! 同样的配置也可以写在当前目录 Cargo.toml 的 `[package.metadata.git-release]` 或
+//! `[workspace.metadata.git-release]` 中，优先级为：命令行参数 > `[package.metadata.git-release]` >
+//! `[workspace.metadata.git-release]` > `.git-release.toml` > 选项默认值。
+//! 同一个键以优先级最高的来源为准，不同来源的列表和表（如 `[[version-sinks]]`）不会合并：
+//!
+//! ```toml
+//! [workspace.metadata.git-release]
+//! tag-prefix = "release-"
+//! changelog = true
+//! ```
+//!
+//! `[[version-files]]` 没有对应的命令行选项，只能在配置文件中定义：
+//!
+//! ```toml
+//! [[version-files]]
+//! path = "Dockerfile"
+//! pattern = 'LABEL version="[^"]*"'
+//! replacement = 'LABEL version="{version}"'
+//! ```
+//!
+//! `[[version-sinks]]` 统一描述 Cargo.toml 之外需要同步版本号的文件，`type` 可以是
+//! `json`、`toml`、`yaml`（用 `key` 给出以 `.` 分隔的键路径，可以是列表）、`regex`（同 `[[version-files]]`）、
+//! `plain`（整个文件只包含版本号）或 `markdown`。`yaml` 只替换块格式映射中的单行标量，
+//! 保留注释和引号，适合 `.github/workflows/` 中的 `env`。`markdown` 只修改 `languages`
+//! （默认为 toml、bash、sh、shell 和 console）围栏代码块中本次发布的 crate 的版本号：
+//! toml 代码块中的 `mycrate = "1.2.3"`、`mycrate = { version = "1.2.3" }` 和
+//! `[dependencies.mycrate]` 下的 `version`，其他代码块中的 `cargo add`/`cargo install`
+//! 命令，正文中的版本号不受影响。`path` 可以是 glob，例如 `docs/*.md`。
+//! `regex` 条目（包括 `[[version-files]]`）可以用 `extensions` 限定文件的扩展名，
+//! 用 `max-matches` 限定每个文件中的最大匹配数，超过时报错以免误改过多内容；
+//! 看起来是二进制（含有 NUL 或不是 UTF-8）的文件会被跳过。
+//! 内置的 `tauri`、`package-json`、`package-lock`、`pyproject`、`version` 和
+//! `helm`（Chart.yaml 中的 `appVersion`）条目，以及使用 `--readme` 时的 `readme-install`（安装命令）和
+//! `readme-badge`（版本徽章）条目可以用同名条目覆盖，或用 `enabled = false` 禁用：
+//!
+//! ```toml
+//! [[version-sinks]]
+//! type = "json"
+//! path = "web/manifest.json"
+//! key = "version"
+//!
+//! [[version-sinks]]
+//! name = "pyproject"
+//! type = "toml"
+//! path = "python/pyproject.toml"
+//! key = ["project.version", "tool.poetry.version"]
+//!
+//! [[version-sinks]]
+//! type = "yaml"
+//! path = ".github/workflows/release.yml"
+//! key = "env.CRATE_VERSION"
+//!
+//! [[version-sinks]]
+//! type = "regex"
+//! path = "deploy/**/*"
+//! pattern = 'image: myapp:[0-9.]+'
+//! replacement = "image: myapp:{version}"
+//! extensions = ["yml", "yaml"]
+//! max-matches = 5
+//!
+//! [[version-sinks]]
+//! type = "markdown"
+//! path = "docs/*.md"
+//! languages = ["toml", "bash"]
+//!
+//! [[version-sinks]]
+//! name = "tauri"
+//! enabled = false
+//! ```
+//!
+//! `[tag-prefixes]` 为按 crate 分别打标签时的个别 crate 指定标签前缀，
+//! 未列出的 crate 仍按 `tag-prefix` 计算：
+//!
+//! ```toml
+//! tag-per-crate = true
+//!
+//! [tag-prefixes]
+//! mycrate = "core/v"
+//! ```
+
+use anyhow::{Result, anyhow};
+use clap::{Arg, ArgAction, CommandFactory};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Cli;
+
+pub const CONFIG_FILE: &str = ".git-release.toml";
+
+/// Cargo.toml 中存放配置的 metadata 表名
+const METADATA_KEY: &str = "git-release";
+const PACKAGE_METADATA: &str = "Cargo.toml 的 [package.metadata.git-release]";
+const WORKSPACE_METADATA: &str = "Cargo.toml 的 [workspace.metadata.git-release]";
+
+/// 只能在配置文件中设置的键
+const VERSION_FILES_KEY: &str = "version-files";
+const VERSION_SINKS_KEY: &str = "version-sinks";
+const TAG_PREFIXES_KEY: &str = "tag-prefixes";
+
+/// markdown 类型默认处理的代码块语言
+const DEFAULT_MARKDOWN_LANGUAGES: &[&str] = &["toml", "bash", "sh", "shell", "console"];
+
+/// 自定义的版本号替换规则，`replacement` 中的 `{version}` 会被替换为新版本号
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionFile {
+    pub path: PathBuf,
+    #[serde(with = "serde_regex")]
+    pub pattern: Regex,
+    pub replacement: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(rename = "max-matches", alias = "max_matches")]
+    pub max_matches: Option<usize>,
+}
+
+/// 需要同步版本号的非 Cargo 文件
+#[derive(Debug, Clone)]
+pub struct VersionSink {
+    /// 内置条目的名称，配置文件中的同名条目会覆盖它
+    pub name: Option<String>,
+    pub path: PathBuf,
+    pub format: SinkFormat,
+    /// 内置条目对应的文件不存在时不提示
+    pub builtin: bool,
+}
+
+/// 版本号在文件中的位置
+#[derive(Debug, Clone)]
+pub enum SinkFormat {
+    /// JSON 中的一个或多个键路径，只替换存在的键
+    Json(Vec<Vec<String>>),
+    /// TOML 中的一个或多个键路径，只替换存在的键
+    Toml(Vec<Vec<String>>),
+    /// YAML 中的一个或多个键路径，只替换存在的单行标量
+    Yaml(Vec<Vec<String>>),
+    /// 正则替换，`replacement` 中的 `{version}` 会被替换为新版本号
+    ///
+    /// `extensions` 不为空时只处理这些扩展名的文件，匹配数超过 `max_matches` 时报错
+    Regex {
+        pattern: Regex,
+        replacement: String,
+        extensions: Vec<String>,
+        max_matches: Option<usize>,
+    },
+    /// 整个文件只包含版本号
+    Plain,
+    /// Markdown 中指定语言的围栏代码块里依赖和安装命令中的版本号，不修改正文
+    Markdown(Vec<String>),
+}
+
+/// `[[version-sinks]]` 和 `[[version-files]]` 中的一项
+#[derive(Debug, Clone)]
+pub enum SinkEntry {
+    /// 新增的条目，与内置条目同名时覆盖内置条目
+    Sink(VersionSink),
+    /// `enabled = false`，禁用同名的内置条目
+    Disabled(String),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SinkKind {
+    Json,
+    Toml,
+    Yaml,
+    Regex,
+    Plain,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSink {
+    name: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<SinkKind>,
+    path: Option<PathBuf>,
+    key: Option<Keys>,
+    pattern: Option<String>,
+    replacement: Option<String>,
+    extensions: Option<Vec<String>>,
+    #[serde(rename = "max-matches", alias = "max_matches")]
+    max_matches: Option<usize>,
+    languages: Option<Vec<String>>,
+    enabled: Option<bool>,
+}
+
+impl RawSink {
+    fn into_entry(self) -> Result<SinkEntry, String> {
+        if self.enabled == Some(false) {
+            return self
+                .name
+                .map(SinkEntry::Disabled)
+                .ok_or_else(|| "enabled = false 时需要指定 name".to_string());
+        }
+
+        let kind = self.kind.ok_or("缺少 type")?;
+        let path = self.path.ok_or("缺少 path")?;
+        let keys = || -> Result<Vec<Vec<String>>, String> {
+            let keys = match self.key.clone() {
+                Some(Keys::One(key)) => vec![key],
+                Some(Keys::Many(keys)) => keys,
+                None => Vec::new(),
+            };
+            if keys.is_empty() {
+                return Err("json、toml 和 yaml 类型需要指定 key".to_string());
+            }
+            Ok(keys
+                .iter()
+                .map(|key| key.split('.').map(str::to_string).collect())
+                .collect())
+        };
+        let format = match kind {
+            SinkKind::Json => SinkFormat::Json(keys()?),
+            SinkKind::Toml => SinkFormat::Toml(keys()?),
+            SinkKind::Yaml => SinkFormat::Yaml(keys()?),
+            SinkKind::Regex => {
+                let pattern = self.pattern.ok_or("regex 类型需要指定 pattern")?;
+                SinkFormat::Regex {
+                    pattern: Regex::new(&pattern).map_err(|error| error.to_string())?,
+                    replacement: self.replacement.ok_or("regex 类型需要指定 replacement")?,
+                    extensions: normalize_extensions(self.extensions.unwrap_or_default()),
+                    max_matches: self.max_matches,
+                }
+            }
+            SinkKind::Plain => SinkFormat::Plain,
+            SinkKind::Markdown => SinkFormat::Markdown(
+                self.languages
+                    .unwrap_or_else(|| {
+                        DEFAULT_MARKDOWN_LANGUAGES
+                            .iter()
+                            .map(|language| language.to_string())
+                            .collect()
+                    })
+                    .iter()
+                    .map(|language| language.to_ascii_lowercase())
+                    .collect(),
+            ),
+        };
+        Ok(SinkEntry::Sink(VersionSink {
+            name: self.name,
+            path,
+            format,
+            builtin: false,
+        }))
+    }
+}
+
+/// 扩展名可以写成 `md` 或 `.md`，比较时不区分大小写
+fn normalize_extensions(extensions: Vec<String>) -> Vec<String> {
+    extensions
+        .into_iter()
+        .map(|extension| extension.trim_start_matches('.').to_ascii_lowercase())
+        .collect()
+}
+
+mod serde_regex {
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer, de::Error};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map_err(D::Error::custom)
+    }
+}
+
+/// 合并后的配置，键名统一为 `-` 分隔的形式
+struct Config {
+    table: toml::Table,
+    /// 每个键来自的位置，用于错误信息
+    sources: HashMap<String, &'static str>,
+}
+
+impl Config {
+    fn source(&self, key: &str) -> &'static str {
+        self.sources.get(key).copied().unwrap_or(CONFIG_FILE)
+    }
+
+    /// 取出一个键的值和它来自的位置
+    fn take(&mut self, key: &str) -> Option<(toml::Value, &'static str)> {
+        let value = self.table.remove(key)?;
+        Some((value, self.source(key)))
+    }
+
+    /// 用优先级更高的来源中的键覆盖已有的键
+    fn overlay(&mut self, table: toml::Table, source: &'static str) {
+        for (key, value) in table {
+            let key = key.replace('_', "-");
+            self.sources.insert(key.clone(), source);
+            self.table.insert(key, value);
+        }
+    }
+}
+
+/// 按优先级从低到高合并 .git-release.toml 和 Cargo.toml 中的 metadata，都没有时返回 `None`
+fn load() -> Result<Option<Config>> {
+    let mut config = Config {
+        table: toml::Table::new(),
+        sources: HashMap::new(),
+    };
+    let mut found = false;
+
+    let config_path = Path::new(CONFIG_FILE);
+    if config_path.exists() {
+        let content = fs::read_to_string(config_path)?;
+        let table = toml::from_str(&content)
+            .map_err(|error| anyhow!("解析 {} 失败: {}", CONFIG_FILE, error))?;
+        config.overlay(table, CONFIG_FILE);
+        found = true;
+    }
+
+    let manifest_path = Path::new("Cargo.toml");
+    if manifest_path.is_file() {
+        // 只读取 metadata，清单其余部分的问题留给发布流程报告
+        let content = fs::read_to_string(manifest_path)?;
+        let manifest: toml::Table = toml::from_str(&content)
+            .map_err(|error| anyhow!("解析 {} 失败: {}", manifest_path.display(), error))?;
+        for (section, source) in [
+            ("workspace", WORKSPACE_METADATA),
+            ("package", PACKAGE_METADATA),
+        ] {
+            let Some(metadata) = manifest
+                .get(section)
+                .and_then(|section| section.get("metadata"))
+                .and_then(|metadata| metadata.get(METADATA_KEY))
+            else {
+                continue;
+            };
+            let toml::Value::Table(table) = metadata else {
+                return Err(anyhow!("{} 应为表", source));
+            };
+            config.overlay(table.clone(), source);
+            found = true;
+        }
+    }
+
+    Ok(found.then_some(config))
+}
+
+fn take_section<T: serde::de::DeserializeOwned>(config: &mut Config, key: &str) -> Result<Vec<T>> {
+    let Some((value, source)) = config.take(key) else {
+        return Ok(Vec::new());
+    };
+    value
+        .try_into()
+        .map_err(|error| anyhow!("{} 中 {} 的格式不正确: {}", source, key, error))
+}
+
+/// 读取配置文件中的 `[[version-sinks]]` 和 `[[version-files]]` 规则，后者排在最后
+pub fn version_sinks() -> Result<Vec<SinkEntry>> {
+    let Some(mut config) = load()? else {
+        return Ok(Vec::new());
+    };
+
+    let source = config.source(VERSION_SINKS_KEY);
+    let mut entries = Vec::new();
+    for (index, raw) in take_section::<RawSink>(&mut config, VERSION_SINKS_KEY)?
+        .into_iter()
+        .enumerate()
+    {
+        let entry = raw.into_entry().map_err(|error| {
+            anyhow!(
+                "{} 中 {} 的第 {} 项不正确: {}",
+                source,
+                VERSION_SINKS_KEY,
+                index + 1,
+                error
+            )
+        })?;
+        entries.push(entry);
+    }
+
+    for rule in take_section::<VersionFile>(&mut config, VERSION_FILES_KEY)? {
+        entries.push(SinkEntry::Sink(VersionSink {
+            name: None,
+            path: rule.path,
+            format: SinkFormat::Regex {
+                pattern: rule.pattern,
+                replacement: rule.replacement,
+                extensions: normalize_extensions(rule.extensions),
+                max_matches: rule.max_matches,
+            },
+            builtin: false,
+        }));
+    }
+    Ok(entries)
+}
+
+/// 读取配置文件中的 `[tag-prefixes]`，按配置文件中的顺序返回 (crate 名, 标签前缀)
+pub fn tag_prefixes() -> Result<Vec<(String, String)>> {
+    let Some(mut config) = load()? else {
+        return Ok(Vec::new());
+    };
+    let Some((value, source)) = config.take(TAG_PREFIXES_KEY) else {
+        return Ok(Vec::new());
+    };
+    let toml::Value::Table(prefixes) = value else {
+        return Err(anyhow!("{} 中 {} 应为表", source, TAG_PREFIXES_KEY));
+    };
+
+    prefixes
+        .into_iter()
+        .map(|(name, prefix)| match prefix {
+            toml::Value::String(prefix) => Ok((name, prefix)),
+            _ => Err(anyhow!(
+                "{} 中 {} 的 {} 应为字符串",
+                source,
+                TAG_PREFIXES_KEY,
+                name
+            )),
+        })
+        .collect()
+}
+
+/// 用配置文件中的条目覆盖、禁用或追加到内置条目
+///
+/// 与内置条目同名的第一个条目替换所有同名的内置条目，其余条目按顺序追加到末尾
+pub fn merge_sinks(defaults: Vec<VersionSink>, entries: &[SinkEntry]) -> Vec<VersionSink> {
+    let builtin_names: Vec<String> = defaults
+        .iter()
+        .filter_map(|sink| sink.name.clone())
+        .collect();
+    let is_builtin =
+        |sink: &VersionSink, name: &str| sink.builtin && sink.name.as_deref() == Some(name);
+
+    let mut sinks = defaults;
+    let mut overridden: Vec<&str> = Vec::new();
+    for entry in entries {
+        let sink = match entry {
+            SinkEntry::Disabled(name) => {
+                sinks.retain(|sink| !is_builtin(sink, name));
+                continue;
+            }
+            SinkEntry::Sink(sink) => sink,
+        };
+
+        let name = sink
+            .name
+            .as_deref()
+            .filter(|name| builtin_names.iter().any(|builtin| builtin == name))
+            .filter(|name| !overridden.contains(name));
+        let Some(name) = name else {
+            sinks.push(sink.clone());
+            continue;
+        };
+
+        // 放在第一个被覆盖的内置条目的位置，保持内置条目之间的更新顺序
+        let position = sinks.iter().position(|existing| is_builtin(existing, name));
+        sinks.retain(|existing| !is_builtin(existing, name));
+        sinks.insert(position.unwrap_or(sinks.len()), sink.clone());
+        overridden.push(name);
+    }
+    sinks
+}
+
+/// 将配置文件和 Cargo.toml metadata 中的选项转换为命令行参数，并与用户参数合并
+///
+/// 给出了子命令时，子命令自己的选项添加在参数末尾，由子命令解析，
+/// 其余选项插入到子命令之前，作为合并子命令选项前的默认值
+pub fn args_with_config<I>(args: I, subcommand: Option<&str>) -> Result<Vec<OsString>>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut args: Vec<OsString> = args.into_iter().collect();
+
+    let Some(config) = load()? else {
+        return Ok(args);
+    };
+
+    let mut command = Cli::command();
+    command.build();
+    let subcommand = subcommand.and_then(|name| command.find_subcommand(name));
+    let (root_args, subcommand_args) = config_to_args(&config, &args, &command, subcommand)?;
+    // -- 之后的参数都是位置参数
+    let end = args
+        .iter()
+        .skip(1)
+        .position(|arg| arg == "--")
+        .map_or(args.len(), |position| position + 1);
+    args.splice(end..end, subcommand_args);
+    let insert_at = args.len().min(1);
+    args.splice(insert_at..insert_at, root_args);
+    Ok(args)
+}
+
+/// 返回插入到子命令之前和由子命令解析的参数
+fn config_to_args(
+    config: &Config,
+    user_args: &[OsString],
+    command: &clap::Command,
+    subcommand: Option<&clap::Command>,
+) -> Result<(Vec<OsString>, Vec<OsString>)> {
+    let mut root_args = Vec::new();
+    let mut subcommand_args = Vec::new();
+
+    for (long, value) in &config.table {
+        if long == VERSION_FILES_KEY || long == VERSION_SINKS_KEY || long == TAG_PREFIXES_KEY {
+            continue;
+        }
+        let source = config.source(long);
+        let (arg, config_args) = match subcommand.and_then(|command| find_arg(command, long)) {
+            Some(arg) => (arg, &mut subcommand_args),
+            None => (
+                find_arg(command, long)
+                    .ok_or_else(|| anyhow!("{} 中存在未知选项: {}", source, long))?,
+                &mut root_args,
+            ),
+        };
+
+        // 命令行参数优先
+        if is_given(arg, user_args) {
+            continue;
+        }
+
+        let flag = format!("--{}", long);
+        match (arg.get_action(), value) {
+            (ArgAction::SetTrue, toml::Value::Boolean(enabled)) => {
+                if *enabled {
+                    config_args.push(flag.into());
+                }
+            }
+            (ArgAction::Count, toml::Value::Integer(count)) => {
+                for _ in 0..*count {
+                    config_args.push(flag.clone().into());
+                }
+            }
+            (ArgAction::Append, toml::Value::Array(values)) => {
+                for value in values {
+                    config_args.push(format!("{}={}", flag, scalar(source, long, value)?).into());
+                }
+            }
+            (ArgAction::Set | ArgAction::Append, value) => {
+                config_args.push(format!("{}={}", flag, scalar(source, long, value)?).into());
+            }
+            _ => return Err(anyhow!("{} 中选项 {} 的值类型不正确", source, long)),
+        }
+    }
+
+    Ok((root_args, subcommand_args))
+}
+
+fn find_arg<'a>(command: &'a clap::Command, long: &str) -> Option<&'a Arg> {
+    command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(long))
+}
+
+fn scalar(source: &str, key: &str, value: &toml::Value) -> Result<String> {
+    match value {
+        toml::Value::String(value) => Ok(value.clone()),
+        toml::Value::Integer(value) => Ok(value.to_string()),
+        toml::Value::Float(value) => Ok(value.to_string()),
+        toml::Value::Boolean(value) => Ok(value.to_string()),
+        _ => Err(anyhow!("{} 中选项 {} 的值类型不正确", source, key)),
+    }
+}
+
+/// 判断用户是否已在命令行中给出该选项
+fn is_given(arg: &Arg, user_args: &[OsString]) -> bool {
+    let long = arg.get_long().map(|long| format!("--{}", long));
+    let short = arg.get_short().map(|short| format!("-{}", short));
+
+    user_args
+        .iter()
+        .skip(1)
+        .filter_map(|user_arg| user_arg.to_str())
+        .take_while(|user_arg| *user_arg != "--")
+        .any(|user_arg| {
+            let long_given = long.as_deref().is_some_and(|long| {
+                user_arg == long || user_arg.starts_with(&format!("{}=", long))
+            });
+            let short_given = short
+                .as_deref()
+                .is_some_and(|short| user_arg.starts_with(short) && !user_arg.starts_with("--"));
+            long_given || short_given
+        })
+}
@@ -0,0 +1,39 @@
+//! Conventional Commits 解析
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::version::Bump;
+
+static HEADER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?P<type>[a-zA-Z]+)(\([^)]*\))?(?P<breaking>!)?:\s").unwrap());
+
+/// 提交标题中的类型（小写），不符合 Conventional Commits 格式时返回 `None`
+pub fn commit_type(message: &str) -> Option<String> {
+    let header = message.lines().next()?;
+    let captures = HEADER_RE.captures(header)?;
+    Some(captures["type"].to_ascii_lowercase())
+}
+
+/// 根据提交信息判断需要的版本递增级别，不属于可发布类型时返回 `None`
+///
+/// `feat` 对应 minor，`fix`/`perf` 对应 patch，标题中的 `!` 或正文中的
+/// `BREAKING CHANGE:` 对应 major。
+pub fn classify(message: &str) -> Option<Bump> {
+    let header = message.lines().next()?;
+    let captures = HEADER_RE.captures(header)?;
+
+    let breaking = captures.name("breaking").is_some()
+        || message.lines().any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+    if breaking {
+        return Some(Bump::Major);
+    }
+
+    match captures["type"].to_ascii_lowercase().as_str() {
+        "feat" => Some(Bump::Minor),
+        "fix" | "perf" => Some(Bump::Patch),
+        _ => None,
+    }
+}
@@ -0,0 +1,137 @@
+//! 错误分类和进程退出码
+//!
+//! | 退出码 | 含义 |
+//! | ------ | ---- |
+//! | 0 | 发布成功，或 --auto 模式下没有需要发布的提交 |
+//! | 1 | 其他错误 |
+//! | 2 | 发布前的检查未通过（版本号、标签、工作区状态等） |
+//! | 3 | git 操作失败（提交、创建标签、推送等） |
+//! | 4 | 使用 --tag-only-if-changed 时没有需要发布的内容 |
+//!
+//! [`ReleaseTool`](crate::ReleaseTool) 的公开方法返回 [`ReleaseError`]，嵌入本库时可以按变体区分错误，
+//! 内部仍使用 `anyhow`，在公开方法的边界上转换。
+
+use std::fmt;
+
+use crate::git::PushError;
+
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_VALIDATION: i32 = 2;
+pub const EXIT_GIT: i32 = 3;
+pub const EXIT_NOTHING_TO_RELEASE: i32 = 4;
+
+/// 发布失败的原因，字符串为面向用户的错误信息
+#[derive(Debug)]
+pub enum ReleaseError {
+    /// 发布前的其他检查未通过
+    Validation(String),
+    /// 其他 git 操作失败
+    Git(String),
+    /// 工作区有未提交的更改
+    DirtyTree(String),
+    /// 本地或远程仓库上已存在同名标签
+    TagExists(String),
+    /// 当前目录不是 git 仓库
+    NotAGitRepo(String),
+    /// 版本号格式不正确
+    InvalidVersion(String),
+    /// 推送到远程仓库失败
+    PushFailed(PushError),
+    /// 使用 --tag-only-if-changed 时没有需要发布的内容，不是真正的失败
+    NothingToRelease(String),
+    /// 其他错误
+    Other(anyhow::Error),
+}
+
+impl ReleaseError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Validation(_)
+            | Self::DirtyTree(_)
+            | Self::TagExists(_)
+            | Self::InvalidVersion(_) => EXIT_VALIDATION,
+            Self::Git(_) | Self::NotAGitRepo(_) | Self::PushFailed(_) => EXIT_GIT,
+            Self::NothingToRelease(_) => EXIT_NOTHING_TO_RELEASE,
+            Self::Other(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for ReleaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(message)
+            | Self::Git(message)
+            | Self::DirtyTree(message)
+            | Self::TagExists(message)
+            | Self::NotAGitRepo(message)
+            | Self::InvalidVersion(message)
+            | Self::NothingToRelease(message) => f.write_str(message),
+            Self::PushFailed(error) => error.fmt(f),
+            Self::Other(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReleaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::PushFailed(error) => Some(error),
+            Self::Other(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ReleaseError {
+    /// 取出内部代码中以 `anyhow::Error` 传递的分类错误
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ReleaseError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        match error.downcast::<PushError>() {
+            Ok(error) => Self::PushFailed(error),
+            Err(error) => Self::Other(error),
+        }
+    }
+}
+
+pub fn validation(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::Validation(message.into()).into()
+}
+
+pub fn git(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::Git(message.into()).into()
+}
+
+pub fn dirty_tree(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::DirtyTree(message.into()).into()
+}
+
+pub fn tag_exists(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::TagExists(message.into()).into()
+}
+
+pub fn not_a_git_repo(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::NotAGitRepo(message.into()).into()
+}
+
+pub fn invalid_version(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::InvalidVersion(message.into()).into()
+}
+
+pub fn nothing_to_release(message: impl Into<String>) -> anyhow::Error {
+    ReleaseError::NothingToRelease(message.into()).into()
+}
+
+/// 错误对应的进程退出码
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    if let Some(error) = error.downcast_ref::<ReleaseError>() {
+        return error.exit_code();
+    }
+    if error.downcast_ref::<PushError>().is_some() {
+        return EXIT_GIT;
+    }
+    EXIT_FAILURE
+}
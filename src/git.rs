@@ -0,0 +1,698 @@
+//! Git 操作抽象
+//!
+//! 本地仓库的查询和标签操作通过 libgit2 完成，不依赖 git 命令的输出格式和语言环境；
+//! 推送等网络操作以及签名仍交给 git 命令，以复用用户配置的凭据、SSH 和签名程序。
+
+use anyhow::Result;
+use git2::{
+    DescribeFormatOptions, DescribeOptions, ErrorClass, ErrorCode, ObjectType, Repository,
+    StatusOptions, TreeWalkMode, TreeWalkResult,
+};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, Stdio};
+
+use crate::error;
+
+/// 创建标签的参数
+#[derive(Debug, Clone, Default)]
+pub struct TagRequest {
+    pub name: String,
+    /// 标签指向的提交，`None` 表示 HEAD
+    pub target: Option<String>,
+    /// 附注信息，`None` 表示创建轻量标签
+    pub message: Option<String>,
+    pub sign: bool,
+    pub signing_key: Option<String>,
+}
+
+impl TagRequest {
+    /// 等价的 `git tag` 参数
+    pub fn command_args(&self) -> Vec<&str> {
+        let mut args = vec!["tag"];
+        match (&self.signing_key, self.sign) {
+            (Some(key), true) => args.extend(["-u", key]),
+            (None, true) => args.push("-s"),
+            _ if self.message.is_some() => args.push("-a"),
+            _ => {}
+        }
+        if let Some(message) = &self.message {
+            args.extend(["-m", message]);
+        }
+        args.push(&self.name);
+        if let Some(target) = &self.target {
+            args.push(target);
+        }
+        args
+    }
+}
+
+/// 当前分支跟踪的远程分支
+#[derive(Debug, Clone)]
+pub struct Upstream {
+    pub remote: String,
+    /// 完整引用名，例如 `refs/remotes/origin/main`
+    pub reference: String,
+}
+
+/// 执行 git 命令，失败时返回以 `message` 开头的 git 错误
+///
+/// `quiet` 时捕获 git 的输出，成功时丢弃，失败时将 git 的错误输出附在错误信息后
+pub fn run(command: &mut StdCommand, quiet: bool, message: impl FnOnce() -> String) -> Result<()> {
+    run_with_input(command, quiet, None, message)
+}
+
+/// 同 [`run`]，并将 `input` 写入 git 的标准输入
+pub fn run_with_input(
+    command: &mut StdCommand,
+    quiet: bool,
+    input: Option<&str>,
+    message: impl FnOnce() -> String,
+) -> Result<()> {
+    if quiet {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    if input.is_some() {
+        command.stdin(Stdio::piped());
+    }
+
+    let mut child = command.spawn()?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let message = message();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = match stderr.trim() {
+        "" => stdout.trim(),
+        stderr => stderr,
+    };
+    if detail.is_empty() {
+        return Err(error::git(message));
+    }
+    Err(error::git(format!("{}\n{}", message, detail)))
+}
+
+/// 将 git 命令渲染为可以直接粘贴到 shell 中执行的文本
+pub fn render_command(args: &[&str]) -> String {
+    let mut rendered = String::from("git");
+    for arg in args {
+        rendered.push(' ');
+        let safe = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=@+,%^{}".contains(c));
+        if safe {
+            rendered.push_str(arg);
+        } else {
+            rendered.push_str(&format!("'{}'", arg.replace('\'', "'\\''")));
+        }
+    }
+    rendered
+}
+
+/// 按 git check-ref-format 的规则检查标签名
+pub fn check_tag_name(name: &str) -> Result<()> {
+    check_ref_name("标签名", name)
+}
+
+/// 按 git check-ref-format 的规则检查分支名
+pub fn check_branch_name(name: &str) -> Result<()> {
+    check_ref_name("分支名", name)
+}
+
+fn check_ref_name(kind: &str, name: &str) -> Result<()> {
+    let mut invalid = Vec::new();
+    for c in name.chars() {
+        if (c.is_ascii_control() || " ~^:?*[\\".contains(c)) && !invalid.contains(&c) {
+            invalid.push(c);
+        }
+    }
+    if !invalid.is_empty() {
+        let chars: Vec<String> = invalid.iter().map(|c| format!("{:?}", c)).collect();
+        return Err(error::validation(format!(
+            "{} {:?} 包含 git 不允许的字符: {}",
+            kind,
+            name,
+            chars.join(" ")
+        )));
+    }
+
+    let reason = if name.is_empty() {
+        Some("不能为空")
+    } else if name == "@" {
+        Some("不能为 @")
+    } else if name.contains("..") {
+        Some("不能包含 ..")
+    } else if name.contains("@{") {
+        Some("不能包含 @{")
+    } else if name.starts_with('-') {
+        Some("不能以 - 开头")
+    } else if name
+        .split('/')
+        .any(|part| part.is_empty() || part.starts_with('.') || part.ends_with(".lock"))
+    {
+        Some("各级路径不能为空、以 . 开头或以 .lock 结尾")
+    } else if name.ends_with('.') {
+        Some("不能以 . 结尾")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => Err(error::validation(format!(
+            "{} {:?} 无效: {}",
+            kind, name, reason
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 推送失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushErrorKind {
+    /// 被远程拒绝（非快进、认证失败等）
+    Rejected,
+    /// 被远程的分支/标签保护规则拒绝
+    Protected,
+    /// 要删除的引用在远程不存在
+    MissingRef,
+    /// 网络等临时错误
+    Other,
+}
+
+/// 推送失败
+#[derive(Debug)]
+pub struct PushError {
+    pub remote: String,
+    pub kind: PushErrorKind,
+    /// git 的错误输出
+    pub detail: String,
+}
+
+impl PushError {
+    /// 被远程明确拒绝，重试也不会成功
+    pub fn is_rejected(&self) -> bool {
+        self.kind != PushErrorKind::Other
+    }
+}
+
+impl std::fmt::Display for PushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            PushErrorKind::Rejected => write!(f, "推送到 {} 被拒绝", self.remote),
+            PushErrorKind::Protected => write!(f, "推送到 {} 被保护规则拒绝", self.remote),
+            PushErrorKind::MissingRef => write!(f, "{} 上不存在要删除的引用", self.remote),
+            PushErrorKind::Other => write!(f, "推送到 {} 失败", self.remote),
+        }
+    }
+}
+
+impl std::error::Error for PushError {}
+
+/// 提交的基本信息
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub id: String,
+    pub message: String,
+}
+
+impl CommitInfo {
+    /// 提交信息的第一行
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// 发布流程用到的 Git 操作
+pub trait Git {
+    /// 当前目录是否位于 git 仓库中
+    fn is_repository(&self) -> bool;
+
+    /// 仓库工作区的根目录（相当于 `git rev-parse --show-toplevel`）
+    fn toplevel(&self) -> Result<PathBuf>;
+
+    /// 工作区是否没有任何改动，`untracked` 为 false 时忽略未跟踪的文件
+    fn is_clean(&self, untracked: bool) -> Result<bool>;
+
+    /// .gitmodules 中登记的子模块目录，路径相对于仓库根目录
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>>;
+
+    /// 工作区中有改动的文件（不包括被忽略的文件），`untracked` 为 false 时不包括未跟踪的文件
+    fn dirty_paths(&self, untracked: bool) -> Result<Vec<PathBuf>>;
+
+    /// 将引用解析为提交 ID
+    fn resolve_commit(&self, rev: &str) -> Result<String>;
+
+    /// 当前分支名，HEAD 处于分离状态时返回 `None`
+    fn current_branch(&self) -> Result<Option<String>>;
+
+    /// 当前分支跟踪的远程分支，未设置时返回 `None`
+    fn upstream(&self) -> Result<Option<Upstream>>;
+
+    /// 返回 `local` 相对 `upstream` 领先和落后的提交数
+    fn ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize)>;
+
+    /// 执行 `git fetch <remote> <args>`
+    fn fetch(&self, remote: &str, args: &[&str]) -> Result<()>;
+
+    /// 从 `rev` 可达的、以 `prefix` 开头的最近标签
+    fn last_tag(&self, rev: &str, prefix: &str) -> Result<Option<String>>;
+
+    /// `since` 之后到 `rev` 的提交（从新到旧），`since` 为 `None` 时返回全部历史
+    fn commits_since(&self, since: Option<&str>, rev: &str) -> Result<Vec<CommitInfo>>;
+
+    /// `since` 到 `rev` 之间修改过的文件（相当于 `git diff --name-only <since>..<rev>`），
+    /// 路径相对于仓库根目录，重命名的文件同时包含新旧路径
+    fn changed_paths(&self, since: &str, rev: &str) -> Result<Vec<PathBuf>>;
+
+    /// `rev` 中文件的内容（相当于 `git show <rev>:<path>`），路径相对于仓库根目录，
+    /// 文件不存在时返回 `None`
+    fn read_file(&self, rev: &str, path: &Path) -> Result<Option<String>>;
+
+    /// `rev` 的目录树中的全部文件（相当于 `git ls-tree -r --name-only <rev>`），
+    /// 路径相对于仓库根目录
+    fn tree_files(&self, rev: &str) -> Result<Vec<PathBuf>>;
+
+    fn tag_exists(&self, name: &str) -> Result<bool>;
+
+    fn create_tag(&self, request: &TagRequest) -> Result<()>;
+
+    fn delete_tag(&self, name: &str) -> Result<()>;
+
+    fn remotes(&self) -> Result<Vec<String>>;
+
+    /// 远程仓库的地址，远程仓库不存在时返回 `None`
+    fn remote_url(&self, name: &str) -> Result<Option<String>>;
+
+    /// 读取 git 配置项（例如 `user.name`），未设置时返回 `None`
+    fn config(&self, key: &str) -> Result<Option<String>>;
+
+    /// 执行 `git push <remote> <args>`
+    fn push(&self, remote: &str, args: &[&str]) -> Result<()>;
+
+    /// 执行 `git push --atomic <remote> <refspecs>`，远程不支持原子推送时返回 `false`
+    fn push_atomic(&self, remote: &str, refspecs: &[&str]) -> Result<bool>;
+
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()>;
+
+    /// 远程仓库上是否已存在指定标签
+    fn remote_tag_exists(&self, remote: &str, name: &str) -> Result<bool>;
+}
+
+/// 基于 libgit2 的实现
+pub struct Git2Backend {
+    path: PathBuf,
+    git_bin: PathBuf,
+    quiet: bool,
+}
+
+impl Git2Backend {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            git_bin: PathBuf::from("git"),
+            quiet: false,
+        }
+    }
+
+    /// 指定执行网络和签名操作时使用的 git 可执行文件
+    pub fn with_git_bin(mut self, git_bin: impl Into<PathBuf>) -> Self {
+        self.git_bin = git_bin.into();
+        self
+    }
+
+    /// 捕获 git 命令的输出，只在失败时显示
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    fn repo(&self) -> Result<Repository> {
+        Repository::discover(&self.path).map_err(|_| error::not_a_git_repo("当前目录不是 git 仓库"))
+    }
+
+    fn git(&self) -> StdCommand {
+        let mut git = StdCommand::new(&self.git_bin);
+        git.current_dir(&self.path);
+        git
+    }
+
+    /// 执行 `git push <args>`，失败时返回 [`PushError`] 并附带 git 的错误输出
+    fn run_push(&self, remote: &str, args: &[&str]) -> Result<()> {
+        let output = self
+            .git()
+            .arg("push")
+            .args(args)
+            // 固定语言环境以便识别错误信息
+            .env("LC_ALL", "C")
+            .output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !self.quiet || !output.status.success() {
+            crate::progress::suspend(|| eprint!("{}", stderr));
+        }
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let contains_any =
+            |patterns: &[&str]| patterns.iter().any(|pattern| stderr.contains(pattern));
+        // 认证失败的提示中也有 denied，需要先于保护规则判断
+        let kind = if contains_any(&["remote ref does not exist"]) {
+            PushErrorKind::MissingRef
+        } else if contains_any(&["Permission denied", "Authentication failed"]) {
+            PushErrorKind::Rejected
+        } else if contains_any(&[
+            "protected",
+            "denied",
+            "denying",
+            "prohibited",
+            "not allowed",
+        ]) {
+            PushErrorKind::Protected
+        } else if contains_any(&["[rejected]", "[remote rejected]", "non-fast-forward"]) {
+            PushErrorKind::Rejected
+        } else {
+            PushErrorKind::Other
+        };
+        Err(PushError {
+            remote: remote.to_string(),
+            kind,
+            detail: stderr.into_owned(),
+        }
+        .into())
+    }
+}
+
+impl Git for Git2Backend {
+    fn is_repository(&self) -> bool {
+        self.repo().is_ok_and(|repo| !repo.is_bare())
+    }
+
+    fn toplevel(&self) -> Result<PathBuf> {
+        let repo = self.repo()?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| error::git("裸仓库没有工作区"))?;
+        Ok(fs::canonicalize(workdir)?)
+    }
+
+    fn is_clean(&self, untracked: bool) -> Result<bool> {
+        Ok(self.dirty_paths(untracked)?.is_empty())
+    }
+
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>> {
+        let repo = self.repo()?;
+        Ok(repo
+            .submodules()?
+            .iter()
+            .map(|submodule| submodule.path().to_path_buf())
+            .collect())
+    }
+
+    fn dirty_paths(&self, untracked: bool) -> Result<Vec<PathBuf>> {
+        // 相当于 git status --porcelain 的输出，untracked 为 false 时去掉 ?? 开头的行：
+        // 只存在于工作区、从未加入索引的文件（WT_NEW）。已暂存的新文件（A）仍算作改动
+        let repo = self.repo()?;
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(untracked)
+            .recurse_untracked_dirs(untracked)
+            .include_ignored(false);
+
+        Ok(repo
+            .statuses(Some(&mut options))?
+            .iter()
+            .filter_map(|entry| entry.path().ok().map(PathBuf::from))
+            .collect())
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let repo = self.repo()?;
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| error::git(format!("无法解析提交: {}", rev)))?;
+        Ok(commit.id().to_string())
+    }
+
+    fn current_branch(&self) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        if repo.head_detached()? {
+            return Ok(None);
+        }
+        let head = repo.head()?;
+        Ok(Some(head.shorthand()?.to_string()))
+    }
+
+    fn upstream(&self) -> Result<Option<Upstream>> {
+        let repo = self.repo()?;
+        if repo.head_detached()? {
+            return Ok(None);
+        }
+        let head = repo.head()?;
+        let branch_ref = head.name()?;
+
+        let (Ok(reference), Ok(remote)) = (
+            repo.branch_upstream_name(branch_ref),
+            repo.branch_upstream_remote(branch_ref),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Upstream {
+            remote: remote.as_str()?.to_string(),
+            reference: reference.as_str()?.to_string(),
+        }))
+    }
+
+    fn ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize)> {
+        let repo = self.repo()?;
+        let local = repo.revparse_single(local)?.peel_to_commit()?.id();
+        let upstream = repo.revparse_single(upstream)?.peel_to_commit()?.id();
+        Ok(repo.graph_ahead_behind(local, upstream)?)
+    }
+
+    fn fetch(&self, remote: &str, args: &[&str]) -> Result<()> {
+        run(
+            self.git().arg("fetch").arg(remote).args(args),
+            self.quiet,
+            || format!("从 {} 获取更新失败", remote),
+        )
+    }
+
+    fn last_tag(&self, rev: &str, prefix: &str) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let mut options = DescribeOptions::new();
+        options.describe_tags().pattern(&format!("{}*", prefix));
+
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+        match commit.as_object().describe(&options) {
+            Ok(describe) => {
+                let mut format = DescribeFormatOptions::new();
+                format.abbreviated_size(0);
+                Ok(Some(describe.format(Some(&format))?))
+            }
+            // 没有任何匹配的标签时 libgit2 报告的是 Describe 类错误而不是 NotFound
+            Err(error)
+                if error.code() == ErrorCode::NotFound || error.class() == ErrorClass::Describe =>
+            {
+                Ok(None)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn commits_since(&self, since: Option<&str>, rev: &str) -> Result<Vec<CommitInfo>> {
+        let repo = self.repo()?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(repo.revparse_single(rev)?.peel_to_commit()?.id())?;
+        if let Some(since) = since {
+            let since = repo.revparse_single(since)?.peel_to_commit()?.id();
+            revwalk.hide(since)?;
+        }
+
+        let mut commits = Vec::new();
+        for id in revwalk {
+            let commit = repo.find_commit(id?)?;
+            commits.push(CommitInfo {
+                id: commit.id().to_string(),
+                message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+            });
+        }
+        Ok(commits)
+    }
+
+    fn changed_paths(&self, since: &str, rev: &str) -> Result<Vec<PathBuf>> {
+        let repo = self.repo()?;
+        let old_tree = repo.revparse_single(since)?.peel_to_tree()?;
+        let new_tree = repo.revparse_single(rev)?.peel_to_tree()?;
+        let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+
+        let mut paths = Vec::new();
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path()
+                    && !paths.iter().any(|known: &PathBuf| known == path)
+                {
+                    paths.push(path.to_path_buf());
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    fn read_file(&self, rev: &str, path: &Path) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+        let entry = match tree.get_path(path) {
+            Ok(entry) => entry,
+            Err(error) if error.code() == ErrorCode::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let Ok(blob) = entry.to_object(&repo)?.into_blob() else {
+            return Ok(None);
+        };
+        let content = String::from_utf8(blob.content().to_vec())
+            .map_err(|_| error::git(format!("{}:{} 不是 UTF-8 文本", rev, path.display())))?;
+        Ok(Some(content))
+    }
+
+    fn tree_files(&self, rev: &str) -> Result<Vec<PathBuf>> {
+        let repo = self.repo()?;
+        let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+        let mut files = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob)
+                && let Ok(name) = entry.name()
+            {
+                files.push(Path::new(dir).join(name));
+            }
+            TreeWalkResult::Ok
+        })?;
+        Ok(files)
+    }
+
+    fn tag_exists(&self, name: &str) -> Result<bool> {
+        let repo = self.repo()?;
+        Ok(repo.find_reference(&format!("refs/tags/{}", name)).is_ok())
+    }
+
+    fn create_tag(&self, request: &TagRequest) -> Result<()> {
+        // libgit2 不支持签名，签名标签交给 git 命令
+        if request.sign {
+            return run(self.git().args(request.command_args()), self.quiet, || {
+                format!("创建标签失败: {}", request.name)
+            });
+        }
+
+        let repo = self.repo()?;
+        let target = repo
+            .revparse_single(request.target.as_deref().unwrap_or("HEAD"))?
+            .peel(git2::ObjectType::Commit)?;
+        let result = match &request.message {
+            Some(message) => {
+                let tagger = repo.signature()?;
+                // 与 git tag -m 一致，附注信息以换行结尾
+                repo.tag(
+                    &request.name,
+                    &target,
+                    &tagger,
+                    &format!("{}\n", message.trim_end()),
+                    false,
+                )
+            }
+            None => repo.tag_lightweight(&request.name, &target, false),
+        };
+        result.map_err(|error| {
+            error::git(format!(
+                "创建标签失败: {}: {}",
+                request.name,
+                error.message()
+            ))
+        })?;
+        Ok(())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<()> {
+        let repo = self.repo()?;
+        repo.tag_delete(name)
+            .map_err(|error| error::git(format!("删除标签失败: {}: {}", name, error.message())))
+    }
+
+    fn remotes(&self) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let remotes = repo.remotes()?;
+        let mut names = Vec::new();
+        for remote in remotes.iter() {
+            if let Some(name) = remote? {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn remote_url(&self, name: &str) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let Ok(remote) = repo.find_remote(name) else {
+            return Ok(None);
+        };
+        Ok(Some(remote.url()?.to_string()))
+    }
+
+    fn config(&self, key: &str) -> Result<Option<String>> {
+        let config = self.repo()?.config()?;
+        match config.get_string(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if error.code() == ErrorCode::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn push(&self, remote: &str, args: &[&str]) -> Result<()> {
+        self.run_push(remote, &[&[remote], args].concat())
+    }
+
+    fn push_atomic(&self, remote: &str, refspecs: &[&str]) -> Result<bool> {
+        let args = [&["--atomic", remote], refspecs].concat();
+        match self.run_push(remote, &args) {
+            Err(error)
+                if error
+                    .downcast_ref::<PushError>()
+                    .is_some_and(|error| error.detail.contains("does not support --atomic")) =>
+            {
+                Ok(false)
+            }
+            result => result.map(|_| true),
+        }
+    }
+
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()> {
+        // 使用完整的引用名，避免与同名分支混淆
+        self.run_push(
+            remote,
+            &[remote, "--delete", &format!("refs/tags/{}", name)],
+        )
+    }
+
+    fn remote_tag_exists(&self, remote: &str, name: &str) -> Result<bool> {
+        let output = self
+            .git()
+            .args(["ls-remote", "--tags", remote])
+            .arg(format!("refs/tags/{}", name))
+            .output()?;
+        if !output.status.success() {
+            return Err(error::git(format!(
+                "查询 {} 的标签失败: {}",
+                remote,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(!output.stdout.is_empty())
+    }
+}
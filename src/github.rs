@@ -0,0 +1,105 @@
+//! 通过 GitHub API 创建 Release 和 Pull Request
+
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+const GITHUB_API: &str = "https://api.github.com";
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// 要创建的 Release
+pub struct Release<'a> {
+    pub tag: &'a str,
+    pub name: &'a str,
+    pub body: &'a str,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+/// 要创建的 Pull Request
+pub struct PullRequest<'a> {
+    pub title: &'a str,
+    /// 源分支
+    pub head: &'a str,
+    /// 目标分支
+    pub base: &'a str,
+    pub body: &'a str,
+}
+
+/// 从远程仓库地址中解析 GitHub 的 owner 和仓库名
+///
+/// 支持 `git@github.com:owner/repo.git`、`ssh://git@github.com/owner/repo`
+/// 和 `https://github.com/owner/repo.git` 等形式。
+pub fn parse_repo(url: &str) -> Option<(String, String)> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| {
+            let rest = url.split_once("://")?.1;
+            let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+            rest.strip_prefix("github.com/")
+                .or_else(|| rest.strip_prefix("github.com:"))
+        })?
+        .trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some((owner.to_string(), repo.to_string()))
+}
+
+/// 创建 Release，返回其页面地址
+pub fn create_release(token: &str, owner: &str, repo: &str, release: &Release) -> Result<String> {
+    let url = format!("{}/repos/{}/{}/releases", GITHUB_API, owner, repo);
+    let payload = json!({
+        "tag_name": release.tag,
+        "name": release.name,
+        "body": release.body,
+        "draft": release.draft,
+        "prerelease": release.prerelease,
+    });
+
+    let mut response = ureq::post(&url)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/vnd.github+json")
+        .header("Authorization", &format!("Bearer {}", token))
+        .send_json(payload)
+        .map_err(|error| anyhow!("创建 GitHub Release 失败 ({}/{}): {}", owner, repo, error))?;
+
+    let body: Value = response.body_mut().read_json()?;
+    Ok(body["html_url"].as_str().unwrap_or_default().to_string())
+}
+
+/// 创建 Pull Request，返回其页面地址
+pub fn create_pull_request(
+    token: &str,
+    owner: &str,
+    repo: &str,
+    pull_request: &PullRequest,
+) -> Result<String> {
+    let url = format!("{}/repos/{}/{}/pulls", GITHUB_API, owner, repo);
+    let payload = json!({
+        "title": pull_request.title,
+        "head": pull_request.head,
+        "base": pull_request.base,
+        "body": pull_request.body,
+    });
+
+    let mut response = ureq::post(&url)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/vnd.github+json")
+        .header("Authorization", &format!("Bearer {}", token))
+        .send_json(payload)
+        .map_err(|error| {
+            anyhow!(
+                "创建 GitHub Pull Request 失败 ({}/{}): {}",
+                owner,
+                repo,
+                error
+            )
+        })?;
+
+    let body: Value = response.body_mut().read_json()?;
+    Ok(body["html_url"].as_str().unwrap_or_default().to_string())
+}
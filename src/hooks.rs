@@ -0,0 +1,154 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 钩子命令的执行时机，按发布流程的先后顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    /// 版本号写入文件之后、提交之前
+    PreCommit,
+    /// 提交之后、打标签之前
+    PostCommit,
+    /// 打标签之后、推送之前
+    PostTag,
+}
+
+impl HookPoint {
+    const ALL: [HookPoint; 3] = [
+        HookPoint::PreCommit,
+        HookPoint::PostCommit,
+        HookPoint::PostTag,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::PreCommit => "pre_commit",
+            HookPoint::PostCommit => "post_commit",
+            HookPoint::PostTag => "post_tag",
+        }
+    }
+}
+
+impl fmt::Display for HookPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 通过 `--run [POINT:]CMD` 指定的钩子命令
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub point: HookPoint,
+    pub command: String,
+}
+
+impl FromStr for Hook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 只有前缀是已知的执行时机才拆分，命令本身可以包含冒号
+        let (point, command) = s
+            .split_once(':')
+            .and_then(|(prefix, command)| {
+                HookPoint::ALL
+                    .into_iter()
+                    .find(|point| point.name() == prefix.trim())
+                    .map(|point| (point, command))
+            })
+            .unwrap_or((HookPoint::PostTag, s));
+
+        let command = command.trim();
+        if command.is_empty() {
+            return Err("钩子命令不能为空".to_string());
+        }
+
+        Ok(Self {
+            point,
+            command: command.to_string(),
+        })
+    }
+}
+
+/// 通过系统 shell 执行用户提供的命令
+pub fn shell_command(command: &str) -> StdCommand {
+    let mut shell = if cfg!(windows) {
+        let mut shell = StdCommand::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = StdCommand::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// 传给钩子的临时文件，drop 时删除
+#[derive(Debug)]
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    /// 在系统临时目录中创建一个新文件并写入内容，文件名包含进程号和时间以免冲突
+    pub fn create(prefix: &str, extension: &str, content: &[u8]) -> io::Result<Self> {
+        let path = temp_path(prefix, &format!(".{}", extension));
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let temp = Self(path);
+        file.write_all(content)?;
+        Ok(temp)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// 临时目录，drop 时连同其中的文件一起删除
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// 在系统临时目录中创建一个新目录，目录名规则与 [`TempFile`] 相同
+    pub fn create(prefix: &str) -> io::Result<Self> {
+        let path = temp_path(prefix, "");
+        fs::create_dir(&path)?;
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn temp_path(prefix: &str, suffix: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!(
+        "{}-{}-{}{}",
+        prefix,
+        std::process::id(),
+        nanos,
+        suffix
+    ))
+}
@@ -0,0 +1,209 @@
+//! 对 JSON 文本做局部修改，只替换目标值，保留其余内容的格式和键顺序
+
+use std::ops::Range;
+
+/// 查找指定键路径对应值在文本中的位置
+pub fn find_value(content: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut scanner = Scanner {
+        bytes: content.as_bytes(),
+        pos: 0,
+    };
+    scanner.skip_ws();
+    scanner.find_in_value(path)
+}
+
+/// 读取指定键路径上的字符串值
+pub fn get_string(content: &str, path: &[&str]) -> Option<String> {
+    let span = find_value(content, path)?;
+    serde_json::from_str(&content[span]).ok()
+}
+
+/// 将指定键路径上的值替换为新的 JSON 文本，返回 (旧值文本, 新内容)
+pub fn replace_raw(content: &str, path: &[&str], raw: &str) -> Option<(String, String)> {
+    let span = find_value(content, path)?;
+    let old = content[span.clone()].to_string();
+    let mut updated = String::with_capacity(content.len() + raw.len());
+    updated.push_str(&content[..span.start]);
+    updated.push_str(raw);
+    updated.push_str(&content[span.end..]);
+    Some((old, updated))
+}
+
+/// 将指定键路径上的字符串值替换为新值，返回 (旧值, 新内容)
+///
+/// 目标不存在或不是字符串时返回 `None`
+pub fn replace_string(content: &str, path: &[&str], value: &str) -> Option<(String, String)> {
+    let old = get_string(content, path)?;
+    let raw = serde_json::to_string(value).ok()?;
+    let (_, updated) = replace_raw(content, path, &raw)?;
+    Some((old, updated))
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_ws();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// 在当前值中查找路径，路径为空时返回当前值的位置
+    fn find_in_value(&mut self, path: &[&str]) -> Option<Range<usize>> {
+        self.skip_ws();
+        let Some((key, rest)) = path.split_first() else {
+            let start = self.pos;
+            self.skip_value()?;
+            return Some(start..self.pos);
+        };
+
+        if self.peek() != Some(b'{') {
+            return None;
+        }
+        self.pos += 1;
+
+        loop {
+            self.skip_ws();
+            match self.peek()? {
+                b'}' => return None,
+                b',' => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let name_span = self.skip_string()?;
+            let name: String = serde_json::from_slice(&self.bytes[name_span]).ok()?;
+            self.expect(b':')?;
+            if name == *key {
+                return self.find_in_value(rest);
+            }
+            self.skip_ws();
+            self.skip_value()?;
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_ws();
+        match self.peek()? {
+            b'"' => self.skip_string().map(|_| ()),
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                loop {
+                    self.skip_ws();
+                    match self.peek()? {
+                        byte if byte == close => {
+                            self.pos += 1;
+                            return Some(());
+                        }
+                        b',' | b':' => self.pos += 1,
+                        _ => self.skip_value()?,
+                    }
+                }
+            }
+            _ => {
+                // 数字、true/false/null
+                let start = self.pos;
+                while matches!(
+                    self.peek(),
+                    Some(b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'-' | b'+' | b'.')
+                ) {
+                    self.pos += 1;
+                }
+                (self.pos > start).then_some(())
+            }
+        }
+    }
+
+    fn skip_string(&mut self) -> Option<Range<usize>> {
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(start..self.pos);
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAURI_CONF: &str = r#"{
+  "$schema": "https://schema.tauri.app/config/2",
+  "productName": "demo",
+  "version": "1.2.3",
+  "identifier": "com.example.demo",
+  "build": {
+    "beforeDevCommand": "npm run dev",
+    "devUrl": "http://localhost:1420",
+    "frontendDist": "../dist"
+  },
+  "app": {
+    "windows": [ { "title": "demo \"1.2.3\"", "width": 800, "height": 600 } ],
+    "security": { "csp": null }
+  },
+  "plugins": {
+    "updater": { "version": "1.2.3", "endpoints": [] }
+  },
+  "bundle": {
+    "active":true,
+    "targets" : "all",
+    "icon": ["icons/32x32.png", "icons/icon.ico"]
+  }
+}
+"#;
+
+    #[test]
+    fn tauri_conf_only_version_literal_changes() {
+        let (old, updated) = replace_string(TAURI_CONF, &["version"], "1.3.0").unwrap();
+
+        assert_eq!(old, "1.2.3");
+        let expected = TAURI_CONF.replacen(r#""version": "1.2.3","#, r#""version": "1.3.0","#, 1);
+        assert_eq!(updated, expected);
+    }
+
+    #[test]
+    fn nested_key_is_found_by_path() {
+        let span = find_value(TAURI_CONF, &["plugins", "updater", "version"]).unwrap();
+        assert_eq!(&TAURI_CONF[span], r#""1.2.3""#);
+        assert_eq!(
+            get_string(TAURI_CONF, &["build", "devUrl"]).as_deref(),
+            Some("http://localhost:1420")
+        );
+    }
+
+    #[test]
+    fn missing_or_non_string_value_is_not_replaced() {
+        assert!(replace_string(TAURI_CONF, &["package", "version"], "1.3.0").is_none());
+        assert!(replace_string(TAURI_CONF, &["app", "security", "csp"], "1.3.0").is_none());
+    }
+}
@@ -1,463 +1,7849 @@
 use anyhow::{Result, anyhow};
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use glob::Pattern;
+use log::{LevelFilter, debug, info, warn};
+use rayon::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::process::Command as StdCommand;
+use std::process::{Command as StdCommand, Stdio};
+use std::thread;
+use std::time::Duration;
+use toml_edit::DocumentMut;
 use walkdir::WalkDir;
 
+mod changelog;
+mod config;
+mod conventional;
+pub mod error;
+pub mod git;
+mod github;
+mod hooks;
+mod json_edit;
+pub mod logger;
+mod markdown;
+mod notify;
+mod progress;
+mod registry;
+#[cfg(test)]
+mod test_support;
+mod version;
+mod yaml_edit;
+
+use config::{SinkFormat, VersionSink};
+pub use error::ReleaseError;
+use git::{Git, Git2Backend, PushError, PushErrorKind, TagRequest};
+use hooks::{Hook, HookPoint, TempDir, TempFile};
+use version::{Bump, PreStep};
+
+/// 标签前缀和标签信息中代表 crate 名的占位符
+const CRATE_PLACEHOLDER: &str = "{crate}";
+
+/// 内置版本文件条目的名称，配置文件中的同名 [[version-sinks]] 条目会覆盖它们
+const TAURI_SINK: &str = "tauri";
+const PACKAGE_JSON_SINK: &str = "package-json";
+const PACKAGE_LOCK_SINK: &str = "package-lock";
+const PYPROJECT_SINK: &str = "pyproject";
+const VERSION_SINK: &str = "version";
+const README_INSTALL_SINK: &str = "readme-install";
+const README_BADGE_SINK: &str = "readme-badge";
+const HELM_SINK: &str = "helm";
+
+/// 默认查找的 Helm Chart.yaml，可以是 glob
+const HELM_CHART_PATHS: &[&str] = &[
+    "Chart.yaml",
+    "chart/Chart.yaml",
+    "charts/*/Chart.yaml",
+    "helm/Chart.yaml",
+    "helm/*/Chart.yaml",
+];
+
+/// 文档中完整的 semver 版本号，用于在 README 和 Markdown 代码块中查找版本号
+const SEMVER_PATTERN: &str = r"[0-9]+\.[0-9]+\.[0-9]+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?";
+
 #[derive(Parser, Debug)]
 #[command(
     name = "git-release",
+    subcommand_negates_reqs = true,
     author = "thlstsul",
     about = "自动化 Git 项目发布流程",
-    long_about = "一个用于自动化 Git 项目发布流程的工具，支持版本号更新、提交、打标签和推送到所有远程仓库。支持 workspace 项目。"
+    long_about = "一个用于自动化 Git 项目发布流程的工具，支持版本号更新、提交、打标签和推送到所有远程仓库。支持 workspace 项目。\n\n\
+                  仓库根目录下的 .git-release.toml 可以为选项提供默认值，键名与命令行选项一致\
+                  （例如 tag-prefix = \"release-\"），也可以写在 Cargo.toml 的 [package.metadata.git-release] \
+                  或 [workspace.metadata.git-release] 中。优先级为：命令行参数 > package metadata > \
+                  workspace metadata > .git-release.toml > 默认值。\n\n\
+                  子命令 bump、tag、revert、list 和 changelog 只接受与其相关的选项，省略子命令时执行 release。\
+                  --path、--dry-run、crate 过滤条件和标签前缀等全局选项可以写在子命令之前或之后。\n\n\
+                  退出码: 0 成功或没有需要发布的提交，1 其他错误，2 发布前检查未通过，3 git 操作失败，\
+                  4 使用 --tag-only-if-changed 时没有需要发布的内容。"
 )]
 pub struct Cli {
-    /// 新版本号 (例如: 1.2.3)
-    #[arg(value_name = "VERSION")]
-    version: String,
+    #[command(flatten)]
+    release: ReleaseArgs,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// 所有子命令共用的选项，可以写在子命令之前或之后
+#[derive(Args, Debug)]
+#[command(next_help_heading = "全局选项")]
+struct GlobalArgs {
+    /// 在指定目录中执行发布（类似 git -C），配置文件也从该目录读取
+    #[arg(long, short = 'C', value_name = "DIR", global = true)]
+    path: Option<PathBuf>,
+
+    /// git 可执行文件的路径，默认读取 GIT 环境变量，未设置时使用 PATH 中的 git
+    #[arg(long, value_name = "PATH", global = true)]
+    git_bin: Option<PathBuf>,
+
+    /// 捕获 git 命令的输出（推送进度等），只在失败时显示
+    #[arg(long, global = true)]
+    quiet_git: bool,
+
+    /// 只预览将要进行的改动，不修改文件也不执行 Git 操作
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// 跳过版本号格式验证等安全检查
+    #[arg(long, short = 'f', global = true)]
+    force: bool,
+
+    /// 输出更详细的信息（-v 显示调试信息，-vv 显示全部）
+    #[arg(long, short = 'v', action = ArgAction::Count, conflicts_with = "quiet", global = true)]
+    verbose: u8,
+
+    /// 只输出警告和错误
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+
+    /// 不使用彩色输出，也可以设置 NO_COLOR 环境变量；输出不是终端时会自动关闭
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// 结果的输出格式，json 时只在 stdout 输出一个 JSON 对象，干运行时为发布计划
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        value_name = "FORMAT",
+        global = true
+    )]
+    format: OutputFormat,
+
+    /// workspace 根目录的 Cargo.toml 或其所在目录（可多次使用），默认为当前目录
+    ///
+    /// 仓库由多个互相独立的 workspace 组成时，每个 workspace 都从自己的根目录查找成员，
+    /// 全部更新到同一个版本号后一起提交并创建一个标签
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "extra_manifest",
+        global = true
+    )]
+    manifest_path: Vec<PathBuf>,
+
+    /// 查找 workspace 成员的方式
+    ///
+    /// members 按根 Cargo.toml 的 [workspace].members 和 exclude 查找成员，根目录不是 workspace
+    /// 或没有 members 时退回 walk；walk 遍历目录下的所有 Cargo.toml；metadata 使用 cargo metadata
+    /// 解析出的 workspace 成员，无法执行 cargo 时退回 walk。
+    /// --max-depth 和 --follow-symlinks 只在遍历目录时生效
+    #[arg(long, value_enum, default_value_t = Discovery::Members, value_name = "MODE", global = true)]
+    discovery: Discovery,
+
+    /// 查找工作区成员时跟随符号链接
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+
+    /// 查找工作区成员时同时进入 git 子模块，默认跳过子模块中的 Cargo.toml
+    #[arg(long, global = true)]
+    include_submodules: bool,
+
+    /// 查找 Cargo.toml 时最多进入的目录层数，0 表示只处理根目录的 Cargo.toml
+    ///
+    /// 默认的 walk 方式通过遍历目录查找成员，而不是读取 workspace.members：
+    /// 超出层数的 crate 即使列在 members 中也不会被更新，
+    /// 层数以内的 crate 仍然受 default-members 和 --only/--exclude 等过滤条件限制
+    #[arg(long, value_name = "N", global = true)]
+    max_depth: Option<usize>,
+
+    /// 并行读取和改写清单的线程数，默认为 CPU 数量，为 1 时串行处理
+    #[arg(long, short = 'j', value_name = "N", global = true)]
+    jobs: Option<NonZeroUsize>,
+
+    /// 只更新指定的 crate（可多次使用），默认更新所有；先按 --only 选择，再应用 --exclude
+    #[arg(long, value_name = "CRATE", global = true)]
+    only: Vec<String>,
+
+    /// 排除更新的 crate 名称（可多次使用），与 --only 同时使用时从 --only 选中的 crate 中排除
+    #[arg(long, value_name = "CRATE", global = true)]
+    exclude: Vec<String>,
+
+    /// 更新所有 workspace 成员，忽略 workspace.default-members
+    #[arg(long, global = true)]
+    all: bool,
+
+    /// 同时更新 publish = false（或 publish = []）的 crate，默认跳过这些不会发布的 crate
+    #[arg(long, global = true)]
+    include_unpublished: bool,
+
+    /// 只更新目录匹配 glob 的 crate（可多次使用），例如 "crates/core/*"
+    #[arg(long, value_name = "GLOB", global = true)]
+    only_path: Vec<Pattern>,
+
+    /// 排除目录匹配 glob 的 crate（可多次使用），例如 "examples/**"
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude_path: Vec<Pattern>,
+
+    /// 额外更新的非 workspace 成员清单（可多次使用），可以是 Cargo.toml 或其所在目录，
+    /// 例如 examples/demo；这些清单不受 workspace.default-members 限制
+    #[arg(long, value_name = "PATH", global = true)]
+    extra_manifest: Vec<PathBuf>,
+
+    /// 标签前缀，默认为 'v'，可以为空（--tag-prefix ""）以使用 1.2.3 这样的标签
+    ///
+    /// 包含 {crate} 时为每个更新了版本号的 crate 分别创建标签，例如 '{crate}-v' 会创建 core-v1.2.0 和 cli-v1.2.0，
+    /// {crate} 同样可以用在 --tag-message 中
+    #[arg(long, default_value = "v", value_name = "PREFIX", global = true)]
+    tag_prefix: String,
+
+    /// 附加在标签名末尾的后缀，不影响写入文件的版本号，
+    /// 例如 '-staging' 时版本号为 1.2.3，标签为 v1.2.3-staging
+    #[arg(long, value_name = "SUFFIX", allow_hyphen_values = true, global = true)]
+    tag_suffix: Option<String>,
+
+    /// 为每个更新了版本号的 crate 分别创建 <crate>-<前缀><版本号> 标签（例如 core-v1.2.0），
+    /// 默认只创建一个全局标签；标签前缀中已包含 {crate} 时无需此选项
+    #[arg(long, global = true)]
+    tag_per_crate: bool,
+
+    /// 只推送到指定的远程仓库（可多次使用），默认推送到所有远程仓库
+    #[arg(long, value_name = "REMOTE", global = true)]
+    remote: Vec<String>,
+}
+
+/// release 的选项，省略子命令时直接写在 git-release 之后
+#[derive(Args, Debug)]
+struct ReleaseArgs {
+    /// 新版本号 (例如: 1.2.3)，major/minor/patch 表示在当前版本上递增，- 表示从标准输入读取
+    #[arg(
+        value_name = "VERSION",
+        required_unless_present_any = ["auto", "version_from_env", "tag_only", "finalize", "to_rc", "bump_pre", "promote", "revert", "generate_completions", "list_crates", "publish_order", "bump_build_metadata"]
+    )]
+    version: Option<String>,
+
+    /// 将指定 shell 的补全脚本输出到 stdout 后退出
+    #[arg(long, value_enum, value_name = "SHELL")]
+    generate_completions: Option<Shell>,
+
+    /// 根据上一个标签以来的 Conventional Commits 自动计算版本号
+    #[arg(long, conflicts_with_all = ["version", "version_from_env"])]
+    auto: bool,
+
+    /// 上一个标签以来没有新的提交时不提交、不打标签也不推送，以退出码 4 结束
+    ///
+    /// 与 --auto 一起使用时，没有需要发布的提交也以退出码 4 结束，便于定时任务区分
+    #[arg(long, conflicts_with = "revert")]
+    tag_only_if_changed: bool,
+
+    /// 从指定的环境变量读取版本号
+    #[arg(long, value_name = "NAME", conflicts_with = "version")]
+    version_from_env: Option<String>,
+
+    /// 在新版本号后添加 -rc.1 作为第一个预发布版本，未指定版本号时在当前版本上递增 patch
+    #[arg(long, conflicts_with_all = ["bump_pre", "promote", "tag_only", "finalize"])]
+    to_rc: bool,
+
+    /// 递增当前预发布版本的序号（例如 2.0.0-rc.1 -> 2.0.0-rc.2）
+    #[arg(
+        long,
+        conflicts_with_all = ["version", "auto", "version_from_env", "promote", "tag_only", "finalize"]
+    )]
+    bump_pre: bool,
+
+    /// 去掉当前版本的预发布部分作为正式版本发布（例如 2.0.0-rc.2 -> 2.0.0）
+    #[arg(
+        long,
+        conflicts_with_all = ["version", "auto", "version_from_env", "tag_only", "finalize"]
+    )]
+    promote: bool,
+
+    /// 只递增当前版本的构建元数据，不改变版本号本身（例如 1.2.3+build.5 -> 1.2.3+build.6），
+    /// 没有构建元数据时为 +build.1；给出 METADATA 时直接设为该值（例如 --bump-build-metadata ci.42）
+    #[arg(
+        long,
+        value_name = "METADATA",
+        num_args = 0..=1,
+        conflicts_with_all = ["version", "auto", "version_from_env", "tag_only", "finalize", "to_rc", "bump_pre", "promote"]
+    )]
+    bump_build_metadata: Option<Option<String>>,
+
+    /// 只输出计算出的版本号，不检查仓库也不修改任何文件
+    #[arg(long)]
+    print_version: bool,
+
+    /// 列出应用 --only、--exclude 等过滤条件和 default-members 后会被更新的 crate
+    /// 及其路径和当前版本号，不修改任何文件（不查询 crates.io）
+    #[arg(
+        long,
+        conflicts_with_all = ["print_version", "workspace_version_only", "only_if_version_greater_on_registry"]
+    )]
+    list_crates: bool,
+
+    /// 按依赖关系列出 crate 的发布顺序（被依赖的在前），不修改任何文件
+    ///
+    /// publish = false 的 crate 以及依赖它们的 crate 不参与排序，但版本号仍按其他参数更新
+    #[arg(
+        long,
+        conflicts_with_all = ["list_crates", "print_version", "workspace_version_only", "only_if_version_greater_on_registry"]
+    )]
+    publish_order: bool,
+
+    /// 首次发布：不查找上一个标签，--auto 和更新日志使用全部提交历史，--changed-only 更新所有 crate
+    #[arg(long, conflicts_with_all = ["since", "re_publish"])]
+    first_release: bool,
+
+    /// 重新发布版本（如果标签已存在则删除重新创建）
+    #[arg(long, short = 'r')]
+    re_publish: bool,
+
+    /// 允许重复运行同一个发布：标签已存在且指向本次要打标签的提交、版本文件已是目标版本时，
+    /// 不再提交和打标签，只推送尚未推送的内容；标签指向其他提交时仍然报错
+    #[arg(long, conflicts_with_all = ["re_publish", "prepare"])]
+    idempotent: bool,
+
+    /// 重新创建已存在的标签时，在标签信息的标题后附加说明，{date} 会被替换为当天日期
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        num_args = 0..=1,
+        default_missing_value = "(re-published on {date})",
+        requires = "re_publish",
+        conflicts_with = "lightweight"
+    )]
+    re_publish_note: Option<String>,
+
+    /// 提交信息模板，{version} 会被替换为实际版本号
+    ///
+    /// {env:NAME} 会被替换为环境变量 NAME 的值，变量未设置时报错，
+    /// 可以用 {env:NAME:-默认值} 指定变量未设置或为空时使用的值
+    #[arg(
+        long,
+        short = 'm',
+        default_value = "Release version {version}",
+        value_name = "MESSAGE"
+    )]
+    message: String,
+
+    /// 从文件读取提交信息（支持多行，占位符与 --message 相同），通过 git commit -F 提交
+    #[arg(long, value_name = "PATH", conflicts_with = "message")]
+    message_file: Option<PathBuf>,
+
+    /// 提交信息和标签信息中代表版本号的占位符，例如沿用旧脚本模板中的 '%VERSION%' 或 '$VERSION'，
+    /// 默认的 {version} 始终可用
+    #[arg(long, default_value = "{version}", value_name = "TOKEN")]
+    version_token: String,
+
+    /// 在提交信息后附上各 crate 的版本变化和修改的文件列表，通过 git commit -F 提交
+    #[arg(long)]
+    detailed_commit: bool,
+
+    /// 创建轻量标签而不是附注标签
+    #[arg(long, conflicts_with = "tag_message")]
+    lightweight: bool,
+
+    /// 附注标签的信息模板，使用 --changelog 时会在其后附上更新日志
+    ///
+    /// 支持的占位符: {version} 版本号，{date} 当天日期（UTC，YYYY-MM-DD），
+    /// {author} 和 {email} 为 git config 中的 user.name 和 user.email，
+    /// {env:NAME} 和 {env:NAME:-默认值} 为环境变量（与 --message 相同），
+    /// 例如 'Release {version} on {date} by {author}'
+    #[arg(long, default_value = "Version {version}", value_name = "TEMPLATE")]
+    tag_message: String,
+
+    /// 签名发布提交和标签，签名方式（GPG/SSH）遵循 git 的 gpg.format 配置
+    #[arg(long, short = 's', conflicts_with = "lightweight")]
+    sign: bool,
+
+    /// 签名使用的密钥 ID（SSH 签名时为密钥文件路径），默认使用 git 的 user.signingkey
+    ///
+    /// 发布开始前会确认密钥存在：GPG 通过 gpg --list-secret-keys 查找私钥，SSH 检查密钥文件
+    #[arg(long, value_name = "KEYID", requires = "sign")]
+    signing_key: Option<String>,
+
+    /// 创建标签后同时验证发布提交的签名（使用 --sign 时总是会验证标签的签名）
+    #[arg(long, requires = "sign")]
+    verify_signature: bool,
+
+    /// 要求上一个标签以来的所有提交都有有效的签名，有未签名或签名无效的提交时中止发布
+    ///
+    /// 使用 git log 的 %G? 判断：G（有效）和 U（有效但密钥可信度未知）视为已签名，
+    /// 与 git verify-commit 一致。使用 --force 时只警告
+    #[arg(long, conflicts_with = "revert")]
+    require_signed_commits: bool,
+
+    /// 只允许在匹配 glob 的分支上发布（可多次使用），例如 "main"、"release/*"
+    #[arg(long, value_name = "PATTERN")]
+    release_branch: Vec<Pattern>,
+
+    /// 断言当前检出的分支为 NAME，否则在提交和推送之前中止（HEAD 分离时同样中止），
+    /// 用于防止 CI 中推送到意料之外的分支
+    #[arg(long, value_name = "NAME")]
+    expect_branch: Option<String>,
+
+    /// 推送的分支名，默认为当前分支（HEAD 处于分离状态时必须指定）
+    #[arg(long, value_name = "NAME")]
+    branch: Option<String>,
+
+    /// 推送发布提交时使用的 refspec，{branch} 会被替换为推送的分支名，标签仍单独推送
+    ///
+    /// 用于需要经过评审的受保护分支，例如 Gerrit 的 'HEAD:refs/for/{branch}'
+    #[arg(long, value_name = "SPEC", conflicts_with = "prepare")]
+    push_refspec: Option<String>,
+
+    /// 推送分支和标签之后额外推送的 refspec（可多次使用），例如 refs/notes/*、refs/release/*
+    ///
+    /// 使用 --atomic 时与分支和标签一起原子推送，否则逐个推送，失败时只警告并记录在推送结果中
+    #[arg(long, value_name = "REFSPEC", conflicts_with = "prepare")]
+    push_ref: Vec<String>,
+
+    /// 执行发布前输出发布计划：版本变化、各 crate 和其他文件的版本、提交信息、标签和远程仓库
+    #[arg(long, conflicts_with = "dry_run")]
+    print_plan: bool,
+
+    /// 输出发布计划后询问是否继续，需要在终端中运行；可以在配置文件中设置 confirm = true
+    #[arg(long, conflicts_with = "dry_run")]
+    confirm: bool,
+
+    /// 不询问确认直接发布，仍然输出发布计划（覆盖配置文件中的 confirm = true）
+    #[arg(long, short = 'y', conflicts_with = "dry_run")]
+    yes: bool,
+
+    /// 只更新根 Cargo.toml 中的 workspace.package.version，不查找和修改任何成员清单，
+    /// 适用于所有成员都使用 version.workspace = true 的 workspace
+    #[arg(
+        long,
+        conflicts_with_all = ["exclude", "only", "all", "exclude_path", "only_path", "max_depth", "extra_manifest"]
+    )]
+    workspace_version_only: bool,
+
+    /// 将继承 workspace 版本（version.workspace = true）的成员改写为具体的版本号，
+    /// 根 Cargo.toml 中的 workspace.package.version 仍会同步更新
+    ///
+    /// 用于不支持在发布时解析 workspace 继承的 registry。改写后成员不再跟随 workspace 版本，
+    /// 之后的发布会按独立版本更新它们；如需恢复继承需要手动改回 version.workspace = true
+    #[arg(
+        long,
+        alias = "freeze-workspace-inheritance",
+        conflicts_with = "workspace_version_only"
+    )]
+    materialize_versions: bool,
+
+    /// 只更新自上一个标签以来目录下有改动的 crate，其余 crate 保持原版本号
+    ///
+    /// 标签前缀包含 {crate} 时与各 crate 自己的上一个标签比较，并且只为更新的 crate 打标签
+    #[arg(
+        long,
+        conflicts_with_all = ["workspace_version_only", "tag_only", "finalize", "at"]
+    )]
+    changed_only: bool,
+
+    /// 更新版本号后、提交前运行 cargo check --workspace，失败时中止并还原修改的文件
+    #[arg(long)]
+    verify_build: bool,
+
+    /// 干运行时将新版本号临时写入文件，按发布顺序对本次更新的每个 crate 执行
+    /// cargo publish --dry-run，检查缺少 license、文件被排除等打包问题，完成后还原文件
+    ///
+    /// 不执行任何 git 操作，也不会真正发布
+    #[arg(long, requires = "dry_run")]
+    verify_publish: bool,
+
+    /// 更新版本号后同步更新 Cargo.lock，并将其纳入发布提交
+    #[arg(long)]
+    update_lock: bool,
+
+    /// 同时递增 tauri.conf.json 中的 Android versionCode 和 iOS bundleVersion
+    #[arg(long, conflicts_with = "no_tauri")]
+    mobile: bool,
+
+    /// 不查找和更新 tauri.conf.json，等同于 --skip-updater tauri
+    #[arg(long)]
+    no_tauri: bool,
+
+    /// 额外需要更新的 tauri.conf.json（可多次使用），例如 apps/desktop/src-tauri/tauri.conf.json
+    ///
+    /// 与默认位置中找到的文件一起更新，不存在的文件会被跳过并警告
+    #[arg(long, value_name = "PATH", conflicts_with = "no_tauri")]
+    tauri_config: Vec<PathBuf>,
+
+    /// 不查找默认位置的 tauri.conf.json 和 src-tauri/tauri.conf.json，只更新 --tauri-config 指定的文件
+    #[arg(long, requires = "tauri_config")]
+    no_tauri_search_paths: bool,
+
+    /// 只运行指定的非 Cargo 版本更新器（可多次使用），默认全部运行
+    #[arg(
+        long,
+        value_enum,
+        value_name = "UPDATER",
+        conflicts_with = "skip_updater"
+    )]
+    updater: Vec<Updater>,
+
+    /// 跳过指定的非 Cargo 版本更新器（可多次使用）
+    #[arg(long, value_enum, value_name = "UPDATER")]
+    skip_updater: Vec<Updater>,
+
+    /// 移动端构建号的计算方式
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MobileVersionCode::Increment,
+        value_name = "MODE",
+        requires = "mobile"
+    )]
+    mobile_version_code: MobileVersionCode,
+
+    /// 不查找和更新 Helm 的 Chart.yaml，等同于 --skip-updater helm
+    #[arg(long)]
+    no_helm: bool,
+
+    /// 额外需要更新的 Helm Chart.yaml（可多次使用），与默认位置中找到的文件一起更新
+    ///
+    /// 默认位置为 Chart.yaml、chart/Chart.yaml、charts/*/Chart.yaml、helm/Chart.yaml 和 helm/*/Chart.yaml
+    #[arg(long, value_name = "PATH", conflicts_with = "no_helm")]
+    helm_chart: Vec<PathBuf>,
+
+    /// 同时更新 Chart.yaml 中 chart 自身的 version，默认只将 appVersion 更新为发布的版本号
+    #[arg(long, value_enum, value_name = "MODE", conflicts_with = "no_helm")]
+    helm_chart_version: Option<ChartVersion>,
+
+    /// 需要同步版本号的 package.json（可多次使用），不存在的文件会被跳过
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_values = ["package.json", "src/package.json"]
+    )]
+    package_json: Vec<PathBuf>,
+
+    /// 同时更新 package.json 旁的 package-lock.json
+    #[arg(long)]
+    package_lock: bool,
+
+    /// 同步 README 中的版本号（可多次使用，默认 README.md）：
+    /// 本次发布的 crate 的 `cargo install <crate> --version X.Y.Z`、`cargo add <crate>@X.Y.Z`
+    /// 和行首的依赖声明 `<crate> = "X.Y.Z"`，
+    /// 以及标签含 version 或 release 的 shields.io 静态徽章（`img.shields.io/badge/version-X.Y.Z-blue`）
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        default_missing_value = "README.md"
+    )]
+    readme: Vec<PathBuf>,
+
+    /// 使用 git push --atomic 在一次更新中推送提交和标签，远程不支持时退回分两步推送
+    #[arg(long)]
+    atomic: bool,
+
+    /// 推送因网络等临时错误失败时的重试次数，每次重试的等待时间翻倍
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    push_retries: u32,
+
+    /// 同步各 crate 对本次发布的 crate 的依赖版本要求时处理的依赖表，逗号分隔，none 表示都不处理
+    ///
+    /// 根 Cargo.toml 的 [workspace.dependencies] 总是会同步
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values = ["dependencies", "dev-dependencies", "build-dependencies"],
+        value_name = "TABLES"
+    )]
+    update_deps: Vec<DependencyTable>,
+
+    /// 根据上一个标签以来的提交生成更新日志，写入 CHANGELOG.md 并随版本一起提交
+    ///
+    /// origin 为 GitHub 或 GitLab 仓库时，在末尾附上与上一个标签比较的链接
+    #[arg(long)]
+    changelog: bool,
+
+    /// 更新日志从指定的标签或提交之后开始，默认为上一个标签
+    #[arg(long, value_name = "REF", requires = "changelog")]
+    since: Option<String>,
+
+    /// 更新日志中提交链接的模板，{hash} 会被替换为完整的提交 ID，
+    /// 例如 https://github.com/owner/repo/commit/{hash}
+    #[arg(long, value_name = "TEMPLATE", requires = "changelog")]
+    commit_link: Option<String>,
+
+    /// 同时将本次的更新日志写入指定文件，- 表示输出到 stdout（此时其他输出只保留警告和错误，
+    /// 可以配合 --quiet-git 去掉 git 命令自身的输出）
+    ///
+    /// 文件在发布提交之后写入，不会进入发布提交，干运行时也会写入
+    #[arg(long, value_name = "PATH|-", requires = "changelog")]
+    changelog_output: Option<PathBuf>,
+
+    /// 不更新 CHANGELOG.md，更新日志只用于 --changelog-output 和 GitHub Release
+    #[arg(long, requires = "changelog")]
+    no_changelog_file: bool,
+
+    /// 按 Keep a Changelog 的约定发布 CHANGELOG.md 中手写的 `## [Unreleased]` 部分
+    ///
+    /// 将其改为 `## [版本号] - 日期` 并在上方插入新的空 Unreleased 部分，同时更新文末的比较链接，
+    /// 该部分的内容也用作 GitHub Release 等处的更新日志。没有该部分或内容为空时警告，
+    /// 并根据提交记录生成
+    #[arg(long, requires = "changelog", conflicts_with = "no_changelog_file")]
+    keep_changelog_unreleased: bool,
+
+    /// 推送后通过 GitHub API 创建 Release（需要 GITHUB_TOKEN 环境变量）
+    #[arg(long)]
+    github_release: bool,
+
+    /// 将 GitHub Release 创建为草稿
+    #[arg(long, requires = "github_release")]
+    draft: bool,
+
+    /// 将 GitHub Release 标记为预发布
+    #[arg(long, requires = "github_release")]
+    prerelease: bool,
+
+    /// 推送成功后以 JSON 向该地址发送通知（Slack、Discord 等的 incoming webhook），可以重复指定
+    ///
+    /// 内容包括 text/content（--notify-template 生成的消息）、version、tag、tags、crates、
+    /// remotes 和 changelog（更新日志的开头部分）。超时或服务端出错时重试，
+    /// 最终失败也只输出警告，不影响发布
+    #[arg(long, value_name = "URL", conflicts_with = "prepare")]
+    notify_webhook: Vec<String>,
+
+    /// 通知消息的模板
+    ///
+    /// 支持的占位符: {version} 版本号，{tag} 标签（多个标签以空格分隔），
+    /// {crates} 发布的 crate（name@version，以逗号分隔），{remotes} 推送的远程仓库，
+    /// {changelog} 更新日志的开头部分
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        default_value = "已发布 {tag}",
+        requires = "notify_webhook"
+    )]
+    notify_template: String,
+
+    /// 在指定的提交或标签上创建发布标签，不修改版本文件也不创建提交
+    ///
+    /// 与 --dry-run 一起使用时改为预览该引用上的发布：把 REF 视为 HEAD，通过 git show REF:路径
+    /// 读取清单，计算版本号、有改动的 crate 和更新日志的提交范围，不检出 REF 也不写入仓库。
+    /// --auto 只能在预览时使用
+    #[arg(long, value_name = "REF", conflicts_with_all = ["changelog", "attest", "revert"])]
+    at: Option<String>,
+
+    /// 允许在工作区有未提交的更改时发布
+    ///
+    /// 发布提交使用 git add -A，工作区中的所有改动（包括未跟踪的文件）都会随版本号一起提交
+    #[arg(long, conflicts_with = "at")]
+    allow_dirty: bool,
+
+    /// 检查工作区是否干净时忽略未跟踪的文件（git status --porcelain 中以 ?? 开头的行）
+    ///
+    /// 已修改或已暂存的文件仍算作未提交的更改。未跟踪的文件不会进入发布提交，
+    /// 发布提交只暂存已跟踪文件的改动和本次发布写入的文件
+    #[arg(long)]
+    allow_untracked: bool,
+
+    /// 未跟踪的文件也算作未提交的更改（默认行为），优先于 --allow-untracked，
+    /// 用于覆盖配置文件中的 allow-untracked = true
+    #[arg(long)]
+    strict_clean: bool,
+
+    /// 发布提交之后工作区仍有改动（例如构建脚本或代码生成改写了文件）时中止发布，默认只警告
+    #[arg(long)]
+    strict: bool,
+
+    /// 只为当前提交创建并推送标签，版本号读取自根 Cargo.toml，不修改版本文件也不创建提交
+    #[arg(
+        long,
+        conflicts_with_all = ["version", "auto", "version_from_env", "at", "changelog"]
+    )]
+    tag_only: bool,
+
+    /// 两阶段发布的第一步：在新的发布分支上提交版本号更新并推送该分支，不创建标签
+    #[arg(long, conflicts_with_all = ["at", "tag_only", "github_release", "attest", "atomic"])]
+    prepare: bool,
+
+    /// --prepare 创建的发布分支名，{version} 会被替换为版本号
+    #[arg(
+        long,
+        default_value = "release/{version}",
+        value_name = "TEMPLATE",
+        requires = "prepare"
+    )]
+    prepare_branch: String,
+
+    /// --prepare 推送发布分支后通过 GitHub API 创建合并到当前分支的 Pull Request（需要 GITHUB_TOKEN 环境变量）
+    #[arg(long, requires = "prepare")]
+    open_pr: bool,
+
+    /// 撤销一次发布：删除该版本在本地和远程仓库（受 --remote 限制）上的标签
+    ///
+    /// 标签不存在时报错，使用 --force 忽略
+    #[arg(
+        long,
+        value_name = "VERSION",
+        conflicts_with_all = [
+            "version", "auto", "version_from_env", "print_version", "to_rc", "bump_pre", "promote",
+            "tag_only", "at", "prepare", "finalize", "changelog"
+        ]
+    )]
+    revert: Option<String>,
+
+    /// 撤销发布时同时还原标签指向的发布提交，并将还原提交推送到远程仓库
+    #[arg(long, requires = "revert")]
+    revert_commit: bool,
+
+    /// 两阶段发布的第二步：发布 PR 合并后为当前提交创建标签并只推送标签，版本号读取自根 Cargo.toml
+    #[arg(
+        long,
+        conflicts_with_all = ["version", "auto", "version_from_env", "at", "tag_only", "prepare", "changelog"]
+    )]
+    finalize: bool,
+
+    /// 将修改的文件列表写入指定文件，干运行时为将要修改的文件
+    ///
+    /// 扩展名为 .json 时写入包含 version、tag、files 和 crates 的 JSON，否则每行一个文件路径
+    #[arg(long, value_name = "PATH")]
+    output_manifest: Option<PathBuf>,
+
+    /// 提交前通过 git ls-remote 检查远程仓库上是否已存在同名标签
+    #[arg(long)]
+    check_remote_tags: bool,
+
+    /// 提交前确认本次的全部标签在本地和每个要推送的远程仓库（受 --remote 限制）上都不存在，
+    /// 存在时列出所在位置并中止，以免推送时才被拒绝
+    ///
+    /// 比 --check-remote-tags 更严格：同时检查本地标签，也适用于按 crate 分别打标签
+    #[arg(long, conflicts_with_all = ["re_publish", "idempotent"])]
+    verify_tag_absent_everywhere: bool,
+
+    /// 重新发布时远程标签受保护无法删除，只警告并跳过该远程仓库，不中止发布
+    #[arg(long, requires = "re_publish")]
+    skip_protected: bool,
+
+    /// 检查前先通过 git fetch --tags 从所有远程仓库同步标签，失败时报错（--force 时只警告）
+    #[arg(long)]
+    fetch: bool,
+
+    /// 配置文件中的 [[version-sinks]] 和 [[version-files]] 规则
+    #[arg(skip)]
+    version_sinks: Vec<config::SinkEntry>,
+
+    /// 配置文件中 [tag-prefixes] 为个别 crate 指定的标签前缀
+    #[arg(skip)]
+    tag_prefixes: Vec<(String, String)>,
+
+    /// 第一次推送被远程拒绝（例如非快进）时，删除本次创建的标签并撤销本次的提交
+    ///
+    /// 只在推送被拒绝时生效，提交之后的 post-commit 钩子或工作区检查失败时发布提交会保留，
+    /// 需要手动 git reset 撤销
+    #[arg(long)]
+    rollback_on_push_failure: bool,
+
+    /// 提交和推送时跳过 git 钩子（git commit/push --no-verify）
+    #[arg(long)]
+    no_verify: bool,
+
+    /// 发布失败时不还原已修改的版本文件
+    #[arg(long)]
+    no_rollback: bool,
+
+    /// 版本文件改动的总行数上限，超过时中止（可用 --force 跳过）
+    #[arg(long, value_name = "N")]
+    max_diff_lines: Option<usize>,
+
+    /// 打标签后执行的来源证明命令，可通过 RELEASE_VERSION、RELEASE_TAG、RELEASE_COMMIT
+    /// 环境变量读取发布信息
+    #[arg(long, value_name = "CMD")]
+    attest: Option<String>,
+
+    /// 来源证明命令生成的文件，存在时会提交到发布中
+    #[arg(
+        long,
+        default_value = "provenance.json",
+        value_name = "PATH",
+        requires = "attest"
+    )]
+    attest_file: PathBuf,
+
+    /// 在发布流程的指定时机执行命令（可多次使用），格式为 [POINT:]CMD
+    ///
+    /// POINT 可选 pre_commit（写入版本号后、提交前）、post_commit（提交后、打标签前）、
+    /// post_tag（打标签后、推送前，默认）。同一时机的命令按给出的顺序执行，任一命令失败
+    /// 都会在推送前中止发布。命令可通过 RELEASE_VERSION、RELEASE_TAG、RELEASE_COMMIT、
+    /// RELEASE_HOOK 环境变量读取发布信息，RELEASE_PLAN_FILE 为发布计划的 JSON 文件
+    /// （与干运行时 --format json 的输出相同），发布结束后会被删除。
+    #[arg(long = "run", value_name = "[POINT:]CMD")]
+    run: Vec<Hook>,
+
+    /// 检查 crates.io 上是否已发布目标版本，已发布时中止
+    #[arg(long)]
+    only_if_version_greater_on_registry: bool,
+
+    /// 跳过 crates.io 上已发布目标版本的 crate，而不是中止
+    #[arg(long, requires = "only_if_version_greater_on_registry")]
+    skip_published: bool,
+}
+
+/// 子命令，每个子命令只接受与其相关的选项和全局选项，省略时等同于 release
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 发布新版本：更新版本号、提交、打标签并推送（默认）
+    Release(Box<ReleaseArgs>),
+    /// 只输出计算出的版本号，不检查仓库也不修改任何文件，等同于 --print-version
+    Bump(VersionArgs),
+    /// 只为当前提交创建并推送标签，版本号读取自根 Cargo.toml，等同于 --tag-only
+    Tag(TagArgs),
+    /// 撤销已发布的版本，删除该版本在本地和远程仓库上的标签，等同于 --revert <VERSION>
+    Revert(RevertArgs),
+    /// 列出会被更新的 crate，等同于 --list-crates
+    List(ListArgs),
+    /// 预览本次发布的更新日志和改动，等同于 --changelog --dry-run
+    Changelog(ChangelogArgs),
+}
+
+/// bump 和 changelog 计算新版本号的方式
+#[derive(Args, Debug)]
+struct VersionArgs {
+    /// 新版本号 (例如: 1.2.3)，major/minor/patch 表示在当前版本上递增，- 表示从标准输入读取
+    #[arg(
+        value_name = "VERSION",
+        required_unless_present_any = ["auto", "version_from_env", "to_rc", "bump_pre", "promote", "bump_build_metadata"]
+    )]
+    version: Option<String>,
+
+    /// 根据上一个标签以来的 Conventional Commits 自动计算版本号
+    #[arg(long, conflicts_with_all = ["version", "version_from_env"])]
+    auto: bool,
+
+    /// 从指定的环境变量读取版本号
+    #[arg(long, value_name = "NAME", conflicts_with = "version")]
+    version_from_env: Option<String>,
+
+    /// 在新版本号后添加 -rc.1 作为第一个预发布版本，未指定版本号时在当前版本上递增 patch
+    #[arg(long, conflicts_with_all = ["bump_pre", "promote"])]
+    to_rc: bool,
+
+    /// 递增当前预发布版本的序号（例如 2.0.0-rc.1 -> 2.0.0-rc.2）
+    #[arg(long, conflicts_with_all = ["version", "auto", "version_from_env", "promote"])]
+    bump_pre: bool,
+
+    /// 去掉当前版本的预发布部分作为正式版本（例如 2.0.0-rc.2 -> 2.0.0）
+    #[arg(long, conflicts_with_all = ["version", "auto", "version_from_env"])]
+    promote: bool,
+
+    /// 只递增当前版本的构建元数据（例如 1.2.3+build.5 -> 1.2.3+build.6），给出 METADATA 时直接设为该值
+    #[arg(
+        long,
+        value_name = "METADATA",
+        num_args = 0..=1,
+        conflicts_with_all = ["version", "auto", "version_from_env", "to_rc", "bump_pre", "promote"]
+    )]
+    bump_build_metadata: Option<Option<String>>,
+
+    /// 首次发布：不查找上一个标签，--auto 和更新日志使用全部提交历史
+    #[arg(long)]
+    first_release: bool,
+}
+
+/// tag 的选项
+#[derive(Args, Debug)]
+struct TagArgs {
+    /// 重新发布版本（如果标签已存在则删除重新创建）
+    #[arg(long, short = 'r')]
+    re_publish: bool,
+
+    /// 重新发布时远程标签受保护无法删除，只警告并跳过该远程仓库，不中止发布
+    #[arg(long, requires = "re_publish")]
+    skip_protected: bool,
+
+    /// 上一个标签以来没有新的提交时不打标签也不推送，以退出码 4 结束
+    #[arg(long)]
+    tag_only_if_changed: bool,
+
+    /// 创建轻量标签而不是附注标签
+    #[arg(long, conflicts_with = "tag_message")]
+    lightweight: bool,
+
+    /// 附注标签的信息模板，占位符与 release 的 --tag-message 相同
+    #[arg(long, default_value = "Version {version}", value_name = "TEMPLATE")]
+    tag_message: String,
+
+    /// 签名标签，签名方式（GPG/SSH）遵循 git 的 gpg.format 配置
+    #[arg(long, short = 's', conflicts_with = "lightweight")]
+    sign: bool,
+
+    /// 签名使用的密钥 ID（SSH 签名时为密钥文件路径），默认使用 git 的 user.signingkey
+    #[arg(long, value_name = "KEYID", requires = "sign")]
+    signing_key: Option<String>,
+
+    /// 打标签前通过 git ls-remote 检查远程仓库上是否已存在同名标签
+    #[arg(long)]
+    check_remote_tags: bool,
+
+    /// 打标签前确认本次的全部标签在本地和每个要推送的远程仓库上都不存在，存在时中止
+    #[arg(long, conflicts_with = "re_publish")]
+    verify_tag_absent_everywhere: bool,
+
+    /// 检查前先通过 git fetch --tags 从所有远程仓库同步标签
+    #[arg(long)]
+    fetch: bool,
+
+    /// 推送因网络等临时错误失败时的重试次数，每次重试的等待时间翻倍
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    push_retries: u32,
+
+    /// 推送时跳过 git 钩子（git push --no-verify）
+    #[arg(long)]
+    no_verify: bool,
+}
+
+/// revert 的选项
+#[derive(Args, Debug)]
+struct RevertArgs {
+    /// 要撤销的版本号，标签不存在时报错，使用 --force 忽略
+    #[arg(value_name = "VERSION")]
+    version: String,
+
+    /// 同时还原标签指向的发布提交，并将还原提交推送到远程仓库
+    #[arg(long)]
+    commit: bool,
+}
+
+/// list 的选项
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// 按依赖关系列出发布顺序（被依赖的在前），等同于 --publish-order
+    #[arg(long)]
+    publish_order: bool,
+}
+
+/// changelog 的选项
+#[derive(Args, Debug)]
+struct ChangelogArgs {
+    #[command(flatten)]
+    version: VersionArgs,
+
+    /// 更新日志从指定的标签或提交之后开始，默认为上一个标签
+    #[arg(long, value_name = "REF", conflicts_with = "first_release")]
+    since: Option<String>,
+
+    /// 更新日志中提交链接的模板，{hash} 会被替换为完整的提交 ID
+    #[arg(long, value_name = "TEMPLATE")]
+    commit_link: Option<String>,
+
+    /// 同时将更新日志写入指定文件，- 表示输出到 stdout（此时其他输出只保留警告和错误）
+    #[arg(long, value_name = "PATH|-")]
+    changelog_output: Option<PathBuf>,
+}
+
+impl Command {
+    /// 将子命令的选项合并到发布选项中
+    fn apply(self, global: &mut GlobalArgs, args: &mut ReleaseArgs) {
+        match self {
+            Self::Release(release) => *args = *release,
+            Self::Bump(version) => {
+                version.apply(args);
+                args.print_version = true;
+            }
+            Self::Tag(tag) => {
+                args.tag_only = true;
+                args.re_publish = tag.re_publish;
+                args.skip_protected = tag.skip_protected;
+                args.tag_only_if_changed = tag.tag_only_if_changed;
+                args.lightweight = tag.lightweight;
+                args.tag_message = tag.tag_message;
+                args.sign = tag.sign;
+                args.signing_key = tag.signing_key;
+                args.check_remote_tags = tag.check_remote_tags;
+                args.verify_tag_absent_everywhere = tag.verify_tag_absent_everywhere;
+                args.fetch = tag.fetch;
+                args.push_retries = tag.push_retries;
+                args.no_verify = tag.no_verify;
+            }
+            Self::Revert(revert) => {
+                args.revert = Some(revert.version);
+                // 配置文件中的 revert-commit 仍然有效
+                args.revert_commit |= revert.commit;
+            }
+            Self::List(list) => {
+                if list.publish_order {
+                    args.publish_order = true;
+                } else {
+                    args.list_crates = true;
+                }
+            }
+            Self::Changelog(changelog) => {
+                changelog.version.apply(args);
+                args.changelog = true;
+                args.since = changelog.since;
+                args.commit_link = changelog.commit_link;
+                args.changelog_output = changelog.changelog_output;
+                global.dry_run = true;
+            }
+        }
+    }
+}
+
+impl VersionArgs {
+    fn apply(self, args: &mut ReleaseArgs) {
+        args.version = self.version;
+        args.auto = self.auto;
+        args.version_from_env = self.version_from_env;
+        args.to_rc = self.to_rc;
+        args.bump_pre = self.bump_pre;
+        args.promote = self.promote;
+        args.bump_build_metadata = self.bump_build_metadata;
+        args.first_release = self.first_release;
+    }
+}
+
+/// 移动端构建号的计算方式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MobileVersionCode {
+    /// 每次发布加 1
+    Increment,
+    /// 由版本号计算：major * 1000000 + minor * 1000 + patch
+    Semver,
+}
+
+/// Helm chart 自身版本号的更新方式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartVersion {
+    /// 与发布的版本号相同
+    Release,
+    /// 在原来的 chart 版本上递增 patch
+    Patch,
+    /// 在原来的 chart 版本上递增 minor
+    Minor,
+    /// 在原来的 chart 版本上递增 major
+    Major,
+}
+
+/// 需要同步内部依赖版本要求的依赖表
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyTable {
+    /// [dependencies]
+    Dependencies,
+    /// [dev-dependencies]
+    DevDependencies,
+    /// [build-dependencies]
+    BuildDependencies,
+    /// 不处理任何依赖表
+    None,
+}
+
+impl DependencyTable {
+    fn key(self) -> Option<&'static str> {
+        match self {
+            Self::Dependencies => Some("dependencies"),
+            Self::DevDependencies => Some("dev-dependencies"),
+            Self::BuildDependencies => Some("build-dependencies"),
+            Self::None => None,
+        }
+    }
+}
+
+/// 查找 workspace 成员的方式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discovery {
+    /// 使用 [workspace].members 和 exclude 列出的成员
+    Members,
+    /// 遍历目录查找 Cargo.toml
+    Walk,
+    /// 使用 cargo metadata 给出的 workspace 成员
+    Metadata,
+}
+
+/// 结果的输出格式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 便于阅读的文本
+    Text,
+    /// 供 CI 解析的 JSON
+    Json,
+}
+
+/// Cargo.toml 之外的版本更新器
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Updater {
+    /// tauri.conf.json 和 src-tauri/tauri.conf.json，以及 --tauri-config 指定的文件
+    Tauri,
+    /// --package-json 指定的 package.json（使用 --package-lock 时包括 package-lock.json）
+    PackageJson,
+    /// pyproject.toml
+    Pyproject,
+    /// 仓库根目录下只包含版本号的 VERSION 文件
+    Version,
+    /// --readme 指定的 README 中的安装命令和版本徽章
+    Readme,
+    /// Helm 的 Chart.yaml 中的 appVersion（使用 --helm-chart-version 时还有 version）
+    Helm,
+    /// 配置文件中的 [[version-sinks]] 和 [[version-files]] 规则
+    VersionFiles,
+}
+
+impl Updater {
+    /// 控制该条目的更新器，覆盖内置条目的配置仍由对应的内置更新器控制
+    fn for_sink(sink: &VersionSink) -> Self {
+        match sink.name.as_deref() {
+            Some(TAURI_SINK) => Self::Tauri,
+            Some(PACKAGE_JSON_SINK | PACKAGE_LOCK_SINK) => Self::PackageJson,
+            Some(PYPROJECT_SINK) => Self::Pyproject,
+            Some(VERSION_SINK) => Self::Version,
+            Some(README_INSTALL_SINK | README_BADGE_SINK) => Self::Readme,
+            Some(HELM_SINK) => Self::Helm,
+            _ => Self::VersionFiles,
+        }
+    }
+}
+
+impl Cli {
+    /// 解析命令行参数，并以 .git-release.toml 和 Cargo.toml metadata 中的配置作为默认值
+    pub fn parse_with_config() -> Result<Self> {
+        let args: Vec<OsString> = std::env::args_os().collect();
+        // 配置文件和所有相对路径都基于 --path 指定的目录，需要在读取配置前切换
+        if let Some(dir) = path_arg(&args) {
+            std::env::set_current_dir(&dir)
+                .map_err(|error| anyhow!("无法进入目录 {}: {}", dir.display(), error))?;
+        }
+        let subcommand = Self::subcommand_name(&args).unwrap_or_else(|error| error.exit());
+        let args = config::args_with_config(args, subcommand.as_deref())?;
+        let mut cli = Self::parse_from(args);
+        cli.resolve_command();
+        cli.release.version_sinks = config::version_sinks()?;
+        cli.release.tag_prefixes = config::tag_prefixes()?;
+        Ok(cli)
+    }
+
+    /// 用户给出的子命令，子命令之前出现了全局选项以外的参数时报错
+    ///
+    /// 此时还没有合并配置文件，缺少必需参数等其他错误留给完整解析时报告
+    fn subcommand_name(args: &[OsString]) -> Result<Option<String>, clap::Error> {
+        let mut command = Self::command().ignore_errors(true);
+        let Ok(matches) = command.try_get_matches_from_mut(args) else {
+            return Ok(None);
+        };
+        let Some(name) = matches.subcommand_name() else {
+            return Ok(None);
+        };
+        let misplaced = command
+            .get_arguments()
+            .filter(|arg| !arg.is_global_set())
+            .find(|arg| {
+                matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            });
+        if let Some(arg) = misplaced {
+            let arg = match arg.get_long() {
+                Some(long) => format!("--{}", long),
+                None => arg.get_value_names().map_or_else(
+                    || arg.get_id().to_string(),
+                    |names| {
+                        names
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    },
+                ),
+            };
+            return Err(command.error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "{} 不是全局选项，不能写在子命令 {} 之前，请写在子命令之后或省略子命令",
+                    arg, name
+                ),
+            ));
+        }
+        Ok(Some(name.to_string()))
+    }
+
+    /// 将子命令的选项合并到发布选项中，之后只需读取 global 和 release
+    fn resolve_command(&mut self) {
+        if let Some(command) = self.command.take() {
+            command.apply(&mut self.global, &mut self.release);
+        }
+    }
+
+    /// 指定了 --generate-completions 时输出补全脚本，返回是否已输出
+    pub fn print_completions(&self) -> bool {
+        let Some(shell) = self.release.generate_completions else {
+            return false;
+        };
+        let mut command = Self::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        true
+    }
+
+    /// 使用的 git 可执行文件：--git-bin > GIT 环境变量 > git
+    pub fn git_bin(&self) -> PathBuf {
+        self.global.git_bin()
+    }
+
+    /// 根据 --verbose/--quiet 计算日志级别
+    pub fn log_level(&self) -> LevelFilter {
+        // JSON 输出和更新日志输出到 stdout 时 stdout 只保留结果
+        if self.global.quiet
+            || self.global.format == OutputFormat::Json
+            || self.release.changelog_to_stdout()
+        {
+            return LevelFilter::Warn;
+        }
+        match self.global.verbose {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    /// 是否允许彩色输出，最终是否着色还取决于终端和 NO_COLOR
+    pub fn color(&self) -> bool {
+        !self.global.no_color
+    }
+}
+
+impl GlobalArgs {
+    fn git_bin(&self) -> PathBuf {
+        self.git_bin
+            .clone()
+            .or_else(|| {
+                std::env::var_os("GIT")
+                    .filter(|bin| !bin.is_empty())
+                    .map(PathBuf::from)
+            })
+            .unwrap_or_else(|| PathBuf::from("git"))
+    }
+}
+
+impl ReleaseArgs {
+    /// 是否运行指定的版本更新器
+    fn runs_updater(&self, updater: Updater) -> bool {
+        if (updater == Updater::Tauri && self.no_tauri)
+            || (updater == Updater::Helm && self.no_helm)
+        {
+            return false;
+        }
+        if !self.updater.is_empty() {
+            return self.updater.contains(&updater);
+        }
+        !self.skip_updater.contains(&updater)
+    }
+
+    /// --changelog-output - 时更新日志输出到 stdout
+    fn changelog_to_stdout(&self) -> bool {
+        self.changelog_output
+            .as_deref()
+            .is_some_and(|path| path == Path::new("-"))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CargoToml {
+    package: Option<CargoPackage>,
+    workspace: Option<CargoWorkspace>,
+    #[serde(flatten)]
+    other: toml::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: PackageVersion,
+    #[serde(flatten)]
+    other: toml::Value,
+}
+
+/// package.version，可以是具体版本号，也可以继承 workspace 版本
+///
+/// `version.workspace = true` 和 `version = { workspace = true }` 解析后是同一个表
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum PackageVersion {
+    Literal(String),
+    Inherited { workspace: bool },
+}
+
+impl<'de> Deserialize<'de> for PackageVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::String(version) => Ok(Self::Literal(version)),
+            toml::Value::Table(table) => match table.get("workspace") {
+                Some(toml::Value::Boolean(true)) => Ok(Self::Inherited { workspace: true }),
+                _ => Err(D::Error::custom(
+                    "package.version 为表时只能是 { workspace = true }",
+                )),
+            },
+            other => Err(D::Error::custom(format!(
+                "package.version 应为版本号字符串或 {{ workspace = true }}，实际为 {}",
+                other.type_str()
+            ))),
+        }
+    }
+}
+
+impl PackageVersion {
+    fn into_literal(self) -> Option<String> {
+        match self {
+            Self::Literal(version) => Some(version),
+            Self::Inherited { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CargoWorkspace {
+    members: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    #[serde(rename = "default-members")]
+    default_members: Option<Vec<String>>,
+    package: Option<WorkspacePackage>,
+    #[serde(flatten)]
+    other: toml::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkspacePackage {
+    version: Option<String>,
+    #[serde(flatten)]
+    other: toml::Value,
+}
+
+/// 命令行中 --path/-C 的值，出现多次时以最后一次为准
+fn path_arg(args: &[OsString]) -> Option<PathBuf> {
+    let mut path = None;
+    let mut args = args.iter().skip(1).take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == "--path" || arg == "-C" {
+            path = args.next().map(PathBuf::from);
+        } else if let Some(value) = arg.strip_prefix("--path=") {
+            path = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("-C").filter(|value| !value.is_empty()) {
+            path = Some(PathBuf::from(value.trim_start_matches('=')));
+        }
+    }
+    path
+}
+
+/// git tag -v 输出中说明签名结果和签名者的行
+fn is_signature_summary(line: &str) -> bool {
+    let line = line.trim_start_matches("gpg:").trim();
+    line.starts_with("Good")
+        || line.starts_with("using")
+        || line.starts_with("Signature made")
+        || line.starts_with("aka")
+}
+
+/// 文件内容含有 NUL 或不是有效的 UTF-8 时视为二进制文件，返回 `None`
+fn text_content(bytes: &[u8]) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// 条目的 path 中含有 glob 通配符时展开为每个匹配的文件各一个条目
+fn expand_sink_paths(sink: VersionSink) -> Result<Vec<VersionSink>> {
+    let pattern = sink.path.to_string_lossy().into_owned();
+    if !pattern.contains(['*', '?', '[']) {
+        return Ok(vec![sink]);
+    }
+
+    let mut sinks = Vec::new();
+    for path in glob::glob(&pattern)
+        .map_err(|error| error::validation(format!("无效的 glob {}: {}", pattern, error)))?
+    {
+        sinks.push(VersionSink {
+            path: path?,
+            ..sink.clone()
+        });
+    }
+    if sinks.is_empty() {
+        warn!("⚠️  没有匹配 {} 的文件，跳过", pattern);
+    }
+    Ok(sinks)
+}
+
+/// --manifest-path 指定的各个 workspace 根目录，未指定时为当前目录
+fn workspace_roots(manifest_paths: &[PathBuf]) -> Vec<PathBuf> {
+    if manifest_paths.is_empty() {
+        return vec![PathBuf::from(".")];
+    }
+    manifest_paths
+        .iter()
+        .map(|path| match path.parent() {
+            Some(parent) if path.file_name() == Some("Cargo.toml".as_ref()) => {
+                if parent.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    parent.to_path_buf()
+                }
+            }
+            _ => path.clone(),
+        })
+        .collect()
+}
+
+/// workspace 根目录下的文件，当前目录下的文件保持原来的相对路径
+fn root_file(root: &Path, name: &str) -> PathBuf {
+    if root == Path::new(".") {
+        PathBuf::from(name)
+    } else {
+        root.join(name)
+    }
+}
+
+/// 读取文件内容，文件不存在时为 `None`，其它读取错误照常返回
+fn read_existing(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(anyhow!("读取 {} 失败: {}", path.display(), error)),
+    }
+}
+
+/// 清单中发布时需要的依赖（[dependencies] 和 [build-dependencies]，包括 [target.*] 下的）的 crate 名
+fn dependency_names(manifest: &toml::Value) -> Vec<String> {
+    let targets = manifest
+        .get("target")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|targets| targets.values());
+    let mut names = Vec::new();
+    for tables in std::iter::once(manifest).chain(targets) {
+        for table in ["dependencies", "build-dependencies"] {
+            let Some(dependencies) = tables.get(table).and_then(toml::Value::as_table) else {
+                continue;
+            };
+            for (key, dependency) in dependencies {
+                // 重命名的依赖通过 package 指定真实的 crate 名
+                let name = dependency
+                    .get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(key);
+                if !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// 替换模板中的 {env:NAME} 和 {env:NAME:-默认值}，`what` 用于错误信息
+fn expand_env(template: &str, what: &str) -> Result<String> {
+    let placeholder = Regex::new(r"\{env:([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}")?;
+    let mut expanded = String::with_capacity(template.len());
+    let mut last = 0;
+    for captures in placeholder.captures_iter(template) {
+        let Some(whole) = captures.get(0) else {
+            continue;
+        };
+        let name = &captures[1];
+        // 与 shell 的 ${NAME:-默认值} 一致，变量为空时也使用默认值
+        let value = match (std::env::var(name), captures.get(2)) {
+            (Ok(value), Some(default)) if value.is_empty() => default.as_str().to_string(),
+            (Ok(value), _) => value,
+            (Err(_), Some(default)) => default.as_str().to_string(),
+            (Err(error), None) => {
+                return Err(error::validation(format!(
+                    "{}使用了 {}，但环境变量 {} {}\n可以使用 {{env:{}:-默认值}} 指定默认值",
+                    what,
+                    whole.as_str(),
+                    name,
+                    match error {
+                        std::env::VarError::NotPresent => "未设置",
+                        std::env::VarError::NotUnicode(_) => "不是有效的 UTF-8",
+                    },
+                    name
+                )));
+            }
+        };
+        expanded.push_str(&template[last..whole.start()]);
+        expanded.push_str(&value);
+        last = whole.end();
+    }
+    expanded.push_str(&template[last..]);
+    Ok(expanded)
+}
+
+/// 用于提示的键路径列表，例如 `project.version 或 tool.poetry.version`
+fn key_names(keys: &[Vec<String>]) -> String {
+    keys.iter()
+        .map(|key| key.join("."))
+        .collect::<Vec<_>>()
+        .join(" 或 ")
+}
+
+/// 替换 TOML 中的字符串值并保留其前后的空白和注释，返回旧值
+fn replace_toml_string(item: &mut toml_edit::Item, value: &str) -> Option<String> {
+    let current = item.as_value_mut()?;
+    let old = current.as_str()?.to_string();
+    let decor = current.decor().clone();
+    *current = toml_edit::Value::from(value);
+    *current.decor_mut() = decor;
+    Some(old)
+}
+
+/// 待写入的文件改动
+struct FileChange {
+    path: PathBuf,
+    /// 文件原来的内容，`None` 表示文件由本次发布创建
+    original: Option<String>,
+    updated: String,
+    /// 文件中原来的版本号，更新日志等不包含版本号的文件为 `None`
+    old_version: Option<String>,
+}
+
+impl FileChange {
+    /// 改动的行数（新增 + 删除）
+    fn changed_lines(&self) -> usize {
+        TextDiff::from_lines(self.original_text(), &self.updated)
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Equal)
+            .count()
+    }
+
+    fn original_text(&self) -> &str {
+        self.original.as_deref().unwrap_or_default()
+    }
+
+    fn is_unchanged(&self) -> bool {
+        self.original.as_deref() == Some(self.updated.as_str())
+    }
+}
+
+/// 文件写入前的内容，`None` 表示文件原本不存在
+struct FileBackup {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+/// 单个 Cargo.toml 的更新计划
+enum CrateUpdate {
+    /// 不包含 [package]，例如纯 workspace 清单
+    NotPackage,
+    /// 使用 `version.workspace = true`
+    Inherited(String, PathBuf),
+    Skipped(String, SkipReason),
+    Updated {
+        name: String,
+        old_version: String,
+        /// 已确认 crates.io 上未发布该版本
+        unpublished: bool,
+        change: FileChange,
+    },
+}
+
+enum SkipReason {
+    Excluded,
+    NotInOnly,
+    PathExcluded,
+    NotInOnlyPath,
+    NotDefaultMember,
+    Published,
+    /// 自该标签以来目录下没有改动
+    Unchanged(String),
+    /// 设置了 publish = false
+    PublishDisabled,
+}
+
+impl SkipReason {
+    /// 用于 --list-crates 的简短说明
+    fn describe(&self) -> String {
+        match self {
+            Self::Excluded => "匹配 --exclude".to_string(),
+            Self::NotInOnly => "不在 --only 列表中".to_string(),
+            Self::PathExcluded => "目录匹配 --exclude-path".to_string(),
+            Self::NotInOnlyPath => "目录不匹配 --only-path".to_string(),
+            Self::NotDefaultMember => "不在 default-members 中".to_string(),
+            Self::Published => "crates.io 已发布".to_string(),
+            Self::Unchanged(tag) => format!("自 {} 以来没有改动", tag),
+            Self::PublishDisabled => "publish = false".to_string(),
+        }
+    }
+}
+
+/// 一次发布的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReleaseReport {
+    /// 最终的版本号
+    pub version: String,
+    /// 标签名，按 crate 分别打标签时为空
+    pub tag: String,
+    /// 本次发布的全部标签
+    pub tags: Vec<String>,
+    /// 推送的分支
+    pub branch: String,
+    /// 修改过的文件，干运行时为将要修改的文件
+    pub updated_files: Vec<PathBuf>,
+    /// 每个远程仓库的推送结果，干运行时为将要推送的远程仓库
+    pub pushes: Vec<PushOutcome>,
+    pub dry_run: bool,
+    /// --auto 模式下没有需要发布的提交
+    pub nothing_to_release: bool,
+    /// 创建 GitHub Release 的仓库（owner/repo）
+    pub github_repo: Option<String>,
+    /// 更新了版本号的 crate，包括继承 workspace 版本的 crate
+    pub crates: Vec<CrateVersion>,
+    /// --revert 删除的标签，远程标签的形式为 remote/tag
+    pub removed_tags: Vec<String>,
+    /// --revert-commit 还原的发布提交
+    pub reverted_commit: Option<String>,
+    /// 干运行得到的发布计划
+    #[serde(skip)]
+    pub plan: Option<ReleasePlan>,
+    /// --list-crates 列出的 crate
+    pub listed_crates: Vec<CrateListing>,
+    /// --publish-order 计算的发布顺序，不参与发布的 crate 排在最后
+    pub publish_order: Vec<PublishStep>,
+    /// --verify-publish 对每个 crate 的检查结果
+    pub publish_checks: Vec<PublishCheck>,
+}
+
+/// --list-crates 列出的单个 crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateListing {
+    pub name: String,
+    /// Cargo.toml 相对于仓库根目录的路径
+    pub path: PathBuf,
+    /// 当前版本号，继承 workspace 版本时为根 Cargo.toml 中的版本号
+    pub version: String,
+    /// 使用 version.workspace = true
+    pub inherited: bool,
+    /// 不会被更新的原因，会被更新时为 `None`
+    pub skipped: Option<String>,
+}
+
+/// --publish-order 中的单个 crate
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishStep {
+    pub name: String,
+    /// Cargo.toml 相对于仓库根目录的路径
+    pub path: PathBuf,
+    /// 不参与发布的原因，参与发布时为 `None`
+    pub excluded: Option<String>,
+    /// 发布时需要的同一仓库中的其他 crate
+    pub dependencies: Vec<String>,
+}
+
+/// --verify-publish 中单个 crate 的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishCheck {
+    pub name: String,
+    /// cargo publish --dry-run 失败时的错误输出，成功时为 `None`
+    pub error: Option<String>,
+}
+
+/// 干运行得到的发布计划，供 CI 在审批后再执行发布
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleasePlan {
+    pub version: String,
+    pub tags: Vec<String>,
+    /// 发布提交的信息，--at 和 --tag-only 模式不提交时为 `None`
+    pub commit_message: Option<String>,
+    /// 推送的分支，--at 模式只推送标签时为 `None`
+    pub branch: Option<String>,
+    pub remotes: Vec<String>,
+    pub files: Vec<PlannedFile>,
+    pub crates: Vec<CrateVersion>,
+    pub github_repo: Option<String>,
+}
+
+/// 将要修改的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    /// 文件中原来的版本号，更新日志等不包含版本号的文件为 `None`
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    /// 文件的新内容
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// 单个 crate 的版本变化
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateVersion {
+    pub name: String,
+    /// Cargo.toml 相对于仓库根目录的路径
+    pub path: PathBuf,
+    pub old_version: String,
+    pub new_version: String,
+}
+
+/// 推送到单个远程仓库的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PushOutcome {
+    pub remote: String,
+    pub pushed: bool,
+    /// 是否通过 git push --atomic 一次推送了提交和标签
+    pub atomic: bool,
+    /// 推送失败时的错误信息
+    pub error: Option<String>,
+    /// --push-ref 指定的额外引用的推送结果
+    pub refs: Vec<RefPushOutcome>,
+}
+
+/// 推送单个额外引用的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RefPushOutcome {
+    pub refspec: String,
+    pub pushed: bool,
+    pub error: Option<String>,
+}
+
+/// 推送到单个远程仓库的方式和额外引用的结果
+#[derive(Default)]
+struct RemotePush {
+    /// 是否使用了原子推送
+    atomic: bool,
+    refs: Vec<RefPushOutcome>,
+}
+
+pub struct ReleaseTool {
+    args: ReleaseArgs,
+    global: GlobalArgs,
+    version: String,
+    /// 本次发布生成的更新日志正文
+    changelog: Option<String>,
+    /// 已存在同名标签的远程仓库
+    remote_tags: Vec<String>,
+    /// 本次运行是否已成功推送过任何内容
+    pushed_any: Cell<bool>,
+    git: Box<dyn Git>,
+    changes: Vec<FileChange>,
+    updated_files: Vec<PathBuf>,
+    backups: Vec<FileBackup>,
+    crate_names: Vec<String>,
+    crate_versions: Vec<CrateVersion>,
+    /// apply 时使用计划中的提交信息
+    commit_message_override: Option<String>,
+    /// 根 Cargo.toml 中更新前的 workspace.package.version
+    workspace_version: Option<String>,
+    /// --idempotent 时上次运行已完成提交和打标签
+    already_released: bool,
+    /// 正在处理的 workspace 的根目录
+    root: PathBuf,
+    /// 执行发布计划期间写出的计划 JSON，钩子通过 RELEASE_PLAN_FILE 读取
+    plan_file: Option<TempFile>,
+    /// --dry-run --at 预览的提交，读取清单和查询提交历史时代替 HEAD 和工作区
+    preview: Option<String>,
+}
+
+impl ReleaseTool {
+    pub fn new(args: Cli) -> Self {
+        let git = Git2Backend::new(".")
+            .with_git_bin(args.git_bin())
+            .with_quiet(args.global.quiet_git);
+        Self::with_git(args, Box::new(git))
+    }
+
+    /// 使用自定义的 Git 实现，便于嵌入其他工具或在测试中替换
+    pub fn with_git(mut cli: Cli, git: Box<dyn Git>) -> Self {
+        cli.resolve_command();
+        let Cli {
+            global,
+            release: args,
+            ..
+        } = cli;
+        Self {
+            version: args.version.clone().unwrap_or_default(),
+            changelog: None,
+            remote_tags: Vec::new(),
+            pushed_any: Cell::new(false),
+            git,
+            changes: Vec::new(),
+            updated_files: Vec::new(),
+            backups: Vec::new(),
+            crate_names: Vec::new(),
+            crate_versions: Vec::new(),
+            commit_message_override: None,
+            workspace_version: None,
+            already_released: false,
+            root: workspace_roots(&global.manifest_path).swap_remove(0),
+            plan_file: None,
+            preview: None,
+            args,
+            global,
+        }
+    }
+
+    /// 执行发布并输出结果摘要
+    pub fn run(&mut self) -> Result<(), ReleaseError> {
+        self.run_and_print().map_err(ReleaseError::from)
+    }
+
+    /// 执行发布，返回结构化的结果而不输出摘要
+    pub fn run_with_report(&mut self) -> Result<ReleaseReport, ReleaseError> {
+        self.release().map_err(ReleaseError::from)
+    }
+
+    /// 计算本次发布要做的全部事情：版本号、要修改的文件及其新内容、提交信息、标签和远程仓库
+    ///
+    /// 只读取清单、工作区和本地的引用，不修改任何文件，也不访问远程仓库或执行外部命令；
+    /// 同步检查、远程标签检查和 cargo metadata 清单检查等由 [`apply`](Self::apply) 执行。
+    /// 显式要求的 --only-if-version-greater-on-registry 和 --discovery metadata 除外。
+    /// --auto 模式下没有需要发布的提交时返回 `None`
+    pub fn plan(&mut self) -> Result<Option<ReleasePlan>, ReleaseError> {
+        self.plan_release(false).map_err(ReleaseError::from)
+    }
+
+    /// 执行发布计划：完成发布前检查后写入文件、提交、创建标签、推送并创建 GitHub Release
+    ///
+    /// 计划中文件的新内容、提交信息和远程仓库可以在调用前修改，其余字段只用于展示
+    pub fn apply(&mut self, plan: ReleasePlan) -> Result<ReleaseReport, ReleaseError> {
+        self.preflight()
+            .and_then(|()| self.apply_plan(plan))
+            .map_err(ReleaseError::from)
+    }
+
+    fn run_and_print(&mut self) -> Result<()> {
+        let report = self.release()?;
+
+        if self.global.format == OutputFormat::Json {
+            let json = match &report.plan {
+                Some(plan) => serde_json::to_string_pretty(plan)?,
+                None if self.args.list_crates => {
+                    serde_json::to_string_pretty(&report.listed_crates)?
+                }
+                None if self.args.publish_order => {
+                    serde_json::to_string_pretty(&report.publish_order)?
+                }
+                None => serde_json::to_string_pretty(&report)?,
+            };
+            println!("{}", json);
+        } else if report.nothing_to_release {
+            info!("✅ 自上一个标签以来没有需要发布的提交");
+        } else if self.args.print_version {
+            println!("{}", report.version);
+        } else if self.args.list_crates {
+            Self::print_crate_list(&report.listed_crates);
+        } else if self.args.publish_order {
+            Self::print_publish_order(&report.publish_order);
+        } else if self.args.revert.is_some() {
+            Self::print_revert_summary(&report);
+        } else if self.preview.is_some() {
+            info!("✅ 预览完成，未修改任何文件");
+            Self::print_crate_versions(&report.crates);
+            if !report.tags.is_empty() {
+                info!("   标签: {}", report.tags.join(", "));
+            }
+        } else if report.dry_run {
+            info!("✅ 干运行模式完成，未修改任何文件");
+            if !self.skips_commit() {
+                info!("   提交信息: {}", self.commit_summary());
+            }
+            self.print_summary(&report);
+        } else {
+            if self.args.prepare {
+                info!(
+                    "✅ 发布分支已推送: {}，合并后使用 --finalize 创建标签",
+                    report.branch
+                );
+            } else {
+                info!("✅ 版本发布成功: {}", report.version);
+            }
+            self.print_summary(&report);
+        }
+
+        if report.nothing_to_release && self.args.tag_only_if_changed {
+            return Err(error::nothing_to_release(
+                "自上一个标签以来没有需要发布的提交",
+            ));
+        }
+        Ok(())
+    }
+
+    fn print_crate_list(crates: &[CrateListing]) {
+        if crates.is_empty() {
+            info!("✅ 没有找到任何 crate");
+            return;
+        }
+
+        let paths: Vec<String> = crates
+            .iter()
+            .map(|krate| krate.path.display().to_string())
+            .collect();
+        let name_width = crates
+            .iter()
+            .map(|krate| krate.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let path_width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+        let version_width = crates
+            .iter()
+            .map(|krate| krate.version.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let updated = crates
+            .iter()
+            .filter(|krate| krate.skipped.is_none())
+            .count();
+        info!("📦 共 {} 个 crate，{} 个会被更新:", crates.len(), updated);
+        for (krate, path) in crates.iter().zip(&paths) {
+            let mut notes = Vec::new();
+            if krate.inherited {
+                notes.push("继承 workspace 版本".to_string());
+            }
+            if let Some(reason) = &krate.skipped {
+                notes.push(format!("跳过: {}", reason));
+            }
+            let marker = if krate.skipped.is_none() {
+                "✅"
+            } else {
+                "⏭️ "
+            };
+            let line = format!(
+                "   {} {:<name_width$}  {:<path_width$}  {:<version_width$}  {}",
+                marker,
+                krate.name,
+                path,
+                krate.version,
+                notes.join("，")
+            );
+            info!("{}", line.trim_end());
+        }
+    }
+
+    fn print_publish_order(steps: &[PublishStep]) {
+        if steps.is_empty() {
+            info!("✅ 没有找到任何 crate");
+            return;
+        }
+
+        let paths: Vec<String> = steps
+            .iter()
+            .map(|step| step.path.display().to_string())
+            .collect();
+        let name_width = steps
+            .iter()
+            .map(|step| step.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let path_width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let published = steps.iter().filter(|step| step.excluded.is_none()).count();
+        info!("📦 发布顺序（共 {} 个 crate）:", published);
+        for (index, (step, path)) in steps.iter().zip(&paths).enumerate() {
+            let line = match &step.excluded {
+                None => format!("   {:>3}. {:<name_width$}  {}", index + 1, step.name, path),
+                Some(reason) => format!(
+                    "⏭️  不发布 {:<name_width$}  {:<path_width$}  {}",
+                    step.name, path, reason
+                ),
+            };
+            info!("{}", line);
+        }
+    }
+
+    fn print_revert_summary(report: &ReleaseReport) {
+        if report.removed_tags.is_empty() && report.reverted_commit.is_none() {
+            info!("✅ 没有需要撤销的内容");
+            return;
+        }
+        if report.dry_run {
+            info!("✅ 干运行模式完成，未修改任何内容，将会撤销:");
+        } else {
+            info!("✅ 已撤销版本 {}:", report.version);
+        }
+        for tag in &report.removed_tags {
+            info!("   删除标签: {}", tag);
+        }
+        if let Some(commit) = &report.reverted_commit {
+            info!("   还原提交: {}", commit);
+        }
+    }
+
+    /// 输出每个 crate 的版本变化，名称、路径和旧版本号分别对齐
+    fn print_crate_versions(crates: &[CrateVersion]) {
+        if crates.is_empty() {
+            return;
+        }
+        let paths: Vec<String> = crates
+            .iter()
+            .map(|krate| krate.path.display().to_string())
+            .collect();
+        let name_width = crates
+            .iter()
+            .map(|krate| krate.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let path_width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+        let old_width = crates
+            .iter()
+            .map(|krate| krate.old_version.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        info!("📋 版本变化:");
+        for (krate, path) in crates.iter().zip(&paths) {
+            info!(
+                "   {:<name_width$}  {:<path_width$}  {:>old_width$} -> {}",
+                krate.name, path, krate.old_version, krate.new_version
+            );
+        }
+    }
+
+    /// 在最后输出各 crate 的版本变化，以及标签和推送的远程仓库
+    fn print_summary(&self, report: &ReleaseReport) {
+        Self::print_crate_versions(&report.crates);
+
+        let remotes: Vec<&str> = report
+            .pushes
+            .iter()
+            .map(|push| push.remote.as_str())
+            .collect();
+        if !report.tags.is_empty() {
+            info!("   标签: {}", report.tags.join(", "));
+        }
+        let branch = if self.pushes_tags_only() {
+            "仅标签"
+        } else {
+            &report.branch
+        };
+        info!("   推送到: {} ({})", remotes.join(", "), branch);
+        for push in &report.pushes {
+            for extra in &push.refs {
+                match &extra.error {
+                    Some(error) => info!("   ❌ {} -> {}: {}", extra.refspec, push.remote, error),
+                    None => info!("   额外引用: {} -> {}", extra.refspec, push.remote),
+                }
+            }
+        }
+        if let Some(repo) = &report.github_repo {
+            match self.args.prepare {
+                true => info!("   GitHub Pull Request: {}", repo),
+                false => info!("   GitHub Release: {}", repo),
+            }
+        }
+    }
+
+    fn release(&mut self) -> Result<ReleaseReport> {
+        if let Some(reference) = &self.args.at {
+            if self.global.dry_run {
+                let reference = reference.clone();
+                return self.preview_release(&reference);
+            }
+            if self.args.auto {
+                return Err(error::validation(
+                    "--at 为已有提交创建标签时不能使用 --auto，预览发布请同时指定 --dry-run",
+                ));
+            }
+        }
+
+        if let Some(version) = self.args.revert.clone() {
+            let mut report = ReleaseReport {
+                dry_run: self.global.dry_run,
+                ..ReleaseReport::default()
+            };
+            self.revert_release(&version, &mut report)?;
+            return Ok(report);
+        }
+
+        if self.args.list_crates {
+            return Ok(ReleaseReport {
+                listed_crates: self.list_crates()?,
+                ..ReleaseReport::default()
+            });
+        }
+
+        if self.args.publish_order {
+            return Ok(ReleaseReport {
+                publish_order: self.publish_order()?,
+                ..ReleaseReport::default()
+            });
+        }
+
+        if self.args.print_version {
+            // 只输出版本号，没有可以输出的 JSON
+            if self.global.format == OutputFormat::Json {
+                return Err(error::validation(
+                    "--print-version 和 bump 只输出版本号，不能与 --format json 一起使用",
+                ));
+            }
+            let mut report = ReleaseReport::default();
+            if !self.resolve_release()? {
+                report.nothing_to_release = true;
+                return Ok(report);
+            }
+            report.version = self.version.clone();
+            if !self.per_crate_tags() {
+                report.tag = self.tag_name();
+            }
+            return Ok(report);
+        }
+
+        let Some(plan) = self.plan_release(true)? else {
+            return Ok(ReleaseReport {
+                dry_run: self.global.dry_run,
+                nothing_to_release: true,
+                ..ReleaseReport::default()
+            });
+        };
+        if self.global.dry_run {
+            return self.print_plan(plan);
+        }
+        if self.args.print_plan || self.args.confirm || self.args.yes {
+            Self::print_release_plan(&plan);
+        }
+        if self.args.confirm && !self.args.yes {
+            Self::confirm_release()?;
+        }
+        self.apply_plan(plan)
+    }
+
+    /// --dry-run --at：把指定引用视为 HEAD，只通过 Git 读取该提交中的清单和历史，计算发布的
+    /// 版本号、有改动的 crate 和更新日志的范围。不检出该引用，也不写入工作区和仓库
+    fn preview_release(&mut self, reference: &str) -> Result<ReleaseReport> {
+        self.check_git_repo()?;
+        let commit = self.git.resolve_commit(reference)?;
+        let short = commit[..commit.len().min(7)].to_string();
+        self.preview = Some(commit.clone());
+
+        let mut report = ReleaseReport {
+            dry_run: true,
+            ..ReleaseReport::default()
+        };
+        if !self.resolve_release()? {
+            report.nothing_to_release = true;
+            return Ok(report);
+        }
+        info!(
+            "🔭 预览 {} ({}) 上的发布: {}",
+            reference, short, self.version
+        );
+
+        let root_manifest = self.read_root_manifest()?;
+        let root: toml::Table = toml::from_str(&root_manifest)?;
+        let current = self.current_version()?;
+        let root_dir = self.tree_path(&self.root)?;
+        let changed = match self.previous_tag(&self.global.tag_prefix)? {
+            Some(tag) => {
+                let commits = self.git.commits_since(Some(&tag), &commit)?;
+                info!(
+                    "   更新日志范围: {}..{} ({} 个提交)",
+                    tag,
+                    short,
+                    commits.len()
+                );
+                Some(self.git.changed_paths(&tag, &commit)?)
+            }
+            None => {
+                let commits = self.git.commits_since(None, &commit)?;
+                info!("   更新日志范围: 全部历史 ({} 个提交)", commits.len());
+                None
+            }
+        };
+
+        let manifests = self.preview_manifests(&commit, &root_dir, &root)?;
+        let dirs: Vec<&Path> = manifests
+            .iter()
+            .map(|manifest| manifest.parent().unwrap_or(Path::new("")))
+            .collect();
+        // 修改过的文件归属于包含它的最深的 crate 目录，根 crate 不包括其他成员目录下的改动
+        let owner = |path: &Path| {
+            dirs.iter()
+                .filter(|dir| path.starts_with(dir))
+                .max_by_key(|dir| dir.components().count())
+                .copied()
+        };
+        let mut changed_crates = Vec::new();
+        for (manifest, dir) in manifests.iter().zip(&dirs) {
+            let Some(content) = self.git.read_file(&commit, manifest)? else {
+                continue;
+            };
+            let cargo: toml::Table = toml::from_str(&content).map_err(|error| {
+                anyhow!("解析 {}:{} 失败: {}", short, manifest.display(), error)
+            })?;
+            let Some(package) = cargo.get("package") else {
+                continue;
+            };
+            let Some(name) = package.get("name").and_then(toml::Value::as_str) else {
+                continue;
+            };
+            let old_version = match package.get("version") {
+                Some(toml::Value::String(version)) => version.clone(),
+                // version.workspace = true 继承 workspace 版本
+                Some(toml::Value::Table(_)) => current.clone(),
+                _ => continue,
+            };
+
+            if changed
+                .as_ref()
+                .is_none_or(|paths| paths.iter().any(|path| owner(path) == Some(*dir)))
+            {
+                changed_crates.push(name.to_string());
+            }
+            report.crates.push(CrateVersion {
+                name: name.to_string(),
+                path: manifest
+                    .strip_prefix(&root_dir)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|_| manifest.clone()),
+                old_version,
+                new_version: self.version.clone(),
+            });
+        }
+        if changed.is_some() {
+            match changed_crates.is_empty() {
+                true => info!("   有改动的 crate: 无"),
+                false => info!("   有改动的 crate: {}", changed_crates.join(", ")),
+            }
+        }
+
+        report.version = self.version.clone();
+        if !self.per_crate_tags() {
+            report.tag = self.tag_name();
+            report.tags = vec![report.tag.clone()];
+        }
+        if self
+            .git
+            .resolve_commit(&format!("refs/heads/{}", reference))
+            .is_ok()
+        {
+            report.branch = reference.to_string();
+        }
+        Ok(report)
+    }
+
+    /// 预览的提交中 workspace 成员的清单，路径相对于仓库根目录。
+    /// 与 --discovery members 一致：[workspace].members 和 exclude 决定成员，没有 members 时
+    /// 使用目录树中的全部 Cargo.toml，根目录不是 workspace 时只有根 Cargo.toml
+    fn preview_manifests(
+        &self,
+        commit: &str,
+        root_dir: &Path,
+        root: &toml::Table,
+    ) -> Result<Vec<PathBuf>> {
+        let root_cargo_path = root_dir.join("Cargo.toml");
+        let Some(workspace) = root.get("workspace") else {
+            return Ok(vec![root_cargo_path]);
+        };
+        let patterns = |key: &str| -> Result<Option<Vec<Pattern>>> {
+            let Some(entries) = workspace.get(key).and_then(toml::Value::as_array) else {
+                return Ok(None);
+            };
+            entries
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .map(|entry| {
+                    let entry = entry.trim_start_matches("./").trim_end_matches('/');
+                    Pattern::new(entry).map_err(|error| {
+                        anyhow!("workspace.{} 中的路径无效: {}: {}", key, entry, error)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(Some)
+        };
+        let members = patterns("members")?;
+        let exclude = patterns("exclude")?.unwrap_or_default();
+
+        let mut manifests = Vec::new();
+        for path in self.git.tree_files(commit)? {
+            if path.file_name() != Some(OsStr::new("Cargo.toml")) {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root_dir) else {
+                continue;
+            };
+            let dir = relative.parent().unwrap_or(Path::new(""));
+            let is_member = match &members {
+                _ if dir.as_os_str().is_empty() => root.contains_key("package"),
+                Some(members) => {
+                    members.iter().any(|member| member.matches_path(dir))
+                        && !exclude.iter().any(|excluded| {
+                            dir.ancestors()
+                                .any(|ancestor| excluded.matches_path(ancestor))
+                        })
+                }
+                None => true,
+            };
+            if is_member {
+                manifests.push(path);
+            }
+        }
+        manifests.sort();
+        Ok(manifests)
+    }
+
+    /// 当前目录下的路径在仓库目录树中对应的路径，用于从提交中读取文件
+    fn tree_path(&self, path: &Path) -> Result<PathBuf> {
+        let absolute = fs::canonicalize(".")?.join(path);
+        let mut normalized = PathBuf::new();
+        for component in absolute.components() {
+            match component {
+                std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+        let toplevel = self.git.toplevel()?;
+        normalized
+            .strip_prefix(&toplevel)
+            .map(Path::to_path_buf)
+            .map_err(|_| anyhow!("{} 不在仓库 {} 中", path.display(), toplevel.display()))
+    }
+
+    /// 输出发布计划，--print-plan 和 --confirm 使用同样的格式
+    fn print_release_plan(plan: &ReleasePlan) {
+        info!("📋 发布计划:");
+
+        // 所有 crate 原来的版本号相同时显示整体的版本变化
+        let mut old_versions: Vec<&str> = plan
+            .crates
+            .iter()
+            .map(|krate| krate.old_version.as_str())
+            .collect();
+        old_versions.dedup();
+        match old_versions[..] {
+            [old_version] => info!("   版本: {} -> {}", old_version, plan.version),
+            _ => info!("   版本: {}", plan.version),
+        }
+
+        if !plan.crates.is_empty() {
+            let name_width = plan
+                .crates
+                .iter()
+                .map(|krate| krate.name.chars().count())
+                .max()
+                .unwrap_or(0);
+            let old_width = plan
+                .crates
+                .iter()
+                .map(|krate| krate.old_version.chars().count())
+                .max()
+                .unwrap_or(0);
+            info!("   crate:");
+            for krate in &plan.crates {
+                info!(
+                    "     {:<name_width$}  {:>old_width$} -> {}",
+                    krate.name, krate.old_version, krate.new_version
+                );
+            }
+        }
+
+        let files: Vec<&PlannedFile> = plan
+            .files
+            .iter()
+            .filter(|file| file.path.file_name() != Some("Cargo.toml".as_ref()))
+            .collect();
+        if !files.is_empty() {
+            let path_width = files
+                .iter()
+                .map(|file| file.path.display().to_string().chars().count())
+                .max()
+                .unwrap_or(0);
+            info!("   其他文件:");
+            for file in files {
+                let path = file.path.display().to_string();
+                match (&file.old_version, &file.new_version) {
+                    (Some(old_version), Some(new_version)) => info!(
+                        "     {:<path_width$}  {} -> {}",
+                        path, old_version, new_version
+                    ),
+                    _ => info!("     {}", path),
+                }
+            }
+        }
+
+        if let Some(message) = &plan.commit_message {
+            info!(
+                "   提交信息: {}",
+                message.lines().next().unwrap_or_default()
+            );
+        }
+        if !plan.tags.is_empty() {
+            info!("   标签: {}", plan.tags.join(", "));
+        }
+        let branch = plan.branch.as_deref().unwrap_or("仅标签");
+        info!("   推送到: {} ({})", plan.remotes.join(", "), branch);
+        if let Some(repo) = &plan.github_repo {
+            info!("   GitHub: {}", repo);
+        }
+    }
+
+    /// 在终端中询问是否继续发布，回答 y 以外的内容时中止
+    fn confirm_release() -> Result<()> {
+        use std::io::{IsTerminal, Write};
+
+        if !std::io::stdin().is_terminal() {
+            return Err(error::validation(
+                "--confirm 需要在终端中运行\n使用 --yes 跳过确认",
+            ));
+        }
+        eprint!("❓ 确认发布？[y/N] ");
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Err(error::validation("已取消发布，未修改任何文件"));
+        }
+        Ok(())
+    }
+
+    /// 计算并验证最终版本号，--auto 模式下没有需要发布的提交时返回 `false`
+    fn resolve_release(&mut self) -> Result<bool> {
+        if !self.resolve_version()? {
+            return Ok(false);
+        }
+        if self.args.tag_only_if_changed && !self.has_changes_since_last_tag()? {
+            return Ok(false);
+        }
+        if self.per_crate_tags() {
+            self.check_per_crate_tags()?;
+        }
+
+        // 验证版本号格式
+        if !self.global.force {
+            self.validate_version_format()?;
+        }
+
+        if let Some(path) = &self.args.message_file {
+            self.args.message = fs::read_to_string(path)
+                .map_err(|error| anyhow!("读取提交信息文件 {} 失败: {}", path.display(), error))?;
+        }
+        if self.args.version_token.is_empty() {
+            return Err(error::validation("--version-token 不能为空"));
+        }
+        // 环境变量在计算计划时就确定，未设置时在修改任何文件之前报错
+        self.args.message = expand_env(&self.args.message, "提交信息")?;
+        self.args.tag_message = expand_env(&self.args.tag_message, "标签信息模板")?;
+
+        if self.version.trim().is_empty() {
+            return Err(error::validation("版本号不能为空"));
+        }
+        if !self.per_crate_tags() {
+            git::check_tag_name(&self.tag_name())?;
+        }
+        Ok(true)
+    }
+
+    /// 计算发布计划，preflight 为 true 时同时执行发布前检查
+    fn plan_release(&mut self, preflight: bool) -> Result<Option<ReleasePlan>> {
+        if !self.resolve_release()? {
+            return Ok(None);
+        }
+
+        info!("🚀 开始发布版本: {}", self.version);
+
+        // 1. 检查是否是 git 仓库
+        self.check_git_repo()?;
+        self.check_release_branch()?;
+        self.check_expected_branch()?;
+
+        // 确认要推送的分支，--at 和 --finalize 模式只推送标签
+        let branch = if self.args.prepare {
+            let branch = self.prepare_branch();
+            git::check_branch_name(&branch)?;
+            Some(branch)
+        } else if !self.pushes_tags_only() {
+            Some(self.release_branch()?)
+        } else {
+            None
+        };
+
+        // 在任何网络操作之前确认 GitHub Release 所需的信息
+        let github_repo = self
+            .github_target()?
+            .map(|(_, owner, repo)| format!("{}/{}", owner, repo));
+
+        if preflight {
+            self.preflight()?;
+        }
+        self.check_tag_branch_ambiguity();
+        self.already_released = self.args.idempotent && self.is_already_released()?;
+
+        if self.already_released {
+            info!(
+                "✅ 版本 {} 已提交并创建标签 {}，只推送尚未推送的内容",
+                self.version,
+                self.tag_name()
+            );
+        } else if let Some(at) = &self.args.at {
+            // 为已有提交补打标签，不需要修改和提交版本文件
+            info!("🔎 在 {} ({}) 上创建标签", at, self.git.resolve_commit(at)?);
+        } else if self.args.tag_only {
+            info!("⏭️  仅创建标签，跳过版本文件更新和提交");
+        } else if self.args.finalize {
+            info!("🏁 为合并后的发布提交创建标签");
+        } else {
+            // 2. 检查工作区是否干净
+            if !self.is_working_tree_clean()? {
+                if !self.args.allow_dirty {
+                    let hint = if self.git.is_clean(false)? {
+                        "\n只有未跟踪的文件，使用 --allow-untracked 忽略它们"
+                    } else {
+                        ""
+                    };
+                    return Err(error::dirty_tree(format!(
+                        "工作区有未提交的更改，请先提交或暂存更改\n使用 --allow-dirty 将这些更改一起提交{}",
+                        hint
+                    )));
+                }
+                warn!("⚠️  工作区有未提交的更改，将随发布提交一起提交");
+            }
+
+            // 3. 计算版本号更新，此时还不写入任何文件
+            self.update_versions()?;
+        }
+
+        let files = self
+            .changes
+            .iter()
+            .map(|change| PlannedFile {
+                path: change
+                    .path
+                    .strip_prefix(".")
+                    .unwrap_or(&change.path)
+                    .to_path_buf(),
+                new_version: change.old_version.as_ref().map(|_| self.version.clone()),
+                old_version: change.old_version.clone(),
+                content: change.updated.clone(),
+            })
+            .collect();
+
+        Ok(Some(ReleasePlan {
+            version: self.version.clone(),
+            tags: if self.args.prepare {
+                Vec::new()
+            } else {
+                self.tag_names()
+            },
+            commit_message: (!self.skips_commit()).then(|| self.commit_message()),
+            branch,
+            remotes: self.remotes()?,
+            files,
+            crates: self.crate_versions.clone(),
+            github_repo,
+        }))
+    }
+
+    /// 需要访问远程仓库或执行外部命令的发布前检查，plan() 不执行这些检查
+    fn preflight(&mut self) -> Result<()> {
+        if self.args.sign {
+            self.check_signing_key()?;
+        }
+
+        // 同步远程标签，确保标签是否已存在的判断准确
+        if self.args.fetch {
+            self.fetch_tags()?;
+        }
+
+        // 确认本地分支不落后于远程分支
+        self.check_up_to_date()?;
+
+        // 本地可能没有拉取全部标签，提交前确认远程仓库上的标签
+        let already_released = self.args.idempotent && self.is_already_released()?;
+        if self.args.check_remote_tags && !already_released {
+            self.check_remote_tags()?;
+        }
+        if self.args.verify_tag_absent_everywhere {
+            self.verify_tags_absent()?;
+        }
+
+        if self.args.require_signed_commits {
+            self.check_signed_commits()?;
+        }
+
+        // 修改任何文件前确认所有清单都有效
+        if !self.skips_commit() && !already_released {
+            for root in workspace_roots(&self.global.manifest_path) {
+                let root_cargo_path = root_file(&root, "Cargo.toml");
+                if root_cargo_path.exists() {
+                    Self::check_manifests(&root_cargo_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_plan(&mut self, plan: ReleasePlan) -> Result<ReleaseReport> {
+        self.install_plan(&plan)?;
+        // 出错时进程会直接退出，需要在返回前删除临时文件
+        if !self.args.run.is_empty() || self.args.attest.is_some() {
+            let json = serde_json::to_vec_pretty(&plan)?;
+            self.plan_file = Some(
+                TempFile::create("git-release-plan", "json", &json)
+                    .map_err(|error| anyhow!("写入发布计划临时文件失败: {}", error))?,
+            );
+        }
+        let result = self.execute_plan(plan);
+        self.plan_file = None;
+        result
+    }
+
+    fn execute_plan(&mut self, plan: ReleasePlan) -> Result<ReleaseReport> {
+        let mut report = self.report_for(&plan);
+        let github = self.github_target()?;
+
+        let mut head_before = None;
+        let mut base_branch = None;
+        if !self.skips_commit() {
+            // 记录提交前的 HEAD，推送被拒绝时据此撤销发布提交
+            if self.args.rollback_on_push_failure {
+                head_before = Some(self.git.resolve_commit("HEAD")?);
+            }
+            // 两阶段发布在新分支上提交，不影响当前分支
+            if self.args.prepare {
+                base_branch = Some(self.switch_to_prepare_branch()?);
+            }
+            if let Err(error) = self.write_and_commit() {
+                if !self.args.no_rollback {
+                    self.rollback();
+                }
+                if let Some(base_branch) = &base_branch {
+                    self.leave_prepare_branch(base_branch);
+                }
+                return Err(error);
+            }
+
+            // 发布提交已经创建，此后的失败不会撤销提交，--rollback-on-push-failure 只处理推送被拒绝
+            self.run_hooks(HookPoint::PostCommit)?;
+            self.check_clean_after_commit()?;
+        }
+
+        // 5. 处理标签，--prepare 模式在 --finalize 时再创建标签
+        let mut created_tags = Vec::new();
+        if !self.args.prepare && !self.already_released {
+            created_tags = self.handle_tag()?;
+            self.run_hooks(HookPoint::PostTag)?;
+        }
+
+        // 生成来源证明
+        if let Some(command) = &self.args.attest {
+            self.run_attestation(command)?;
+        }
+
+        // 6. 推送到所有远程仓库
+        report.updated_files = self.updated_files.clone();
+        self.write_output_manifest(&report)?;
+        self.write_changelog_output()?;
+        report.pushes = match self.push_to_remotes() {
+            Ok(pushes) => pushes,
+            Err(error) => {
+                let rejected = error
+                    .downcast_ref::<PushError>()
+                    .is_some_and(PushError::is_rejected);
+                if self.args.rollback_on_push_failure && rejected && !self.pushed_any.get() {
+                    self.undo_release(&created_tags, head_before.as_deref());
+                }
+                return Err(error);
+            }
+        };
+
+        if let Some((token, owner, repo)) = &github {
+            if self.already_released {
+                info!("⏭️  版本已发布过，不重复创建 GitHub Release");
+            } else if let Some(base_branch) = &base_branch {
+                self.open_pull_request(token, owner, repo, base_branch)?;
+            } else {
+                self.create_github_release(token, owner, repo)?;
+            }
+        }
+
+        if self.already_released {
+            if !self.args.notify_webhook.is_empty() {
+                info!("⏭️  版本已发布过，不重复发送通知");
+            }
+        } else {
+            self.send_notifications(&report);
+        }
+
+        Ok(report)
+    }
+
+    /// 干运行时输出计划中的改动和将要执行的 git 命令
+    fn print_plan(&mut self, plan: ReleasePlan) -> Result<ReleaseReport> {
+        let mut report = self.report_for(&plan);
+
+        if !self.skips_commit() {
+            if self.args.prepare {
+                self.switch_to_prepare_branch()?;
+            }
+            self.print_changes();
+            info!("✅ 版本号更新完成，共更新 {} 个文件", self.changes.len());
+            if self.args.verify_publish {
+                report.publish_checks = self.verify_publish()?;
+            }
+        }
+
+        report.updated_files = self
+            .changes
+            .iter()
+            .map(|change| change.path.clone())
+            .collect();
+        self.write_output_manifest(&report)?;
+        self.write_changelog_output()?;
+
+        // 输出将要执行的 git 命令
+        if !self.skips_commit() {
+            self.commit_changes()?;
+        }
+        if !self.args.prepare && !self.already_released {
+            self.handle_tag()?;
+        }
+        report.pushes = self.push_to_remotes()?;
+        for url in &self.args.notify_webhook {
+            info!("🔔 将发送通知到 {}", notify::redact(url));
+        }
+        report.plan = Some(plan);
+        Ok(report)
+    }
+
+    fn report_for(&self, plan: &ReleasePlan) -> ReleaseReport {
+        ReleaseReport {
+            version: plan.version.clone(),
+            tag: if self.per_crate_tags() {
+                String::new()
+            } else {
+                self.tag_name()
+            },
+            tags: plan.tags.clone(),
+            branch: plan.branch.clone().unwrap_or_default(),
+            dry_run: self.global.dry_run,
+            github_repo: plan.github_repo.clone(),
+            crates: plan.crates.clone(),
+            ..ReleaseReport::default()
+        }
+    }
+
+    /// 使用计划中（可能已被修改的）文件内容、提交信息和远程仓库
+    fn install_plan(&mut self, plan: &ReleasePlan) -> Result<()> {
+        let mut changes = Vec::with_capacity(plan.files.len());
+        for file in &plan.files {
+            let planned = self.changes.iter().position(|change| {
+                change.path.strip_prefix(".").unwrap_or(&change.path) == file.path
+            });
+            let mut change = match planned {
+                Some(index) => self.changes.swap_remove(index),
+                None => FileChange {
+                    path: file.path.clone(),
+                    original: read_existing(&file.path)?,
+                    updated: String::new(),
+                    old_version: file.old_version.clone(),
+                },
+            };
+            change.updated = file.content.clone();
+            changes.push(change);
+        }
+        self.changes = changes;
+
+        self.commit_message_override = plan.commit_message.clone();
+        self.global.remote = plan.remotes.clone();
+        Ok(())
+    }
+
+    /// 从当前分支切换到新的发布分支，返回原来的分支
+    fn switch_to_prepare_branch(&self) -> Result<String> {
+        let base_branch = self.release_branch()?;
+        let branch = self.prepare_branch();
+        info!("🌿 创建发布分支: {} (基于 {})", branch, base_branch);
+        if self.global.dry_run {
+            self.plan_git(&["switch", "-c", &branch]);
+            return Ok(base_branch);
+        }
+
+        git::run(
+            self.git_command().args(["switch", "-q", "-c", &branch]),
+            self.global.quiet_git,
+            || format!("创建发布分支 {} 失败", branch),
+        )?;
+        Ok(base_branch)
+    }
+
+    /// 准备失败时切回原来的分支并删除发布分支
+    fn leave_prepare_branch(&self, base_branch: &str) {
+        if self.global.dry_run {
+            return;
+        }
+        let branch = self.prepare_branch();
+        let switched = git::run(
+            self.git_command().args(["switch", "-q", base_branch]),
+            self.global.quiet_git,
+            String::new,
+        )
+        .is_ok();
+        let deleted = switched
+            && git::run(
+                self.git_command().args(["branch", "-q", "-D", &branch]),
+                self.global.quiet_git,
+                String::new,
+            )
+            .is_ok();
+        if !deleted {
+            warn!(
+                "⚠️  未能清理发布分支 {}，请手动切回 {}",
+                branch, base_branch
+            );
+        }
+    }
+
+    fn open_pull_request(&self, token: &str, owner: &str, repo: &str, base: &str) -> Result<()> {
+        info!("🔀 创建 Pull Request {}/{}...", owner, repo);
+        let pull_request = github::PullRequest {
+            title: &self.commit_summary(),
+            head: &self.prepare_branch(),
+            base,
+            body: self.changelog.as_deref().unwrap_or_default(),
+        };
+        let url = github::create_pull_request(token, owner, repo, &pull_request)?;
+        info!("✅ Pull Request 已创建: {}", url);
+        Ok(())
+    }
+
+    /// 推送被拒绝时撤销本次创建的标签和提交，只处理本次运行产生的内容
+    fn undo_release(&mut self, created_tags: &[String], head_before: Option<&str>) {
+        warn!("↩️  推送被拒绝，撤销本次创建的标签和提交...");
+
+        for tag_name in created_tags {
+            match self.git.delete_tag(tag_name) {
+                Ok(()) => info!("🗑️  已删除标签 {}", tag_name),
+                Err(error) => warn!("⚠️  删除标签 {} 失败: {}", tag_name, error),
+            }
+        }
+
+        let Some(head_before) = head_before else {
+            return;
+        };
+        match self.git.resolve_commit("HEAD") {
+            Ok(head) if head == head_before => return,
+            Ok(_) => {}
+            Err(error) => {
+                warn!("⚠️  无法读取 HEAD，未撤销提交: {}", error);
+                return;
+            }
+        }
+
+        // --mixed 只移动分支，随发布提交一起提交的其他改动会保留在工作区
+        let reset = git::run(
+            self.git_command()
+                .args(["reset", "-q", "--mixed", head_before]),
+            self.global.quiet_git,
+            String::new,
+        );
+        if reset.is_err() {
+            warn!("⚠️  撤销发布提交失败，请手动执行 git reset {}", head_before);
+            return;
+        }
+        self.rollback();
+        info!("✅ 已将分支还原到 {}", head_before);
+    }
+
+    /// 将更新日志写入 --changelog-output 指定的文件或 stdout
+    fn write_changelog_output(&self) -> Result<()> {
+        let (Some(path), Some(notes)) = (&self.args.changelog_output, &self.changelog) else {
+            return Ok(());
+        };
+        if self.args.changelog_to_stdout() {
+            print!("{}", notes);
+            return Ok(());
+        }
+        fs::write(path, notes)
+            .map_err(|error| anyhow!("写入 {} 失败: {}", path.display(), error))?;
+        info!("💾 已写入更新日志: {}", path.display());
+        Ok(())
+    }
+
+    fn write_output_manifest(&self, report: &ReleaseReport) -> Result<()> {
+        let Some(path) = &self.args.output_manifest else {
+            return Ok(());
+        };
+
+        let files: Vec<String> = report
+            .updated_files
+            .iter()
+            .map(|file| file.strip_prefix(".").unwrap_or(file).display().to_string())
+            .collect();
+        let content = if path.extension().is_some_and(|ext| ext == "json") {
+            let manifest = serde_json::json!({
+                "version": report.version,
+                "tag": report.tag,
+                "tags": report.tags,
+                "files": files,
+                "crates": report.crates,
+                "dry_run": report.dry_run,
+            });
+            format!("{}\n", serde_json::to_string_pretty(&manifest)?)
+        } else {
+            files.iter().map(|file| format!("{}\n", file)).collect()
+        };
+
+        fs::write(path, content)
+            .map_err(|error| anyhow!("写入 {} 失败: {}", path.display(), error))?;
+        debug!("💾 已写入文件清单: {}", path.display());
+        Ok(())
+    }
+
+    /// 标签与本地分支同名时提示，git 命令中统一使用 refs/tags/ 引用标签
+    fn check_tag_branch_ambiguity(&self) {
+        if self.per_crate_tags() {
+            return;
+        }
+        let tag_name = self.tag_name();
+        if self
+            .git
+            .resolve_commit(&format!("refs/heads/{}", tag_name))
+            .is_ok()
+        {
+            warn!(
+                "⚠️  标签 {} 与本地分支同名，操作标签时将使用 refs/tags/{}",
+                tag_name, tag_name
+            );
+        }
+    }
+
+    fn fetch_tags(&self) -> Result<()> {
+        for remote in self.remotes()? {
+            info!("🔄 从 {} 同步标签...", remote);
+            let _spinner = self.spinner(format!("正在从 {} 同步标签", remote));
+            if let Err(error) = self.git.fetch(&remote, &["--tags"]) {
+                if !self.global.force {
+                    return Err(error::git(format!(
+                        "{}\n无法同步远程标签，检查网络后重试，或使用 --force 跳过",
+                        error
+                    )));
+                }
+                warn!("⚠️  {}，已使用 --force 继续", error);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_remote_tags(&mut self) -> Result<()> {
+        let tag_name = self.tag_name();
+        for remote in self.remotes()? {
+            debug!("🔍 检查 {} 上的标签 {}", remote, tag_name);
+            if self.git.remote_tag_exists(&remote, &tag_name)? {
+                self.remote_tags.push(remote);
+            }
+        }
+
+        if !self.remote_tags.is_empty() && !self.args.re_publish {
+            return Err(error::tag_exists(format!(
+                "标签 {} 已存在于远程仓库 {}，使用 --re-publish 重新发布",
+                tag_name,
+                self.remote_tags.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// --verify-tag-absent-everywhere：在本地和每个远程仓库上查找本次的标签，汇总所有已存在的位置
+    fn verify_tags_absent(&self) -> Result<()> {
+        let tags = self.tag_names();
+        info!(
+            "🔍 确认标签 {} 在本地和远程仓库上都不存在...",
+            tags.join(", ")
+        );
+
+        let mut existing = Vec::new();
+        for tag in &tags {
+            if self.git.tag_exists(tag)? {
+                existing.push(format!("{} (本地)", tag));
+            }
+        }
+        for remote in self.remotes()? {
+            for tag in &tags {
+                debug!("🔍 检查 {} 上的标签 {}", remote, tag);
+                if self.git.remote_tag_exists(&remote, tag)? {
+                    existing.push(format!("{} ({})", tag, remote));
+                }
+            }
+        }
+
+        if !existing.is_empty() {
+            return Err(error::tag_exists(format!(
+                "以下标签已存在，发布不会继续: {}\n确认后删除这些标签，或使用 --re-publish 重新发布",
+                existing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn write_and_commit(&mut self) -> Result<()> {
+        self.write_versions()?;
+        self.run_hooks(HookPoint::PreCommit)?;
+
+        // 4. 提交更改
+        self.commit_changes()
+    }
+
+    /// 提交后工作区应当是干净的，否则说明有文件在提交过程中被重新生成，没有进入发布提交
+    fn check_clean_after_commit(&self) -> Result<()> {
+        let paths = self.git.dirty_paths(!self.allows_untracked())?;
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let message = format!(
+            "发布提交之后工作区仍有改动，可能有文件在提交过程中被重新生成: {}",
+            paths.join(", ")
+        );
+        if self.args.strict {
+            return Err(error::dirty_tree(format!(
+                "{}\n发布提交已创建但尚未打标签和推送，确认后可使用 git reset HEAD~1 撤销",
+                message
+            )));
+        }
+        warn!("⚠️  {}", message);
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        if self.backups.is_empty() {
+            return;
+        }
+
+        warn!("↩️  发布失败，还原已修改的文件...");
+        self.restore_backups();
+    }
+
+    /// 按备份还原文件并撤销对它们的暂存
+    fn restore_backups(&mut self) {
+        let git_bin = self.global.git_bin();
+        let quiet = self.global.quiet_git;
+        for backup in self.backups.drain(..).rev() {
+            let result = match &backup.content {
+                Some(content) => fs::write(&backup.path, content),
+                // 原本不存在的文件可能从未被创建
+                None => fs::remove_file(&backup.path).or_else(|error| match error.kind() {
+                    std::io::ErrorKind::NotFound => Ok(()),
+                    _ => Err(error),
+                }),
+            };
+            if let Err(error) = result {
+                warn!("⚠️  还原 {} 失败: {}", backup.path.display(), error);
+                continue;
+            }
+
+            // 撤销 git add 对该文件的暂存
+            let _ = git::run(
+                StdCommand::new(&git_bin)
+                    .arg("reset")
+                    .arg("-q")
+                    .arg("--")
+                    .arg(&backup.path),
+                quiet,
+                String::new,
+            );
+        }
+    }
+
+    /// 记录文件当前内容，以便失败时还原
+    fn backup_file(&mut self, path: &Path) -> Result<()> {
+        if self.backups.iter().any(|backup| backup.path == path) {
+            return Ok(());
+        }
+
+        let content = if path.exists() {
+            Some(fs::read_to_string(path)?)
+        } else {
+            None
+        };
+        self.backups.push(FileBackup {
+            path: path.to_path_buf(),
+            content,
+        });
+        Ok(())
+    }
+
+    /// 计算最终版本号，--auto 模式下没有需要发布的提交时返回 `false`
+    fn resolve_version(&mut self) -> Result<bool> {
+        self.read_version_source()?;
+
+        let bump = if self.args.auto {
+            match self.conventional_bump()? {
+                Some(bump) => Some(bump),
+                None => return Ok(false),
+            }
+        } else {
+            Bump::from_keyword(&self.version)
+        };
+
+        if let Some(bump) = bump {
+            let current = self.current_version()?;
+            let next = bump.apply(&version::parse(&current)?).to_string();
+            debug!("🔢 {} -> {} ({:?})", current, next, bump);
+            self.version = next;
+        }
+
+        if let Some(step) = self.pre_step() {
+            let base = version::parse(&self.version)?;
+            let next = step.apply(&base)?.to_string();
+            debug!("🔢 {} -> {} ({:?})", base, next, step);
+            self.version = next;
+        }
+
+        if let Some(metadata) = &self.args.bump_build_metadata {
+            let base = version::parse(&self.version)?;
+            let next = version::bump_build(&base, metadata.as_deref())?.to_string();
+            info!("🔢 构建元数据: {} -> {}", base, next);
+            self.version = next;
+        }
+
+        // 为历史提交补打标签时版本号通常低于当前版本，预览时仍按该提交中的版本检查
+        if !self.global.force && (self.args.at.is_none() || self.preview.is_some()) {
+            self.check_monotonic()?;
+        }
+        Ok(true)
+    }
+
+    /// --to-rc/--bump-pre/--promote 对应的预发布调整
+    fn pre_step(&self) -> Option<PreStep> {
+        if self.args.to_rc {
+            Some(PreStep::ToRc)
+        } else if self.args.bump_pre {
+            Some(PreStep::BumpPre)
+        } else if self.args.promote {
+            Some(PreStep::Promote)
+        } else {
+            None
+        }
+    }
+
+    /// 从环境变量、标准输入或 --tag-only/--finalize/--bump-pre/--promote/--bump-build-metadata 时
+    /// 从根 Cargo.toml 读取版本号
+    fn read_version_source(&mut self) -> Result<()> {
+        if self.args.tag_only
+            || self.args.finalize
+            || self.args.bump_pre
+            || self.args.promote
+            || self.args.bump_build_metadata.is_some()
+        {
+            self.version = self.current_version()?;
+        } else if self.args.to_rc && self.version.is_empty() && !self.args.auto {
+            self.version = "patch".to_string();
+        } else if let Some(name) = &self.args.version_from_env {
+            self.version = std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| anyhow!("环境变量 {} 未设置或为空", name))?;
+        } else if self.version == "-" {
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            self.version = input.trim().to_string();
+            if self.version.is_empty() {
+                return Err(anyhow!("未能从标准输入读取到版本号"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 上一个标签到 HEAD 之间是否有提交，没有标签时视为有改动
+    fn has_changes_since_last_tag(&self) -> Result<bool> {
+        let Some(tag) = self.previous_tag(&self.global.tag_prefix)? else {
+            return Ok(true);
+        };
+        if self.git.commits_since(Some(&tag), self.head())?.is_empty() {
+            info!("⏭️  {} 以来没有新的提交", tag);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// 上一个匹配前缀的标签，--first-release 时总是 `None`
+    fn previous_tag(&self, prefix: &str) -> Result<Option<String>> {
+        if !self.args.first_release {
+            return self.git.last_tag(self.head(), prefix);
+        }
+        if let Some(tag) = self.git.last_tag(self.head(), prefix)? {
+            warn!("⚠️  已存在标签 {}，--first-release 仍按首次发布处理", tag);
+        }
+        Ok(None)
+    }
+
+    /// 根据上一个标签以来的提交计算版本递增级别
+    fn conventional_bump(&self) -> Result<Option<Bump>> {
+        let last_tag = self.previous_tag(&self.global.tag_prefix)?;
+        let commits = self.git.commits_since(last_tag.as_deref(), self.head())?;
+        match &last_tag {
+            Some(tag) => info!("🔍 分析 {} 以来的 {} 个提交...", tag, commits.len()),
+            None => info!("🔍 未找到历史标签，分析全部 {} 个提交...", commits.len()),
+        }
+
+        let mut bump = None;
+        for commit in &commits {
+            if let Some(commit_bump) = conventional::classify(&commit.message) {
+                info!(
+                    "   {} {} -> {:?}",
+                    &commit.id[..7.min(commit.id.len())],
+                    commit.summary(),
+                    commit_bump
+                );
+                bump = bump.max(Some(commit_bump));
+            }
+        }
+        Ok(bump)
+    }
+
+    /// 新版本号不能低于当前版本号
+    fn check_monotonic(&self) -> Result<()> {
+        let (Ok(current), Ok(next)) = (
+            self.current_version()
+                .and_then(|current| version::parse(&current)),
+            version::parse(&self.version),
+        ) else {
+            return Ok(());
+        };
+
+        if next < current {
+            return Err(error::validation(format!(
+                "新版本号 {} 低于当前版本号 {}\n使用 --force 跳过此检查",
+                next, current
+            )));
+        }
+        // 只打标签的模式和 --re-publish 本来就使用当前版本号，--idempotent 重复运行时标签已存在
+        let rerun = self.args.idempotent && self.git.tag_exists(&self.tag_name())?;
+        if next == current && !self.skips_commit() && !self.args.re_publish && !rerun {
+            return Err(error::validation(format!(
+                "新版本号 {} 与当前版本号相同，没有需要发布的更改\n使用 --tag-only 为当前版本创建标签，或使用 --force 继续",
+                next
+            )));
+        }
+        Ok(())
+    }
+
+    /// 根 Cargo.toml 中的当前版本号，workspace 优先使用 workspace.package.version
+    fn current_version(&self) -> Result<String> {
+        let content = self.read_root_manifest()?;
+        let cargo: CargoToml = toml::from_str(&content)?;
+        cargo
+            .workspace
+            .and_then(|workspace| workspace.package)
+            .and_then(|package| package.version)
+            .or(cargo
+                .package
+                .and_then(|package| package.version.into_literal()))
+            .ok_or_else(|| anyhow!("无法从 Cargo.toml 中读取当前版本号"))
+    }
+
+    /// 根 Cargo.toml 的内容，--dry-run --at 时从预览的提交中读取
+    fn read_root_manifest(&self) -> Result<String> {
+        let root_cargo_path = root_file(&self.root, "Cargo.toml");
+        if let Some(commit) = &self.preview {
+            let path = self.tree_path(&root_cargo_path)?;
+            return self.git.read_file(commit, &path)?.ok_or_else(|| {
+                anyhow!(
+                    "提交 {} 中没有 {} 文件",
+                    &commit[..commit.len().min(7)],
+                    path.display()
+                )
+            });
+        }
+        if !root_cargo_path.exists() {
+            return Err(anyhow!("未找到 {} 文件", root_cargo_path.display()));
+        }
+        Ok(fs::read_to_string(&root_cargo_path)?)
+    }
+
+    fn validate_version_format(&self) -> Result<()> {
+        let version_re = Regex::new(r"^\d+\.\d+\.\d+(-[a-zA-Z0-9\.]+)?(\+[a-zA-Z0-9\.]+)?$")?;
+        if !version_re.is_match(&self.version) {
+            return Err(error::invalid_version(
+                "版本号格式不正确，请使用语义化版本号 (例如: 1.2.3, 2.0.0-beta.1)\n\
+                 使用 --force 跳过此验证",
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_git_repo(&self) -> Result<()> {
+        if !self.git.is_repository() {
+            return Err(error::not_a_git_repo(
+                "当前目录不是 git 仓库\n请在仓库中运行，使用 git init 创建仓库，或使用 --path 指定仓库目录",
+            ));
+        }
+
+        // 查找 Cargo.toml 等文件都基于当前目录，在子目录中运行时只会处理该目录下的文件
+        let toplevel = self.git.toplevel()?;
+        let current = fs::canonicalize(".")?;
+        if current != toplevel {
+            let relative = current.strip_prefix(&toplevel).unwrap_or(&current);
+            warn!(
+                "⚠️  当前目录 {} 不是仓库根目录，只会查找和更新该目录下的文件",
+                relative.display()
+            );
+            warn!(
+                "   如需发布整个仓库，请在 {} 中运行或使用 --path 指定",
+                toplevel.display()
+            );
+        }
+        Ok(())
+    }
+
+    fn check_up_to_date(&self) -> Result<()> {
+        let Some(upstream) = self.git.upstream()? else {
+            debug!("⚠️  当前分支没有跟踪的远程分支，跳过同步检查");
+            return Ok(());
+        };
+
+        info!("🔄 检查是否与 {} 同步...", upstream.remote);
+        {
+            let _spinner = self.spinner(format!("正在从 {} 拉取", upstream.remote));
+            // 指示器代替 git 自身的进度输出
+            let args: &[&str] = if self.shows_progress() {
+                &["--quiet"]
+            } else {
+                &[]
+            };
+            self.git.fetch(&upstream.remote, args)?;
+        }
+
+        let (_, behind) = self.git.ahead_behind("HEAD", &upstream.reference)?;
+        if behind > 0 {
+            let upstream_name = upstream
+                .reference
+                .strip_prefix("refs/remotes/")
+                .unwrap_or(&upstream.reference);
+            if self.global.force {
+                warn!(
+                    "⚠️  本地分支落后 {} {} 个提交，已使用 --force 继续",
+                    upstream_name, behind
+                );
+            } else {
+                return Err(error::validation(format!(
+                    "本地分支落后 {} {} 个提交，请先拉取远程更新\n使用 --force 跳过此检查",
+                    upstream_name, behind
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_working_tree_clean(&self) -> Result<bool> {
+        self.git.is_clean(!self.allows_untracked())
+    }
+
+    fn allows_untracked(&self) -> bool {
+        self.args.allow_untracked && !self.args.strict_clean
+    }
+
+    /// 暂存发布提交的改动，--allow-untracked 时只暂存已跟踪的文件和本次发布写入的文件
+    fn stage_args(&self) -> Vec<Vec<String>> {
+        if !self.allows_untracked() {
+            return vec![vec!["add".to_string(), "-A".to_string()]];
+        }
+
+        let mut paths: Vec<String> = Vec::new();
+        let written = self.changes.iter().map(|change| &change.path);
+        for path in written.chain(&self.updated_files) {
+            let path = path.display().to_string();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        let mut stages = vec![vec!["add".to_string(), "-u".to_string()]];
+        if !paths.is_empty() {
+            let mut add = vec!["add".to_string(), "--".to_string()];
+            add.extend(paths);
+            stages.push(add);
+        }
+        stages
+    }
+
+    fn update_versions(&mut self) -> Result<()> {
+        info!("📝 更新版本号...");
+
+        let roots = workspace_roots(&self.global.manifest_path);
+        let mut workspaces = Vec::new();
+        for root in &roots {
+            if roots.len() > 1 {
+                info!("📁 处理 workspace: {}", root.display());
+            }
+            self.root = root.clone();
+            self.workspace_version = None;
+            if let Some(manifests) = self.update_cargo_versions()? {
+                workspaces.push((root.clone(), manifests));
+            }
+        }
+        // 所有 workspace 的版本号都确定后再同步依赖，workspace 之间的依赖也会被更新
+        for (root, manifests) in workspaces {
+            self.root = root;
+            self.update_dependency_requirements(&manifests)?;
+        }
+        self.root = roots[0].clone();
+
+        self.validate_crate_filters()?;
+
+        // 更新 tauri.conf.json、package.json、pyproject.toml、VERSION 和配置文件中的其他文件
+        for sink in config::merge_sinks(self.default_sinks()?, &self.args.version_sinks) {
+            if self.args.runs_updater(Updater::for_sink(&sink)) {
+                for sink in expand_sink_paths(sink)? {
+                    self.update_sink(&sink)?;
+                }
+            }
+        }
+
+        // 先计算全部改动，检查无误后再统一写入
+        self.check_diff_size()?;
+
+        // 避免产生空的发布提交
+        let unchanged = self.changes.iter().all(FileChange::is_unchanged);
+        if unchanged && !self.global.force && !self.args.re_publish {
+            return Err(error::validation(format!(
+                "更新到 {} 不会修改任何文件，没有需要发布的内容\n使用 --force 继续",
+                self.version
+            )));
+        }
+
+        if self.args.changelog {
+            self.update_changelog()?;
+        }
+        Ok(())
+    }
+
+    /// 计算当前 workspace 中 Cargo 清单的改动，返回需要同步依赖版本要求的成员清单
+    fn update_cargo_versions(&mut self) -> Result<Option<Vec<PathBuf>>> {
+        // 检查是否是 workspace 项目
+        let root_cargo_path = root_file(&self.root, "Cargo.toml");
+        if !root_cargo_path.exists() {
+            return Err(anyhow!("未找到 {} 文件", root_cargo_path.display()));
+        }
+
+        let content = fs::read_to_string(&root_cargo_path)?;
+        let cargo: CargoToml = toml::from_str(&content)?;
+
+        if self.args.workspace_version_only {
+            if !self.update_root_workspace_version()? {
+                return Err(error::validation(format!(
+                    "--workspace-version-only 需要 {} 设置 [workspace.package] version",
+                    root_cargo_path.display()
+                )));
+            }
+        } else if cargo.workspace.is_some() {
+            info!("🔍 检测到 workspace 项目，更新所有成员...");
+            return self.update_workspace_versions().map(Some);
+        } else {
+            // 单个项目
+            self.update_single_crate(&root_cargo_path)?;
+        }
+        Ok(None)
+    }
+
+    /// 写入计划中的文件，检查构建并按需更新 Cargo.lock
+    fn write_versions(&mut self) -> Result<()> {
+        self.write_changes()?;
+        for root in workspace_roots(&self.global.manifest_path) {
+            let manifest = root_file(&root, "Cargo.toml");
+            if self.args.verify_build {
+                Self::verify_build(&manifest)?;
+            } else {
+                Self::cargo_check(&manifest)?;
+            }
+        }
+        if self.args.update_lock {
+            self.update_lockfile()?;
+        }
+
+        info!("✅ 版本号更新完成，共更新 {} 个文件", self.changes.len());
+        Ok(())
+    }
+
+    /// 确认 --only/--exclude 中的名称都对应实际存在的 crate
+    fn validate_crate_filters(&self) -> Result<()> {
+        let unknown: Vec<&str> = self
+            .global
+            .only
+            .iter()
+            .chain(&self.global.exclude)
+            .filter(|name| !self.crate_names.contains(name))
+            .map(String::as_str)
+            .collect();
+
+        if !unknown.is_empty() {
+            return Err(error::validation(format!(
+                "未找到 crate: {}\n可用的 crate: {}",
+                unknown.join(", "),
+                self.crate_names.join(", ")
+            )));
+        }
+
+        if !self.args.tag_prefixes.is_empty() {
+            if !self.per_crate_tags() {
+                warn!("⚠️  没有按 crate 分别打标签，配置文件中的 [tag-prefixes] 不会生效");
+            }
+            let unknown: Vec<&str> = self
+                .args
+                .tag_prefixes
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .filter(|name| !self.crate_names.iter().any(|known| known == name))
+                .collect();
+            if !unknown.is_empty() {
+                return Err(error::validation(format!(
+                    "配置文件 [tag-prefixes] 中的 crate 不存在: {}\n可用的 crate: {}",
+                    unknown.join(", "),
+                    self.crate_names.join(", ")
+                )));
+            }
+        }
+
+        // --exclude 只会从 --only 选中的 crate 中排除
+        if !self.global.only.is_empty() {
+            for name in &self.global.exclude {
+                if self.global.only.contains(name) {
+                    warn!("⚠️  {} 同时出现在 --only 和 --exclude 中，将被排除", name);
+                } else {
+                    warn!(
+                        "⚠️  --exclude {} 不在 --only 选中的 crate 中，不会产生影响",
+                        name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn stage_change(
+        &mut self,
+        path: &Path,
+        original: Option<String>,
+        updated: String,
+        old_version: Option<&str>,
+    ) {
+        self.changes.push(FileChange {
+            path: path.to_path_buf(),
+            original,
+            updated,
+            old_version: old_version.map(str::to_string),
+        });
+    }
+
+    fn check_diff_size(&self) -> Result<()> {
+        let Some(max_diff_lines) = self.args.max_diff_lines else {
+            return Ok(());
+        };
+
+        let total: usize = self.changes.iter().map(FileChange::changed_lines).sum();
+        if total > max_diff_lines {
+            if self.global.force {
+                warn!(
+                    "⚠️  版本文件共改动 {} 行，超过上限 {}，已使用 --force 继续",
+                    total, max_diff_lines
+                );
+            } else {
+                return Err(error::validation(format!(
+                    "版本文件共改动 {} 行，超过 --max-diff-lines 上限 {}\n\
+                     使用 --force 跳过此检查",
+                    total, max_diff_lines
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn print_changes(&self) {
+        for change in &self.changes {
+            let path = change.path.display().to_string();
+            let diff = TextDiff::from_lines(change.original_text(), &change.updated);
+            let diff = diff
+                .unified_diff()
+                .context_radius(2)
+                .header(&path, &path)
+                .to_string();
+            info!("{}", logger::paint_diff(diff.trim_end()));
+        }
+    }
+
+    fn write_changes(&mut self) -> Result<()> {
+        // cargo check 和 --update-lock 会改写 Cargo.lock
+        for root in workspace_roots(&self.global.manifest_path) {
+            self.backup_file(&root_file(&root, "Cargo.lock"))?;
+        }
+
+        for change in &self.changes {
+            self.backups.push(FileBackup {
+                path: change.path.clone(),
+                content: change.original.clone(),
+            });
+            fs::write(&change.path, &change.updated)?;
+            self.updated_files.push(change.path.clone());
+        }
+        Ok(())
+    }
+
+    fn update_lockfile(&mut self) -> Result<()> {
+        for root in workspace_roots(&self.global.manifest_path) {
+            let status = StdCommand::new("cargo")
+                .arg("update")
+                .arg("--workspace")
+                .arg("--offline")
+                .arg("--manifest-path")
+                .arg(root_file(&root, "Cargo.toml"))
+                .status()?;
+            let lock_path = root_file(&root, "Cargo.lock");
+            if !status.success() {
+                return Err(anyhow!("更新 {} 失败", lock_path.display()));
+            }
+
+            if lock_path.exists() {
+                info!("✅ 更新 {}", lock_path.display());
+                self.updated_files.push(lock_path);
+            }
+        }
+        Ok(())
+    }
+
+    /// 通过 cargo metadata 确认 cargo 可用、workspace 中的清单和成员路径都有效
+    fn check_manifests(manifest: &Path) -> Result<()> {
+        debug!("🔍 检查 Cargo 清单...");
+        let output = StdCommand::new("cargo")
+            .args([
+                "metadata",
+                "--no-deps",
+                "--format-version",
+                "1",
+                "--offline",
+            ])
+            .arg("--manifest-path")
+            .arg(manifest)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|error| {
+                error::validation(format!(
+                    "无法执行 cargo: {}\n请确认 cargo 已安装并在 PATH 中",
+                    error
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(error::validation(format!(
+                "Cargo 清单无效，未修改任何文件:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// 运行 cargo check --workspace，失败时中止发布并还原已修改的文件
+    fn verify_build(manifest: &Path) -> Result<()> {
+        info!("🔨 检查更新后的 workspace 能否编译...");
+        let status = StdCommand::new("cargo")
+            .args(["check", "--workspace", "--manifest-path"])
+            .arg(manifest)
+            .status()
+            .map_err(|error| error::validation(format!("无法执行 cargo: {}", error)))?;
+        if !status.success() {
+            return Err(error::validation(
+                "cargo check --workspace 失败\n请检查版本号和依赖版本要求是否正确",
+            ));
+        }
+        Ok(())
+    }
+
+    fn cargo_check(manifest: &Path) -> Result<()> {
+        StdCommand::new("cargo")
+            .args(["check", "--manifest-path"])
+            .arg(manifest)
+            .status()?;
+        Ok(())
+    }
+
+    fn update_workspace_versions(&mut self) -> Result<Vec<PathBuf>> {
+        // 首先更新根 Cargo.toml 中的 workspace.package.version（如果存在）
+        let has_workspace_version = self.update_root_workspace_version()?;
+        if !has_workspace_version {
+            info!("🔍 根 Cargo.toml 没有 [workspace.package] version，逐个更新成员版本");
+        }
+
+        // 查找并更新所有成员的 Cargo.toml
+        let mut cargo_toml_files = self.find_all_cargo_toml()?;
+        let extra_manifests = self.extra_manifests(&cargo_toml_files)?;
+
+        let default_members = self.default_members()?;
+
+        let manifests: Vec<(&PathBuf, Option<&[Pattern]>)> = cargo_toml_files
+            .iter()
+            .map(|cargo_path| (cargo_path, default_members.as_deref()))
+            .chain(extra_manifests.iter().map(|cargo_path| (cargo_path, None)))
+            .collect();
+        let counter = progress::Counter::start(
+            self.shows_progress(),
+            "正在处理清单".to_string(),
+            manifests.len(),
+        );
+        let plan = |&(cargo_path, default_members): &(&PathBuf, Option<&[Pattern]>)| {
+            let update = Self::plan_crate_update(
+                &self.global,
+                &self.args,
+                &self.version,
+                &self.root,
+                default_members,
+                cargo_path,
+            );
+            counter.inc();
+            update
+        };
+
+        // 读取和改写清单可以并行，collect 保持原有顺序
+        let updates: Vec<Result<CrateUpdate>> = match self.global.jobs.map(NonZeroUsize::get) {
+            Some(1) => manifests.iter().map(plan).collect(),
+            Some(jobs) => rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()?
+                .install(|| manifests.par_iter().map(plan).collect()),
+            None => manifests.par_iter().map(plan).collect(),
+        };
+        drop(counter);
+        let updates = updates.into_iter().collect::<Result<Vec<_>>>()?;
+        let updates = self.skip_unchanged(updates)?;
+        // 额外的清单不是成员，不要求与成员使用相同的版本声明方式
+        self.check_workspace_layout(&updates[..cargo_toml_files.len()], has_workspace_version)?;
+        for update in updates {
+            self.apply_crate_update(update)?;
+        }
+        cargo_toml_files.extend(extra_manifests);
+
+        if self.changes.is_empty() {
+            if self.crate_names.is_empty() {
+                return Err(anyhow!("workspace 中没有找到任何 crate"));
+            }
+            warn!("⚠️  没有需要更新版本号的 crate，所有 crate 都已被跳过");
+        }
+
+        Ok(cargo_toml_files)
+    }
+
+    /// 按与发布相同的查找和过滤规则列出 crate，不修改文件和发布状态
+    fn list_crates(&mut self) -> Result<Vec<CrateListing>> {
+        let roots = workspace_roots(&self.global.manifest_path);
+        let mut crates = Vec::new();
+        for root in &roots {
+            self.root = root.clone();
+            crates.extend(self.list_workspace_crates()?);
+        }
+        self.root = roots[0].clone();
+        Ok(crates)
+    }
+
+    fn list_workspace_crates(&self) -> Result<Vec<CrateListing>> {
+        let root_cargo_path = root_file(&self.root, "Cargo.toml");
+        if !root_cargo_path.exists() {
+            return Err(anyhow!("未找到 {} 文件", root_cargo_path.display()));
+        }
+        Self::check_manifests(&root_cargo_path)?;
+        let root: CargoToml = toml::from_str(&fs::read_to_string(&root_cargo_path)?)?;
+        let workspace_version = root
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.package.as_ref())
+            .and_then(|package| package.version.clone());
+
+        let (manifests, default_members) = if root.workspace.is_some() {
+            (self.find_all_cargo_toml()?, self.default_members()?)
+        } else {
+            (vec![root_cargo_path], None)
+        };
+        let extra_manifests = self.extra_manifests(&manifests)?;
+        let paths: Vec<(&PathBuf, Option<&[Pattern]>)> = manifests
+            .iter()
+            .map(|path| (path, default_members.as_deref()))
+            .chain(extra_manifests.iter().map(|path| (path, None)))
+            .collect();
+
+        let updates = paths
+            .iter()
+            .map(|(path, default_members)| {
+                Self::plan_crate_update(
+                    &self.global,
+                    &self.args,
+                    &self.version,
+                    &self.root,
+                    *default_members,
+                    path,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let updates = self.skip_unchanged(updates)?;
+
+        let mut crates = Vec::new();
+        for ((path, _), update) in paths.into_iter().zip(updates) {
+            let skipped = match update {
+                CrateUpdate::NotPackage => continue,
+                CrateUpdate::Skipped(_, reason) => Some(reason.describe()),
+                CrateUpdate::Inherited(..) | CrateUpdate::Updated { .. } => None,
+            };
+            let cargo: CargoToml = toml::from_str(&fs::read_to_string(path)?)?;
+            let Some(package) = cargo.package else {
+                continue;
+            };
+            let (version, inherited) = match package.version {
+                PackageVersion::Literal(version) => (version, false),
+                PackageVersion::Inherited { .. } => {
+                    (workspace_version.clone().unwrap_or_default(), true)
+                }
+            };
+            crates.push(CrateListing {
+                name: package.name,
+                path: path.strip_prefix(".").unwrap_or(path).to_path_buf(),
+                version,
+                inherited,
+                skipped,
+            });
+        }
+        Ok(crates)
+    }
+
+    /// 将计划中的改动临时写入文件，按发布顺序对本次更新的 crate 执行 cargo publish --dry-run
+    ///
+    /// 无论检查结果如何都会还原文件，有 crate 无法发布时返回错误
+    fn verify_publish(&mut self) -> Result<Vec<PublishCheck>> {
+        let order = self.publish_order()?;
+        info!("📦 检查 crate 能否发布（cargo publish --dry-run）...");
+
+        // 新版本号只写入仓库的临时副本，工作区中的文件不会被改动
+        let copy = self.copy_worktree()?;
+        for change in &self.changes {
+            let path = copy.path().join(self.tree_path(&change.path)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, &change.updated)?;
+        }
+        let checks = self.run_publish_checks(&order, copy.path())?;
+
+        let failed: Vec<&str> = checks
+            .iter()
+            .filter(|check| check.error.is_some())
+            .map(|check| check.name.as_str())
+            .collect();
+        if !failed.is_empty() {
+            return Err(error::validation(format!(
+                "{} 个 crate 无法发布: {}",
+                failed.len(),
+                failed.join(", ")
+            )));
+        }
+        Ok(checks)
+    }
+
+    /// 把仓库工作区复制到临时目录，跳过 .git 和 target 目录
+    fn copy_worktree(&self) -> Result<TempDir> {
+        let toplevel = self.git.toplevel()?;
+        let copy = TempDir::create("git-release-publish")?;
+        let entries = WalkDir::new(&toplevel)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| {
+                !(entry.file_type().is_dir()
+                    && matches!(entry.file_name().to_str(), Some(".git" | "target")))
+            });
+        for entry in entries {
+            let entry = entry?;
+            let target = copy.path().join(entry.path().strip_prefix(&toplevel)?);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&target)?;
+            } else if entry.path().is_file() {
+                // 指向文件的符号链接复制为普通文件，指向目录的符号链接被跳过
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+        Ok(copy)
+    }
+
+    /// 在 `copy` 中的仓库副本上逐个执行 cargo publish --dry-run
+    fn run_publish_checks(&self, order: &[PublishStep], copy: &Path) -> Result<Vec<PublishCheck>> {
+        let released: HashSet<&str> = self
+            .crate_versions
+            .iter()
+            .map(|krate| krate.name.as_str())
+            .collect();
+        let steps: Vec<&PublishStep> = order
+            .iter()
+            .filter(|step| released.contains(step.name.as_str()))
+            .collect();
+        // crate 所在的 workspace 根目录，--package 只能指定同一个 workspace 中的 crate
+        let roots = workspace_roots(&self.global.manifest_path);
+        let workspace_of = |path: &Path| {
+            roots
+                .iter()
+                .map(|root| root.strip_prefix(".").unwrap_or(root))
+                .filter(|root| path.starts_with(root))
+                .max_by_key(|root| root.components().count())
+        };
+
+        let mut checks = Vec::new();
+        for step in &steps {
+            if let Some(reason) = &step.excluded {
+                info!("⏭️  {} 不会发布: {}", step.name, reason);
+                continue;
+            }
+
+            // 同时打包本次更新的依赖，cargo 会在本地解析它们尚未发布的新版本
+            let mut packages = vec![step.name.as_str()];
+            let mut other_workspace = None;
+            let mut index = 0;
+            while index < packages.len() {
+                let name = packages[index];
+                index += 1;
+                let Some(dependency_step) = order.iter().find(|other| other.name == name) else {
+                    continue;
+                };
+                for dependency in &dependency_step.dependencies {
+                    if !released.contains(dependency.as_str())
+                        || packages.contains(&dependency.as_str())
+                    {
+                        continue;
+                    }
+                    let same_workspace = order
+                        .iter()
+                        .find(|other| &other.name == dependency)
+                        .is_some_and(|other| workspace_of(&other.path) == workspace_of(&step.path));
+                    if same_workspace {
+                        packages.push(dependency);
+                    } else {
+                        other_workspace.get_or_insert(dependency.as_str());
+                    }
+                }
+            }
+            if let Some(dependency) = other_workspace {
+                info!(
+                    "⏭️  {} 依赖其他 workspace 中本次更新的 {}，需要先发布 {} 才能检查",
+                    step.name, dependency, dependency
+                );
+                continue;
+            }
+
+            let mut command = StdCommand::new("cargo");
+            command
+                .args(["publish", "--dry-run", "--allow-dirty", "--manifest-path"])
+                .arg(copy.join(self.tree_path(&step.path)?));
+            for package in packages.iter().rev() {
+                command.args(["--package", package]);
+            }
+            let output = command
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|error| error::validation(format!("无法执行 cargo: {}", error)))?;
+
+            let error = if output.status.success() {
+                info!("✅ {} 可以发布", step.name);
+                None
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                // 只保留错误部分，略过编译和打包的进度输出
+                let message = stderr
+                    .find("error")
+                    .map_or(stderr.as_ref(), |start| &stderr[start..])
+                    .trim()
+                    .to_string();
+                warn!("⚠️  {} 无法发布:\n{}", step.name, message);
+                Some(message)
+            };
+            checks.push(PublishCheck {
+                name: step.name.clone(),
+                error,
+            });
+        }
+        Ok(checks)
+    }
+
+    /// 按依赖关系计算所有 crate 的发布顺序，不会发布的 crate 及依赖它们的 crate 排在最后
+    ///
+    /// 只考虑 [dependencies] 和 [build-dependencies]（包括 [target.*] 下的同名表），
+    /// cargo publish 时开发依赖不要求已发布
+    fn publish_order(&mut self) -> Result<Vec<PublishStep>> {
+        struct Node {
+            name: String,
+            path: PathBuf,
+            dependencies: Vec<String>,
+            excluded: Option<String>,
+        }
+
+        let roots = workspace_roots(&self.global.manifest_path);
+        let mut nodes = Vec::new();
+        for root in &roots {
+            self.root = root.clone();
+            let root_cargo_path = root_file(root, "Cargo.toml");
+            if !root_cargo_path.exists() {
+                return Err(anyhow!("未找到 {} 文件", root_cargo_path.display()));
+            }
+            Self::check_manifests(&root_cargo_path)?;
+            let cargo: CargoToml = toml::from_str(&fs::read_to_string(&root_cargo_path)?)?;
+            let manifests = if cargo.workspace.is_some() {
+                self.find_all_cargo_toml()?
+            } else {
+                vec![root_cargo_path]
+            };
+
+            for path in manifests {
+                let cargo: CargoToml = toml::from_str(&fs::read_to_string(&path)?)?;
+                let Some(package) = &cargo.package else {
+                    continue;
+                };
+                nodes.push(Node {
+                    name: package.name.clone(),
+                    excluded: Self::publish_disabled(package, root)?
+                        .then(|| "publish = false".to_string()),
+                    dependencies: dependency_names(&cargo.other),
+                    path: path.strip_prefix(".").unwrap_or(&path).to_path_buf(),
+                });
+            }
+        }
+        self.root = roots[0].clone();
+
+        // 依赖不会发布的 crate 时自身也无法发布，逐轮传递直到不再变化
+        loop {
+            let excluded: HashSet<String> = nodes
+                .iter()
+                .filter(|node| node.excluded.is_some())
+                .map(|node| node.name.clone())
+                .collect();
+            let mut changed = false;
+            for node in nodes.iter_mut().filter(|node| node.excluded.is_none()) {
+                if let Some(dependency) = node
+                    .dependencies
+                    .iter()
+                    .find(|dependency| excluded.contains(*dependency))
+                {
+                    node.excluded = Some(format!("依赖不会发布的 crate {}", dependency));
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // 拓扑排序，同一轮中保持查找到的顺序
+        let names: HashSet<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        let mut published: HashSet<&str> = HashSet::new();
+        let mut order = Vec::new();
+        let mut pending: Vec<&Node> = nodes
+            .iter()
+            .filter(|node| node.excluded.is_none())
+            .collect();
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<&Node>, Vec<&Node>) =
+                pending.into_iter().partition(|node| {
+                    node.dependencies.iter().all(|dependency| {
+                        dependency == &node.name
+                            || !names.contains(dependency.as_str())
+                            || published.contains(dependency.as_str())
+                    })
+                });
+            if ready.is_empty() {
+                let cycle: Vec<&str> = blocked.iter().map(|node| node.name.as_str()).collect();
+                return Err(error::validation(format!(
+                    "以下 crate 之间存在循环依赖，无法确定发布顺序: {}",
+                    cycle.join(", ")
+                )));
+            }
+            for node in ready {
+                published.insert(&node.name);
+                order.push(node);
+            }
+            pending = blocked;
+        }
+
+        order.extend(nodes.iter().filter(|node| node.excluded.is_some()));
+        Ok(order
+            .into_iter()
+            .map(|node| PublishStep {
+                name: node.name.clone(),
+                path: node.path.clone(),
+                excluded: node.excluded.clone(),
+                dependencies: node
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| {
+                        *dependency != &node.name && names.contains(dependency.as_str())
+                    })
+                    .cloned()
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// 确认成员的版本声明方式一致，避免只更新了一部分 crate
+    fn check_workspace_layout(
+        &self,
+        updates: &[CrateUpdate],
+        has_workspace_version: bool,
+    ) -> Result<()> {
+        let mut inherited = Vec::new();
+        let mut literal = Vec::new();
+        for update in updates {
+            match update {
+                CrateUpdate::Inherited(name, _) => inherited.push(name.as_str()),
+                CrateUpdate::Updated { name, .. } => literal.push(name.as_str()),
+                _ => {}
+            }
+        }
+
+        if !inherited.is_empty() && !has_workspace_version {
+            return Err(error::validation(format!(
+                "以下 crate 使用 version.workspace = true，但根 Cargo.toml 没有设置 [workspace.package] version: {}",
+                inherited.join(", ")
+            )));
+        }
+
+        if !inherited.is_empty() && !literal.is_empty() {
+            let message = format!(
+                "workspace 中部分 crate 继承 workspace 版本 ({})，部分使用独立版本 ({})",
+                inherited.join(", "),
+                literal.join(", ")
+            );
+            if !self.global.force {
+                return Err(error::validation(format!(
+                    "{}\n请统一版本声明，或使用 --force 同时更新两者",
+                    message
+                )));
+            }
+            warn!("⚠️  {}，已使用 --force 继续", message);
+        }
+        Ok(())
+    }
+
+    fn find_all_cargo_toml(&self) -> Result<Vec<PathBuf>> {
+        if self.global.discovery == Discovery::Members
+            && let Some(cargo_files) = self.workspace_member_manifests()?
+        {
+            return Ok(cargo_files);
+        }
+
+        if self.global.discovery == Discovery::Metadata {
+            match StdCommand::new("cargo")
+                .args([
+                    "metadata",
+                    "--no-deps",
+                    "--format-version",
+                    "1",
+                    "--offline",
+                ])
+                .arg("--manifest-path")
+                .arg(root_file(&self.root, "Cargo.toml"))
+                .stdin(Stdio::null())
+                .output()
+            {
+                Ok(output) => return Self::metadata_manifests(&output, &self.root),
+                Err(error) => warn!(
+                    "⚠️  无法执行 cargo metadata ({})，改为遍历目录查找成员",
+                    error
+                ),
+            }
+        }
+
+        let mut cargo_files = Vec::new();
+
+        // 跟随符号链接时同一个文件可能通过多条路径到达，按规范路径去重
+        let mut visited = HashSet::new();
+        let submodules = self.submodule_dirs()?;
+
+        let mut walker = WalkDir::new(&self.root).follow_links(self.global.follow_symlinks);
+        if let Some(max_depth) = self.global.max_depth {
+            // WalkDir 中根目录本身为第 0 层，根目录下的文件为第 1 层
+            walker = walker.max_depth(max_depth + 1);
+        }
+
+        for entry in walker
+            .sort_by_file_name()
+            .into_iter()
+            // 跳过 cargo 的构建目录（带有 CACHEDIR.TAG），其中 target/package 下有打包时复制的清单
+            .filter_entry(|entry| {
+                !(entry.file_type().is_dir()
+                    && (entry.path().join("CACHEDIR.TAG").is_file()
+                        || Self::is_submodule(&submodules, entry.path())))
+            })
+            .filter_map(|e| match e {
+                Ok(entry) => Some(entry),
+                Err(error) => {
+                    if error.loop_ancestor().is_some() {
+                        debug!("⏭️  跳过循环符号链接: {}", error);
+                    }
+                    None
+                }
+            })
+        {
+            let path = entry.path();
+            if path.file_name().and_then(|s| s.to_str()) == Some("Cargo.toml")
+                && visited.insert(fs::canonicalize(path)?)
+            {
+                cargo_files.push(path.to_path_buf());
+            }
+        }
+
+        Ok(cargo_files)
+    }
+
+    /// 按 [workspace].members 和 exclude 展开成员的清单，路径与遍历目录时的形式一致。
+    /// 根 Cargo.toml 没有 [workspace].members 时返回 None，由调用方退回遍历目录
+    fn workspace_member_manifests(&self) -> Result<Option<Vec<PathBuf>>> {
+        let root_cargo_path = root_file(&self.root, "Cargo.toml");
+        let cargo: CargoToml = toml::from_str(&fs::read_to_string(&root_cargo_path)?)?;
+        let Some(workspace) = cargo.workspace else {
+            return Ok(None);
+        };
+        let Some(members) = workspace.members else {
+            // 没有 members 时成员由根 package 的 path 依赖隐式决定，交给遍历目录处理
+            return Ok(None);
+        };
+        let excluded: Vec<PathBuf> = workspace
+            .exclude
+            .unwrap_or_default()
+            .iter()
+            // 不存在的排除路径不会匹配任何成员，直接忽略
+            .filter_map(|path| fs::canonicalize(self.root.join(path)).ok())
+            .collect();
+        let submodules = self.submodule_dirs()?;
+
+        let mut visited = HashSet::new();
+        let mut cargo_files = Vec::new();
+        if cargo.package.is_some() && visited.insert(fs::canonicalize(&root_cargo_path)?) {
+            cargo_files.push(root_cargo_path);
+        }
+
+        let mut member_dirs = Vec::new();
+        for member in &members {
+            let member = member.trim_start_matches("./").trim_end_matches('/');
+            let pattern = self.root.join(member);
+            let paths = glob::glob(&pattern.to_string_lossy()).map_err(|error| {
+                anyhow!("workspace.members 中的路径无效: {}: {}", member, error)
+            })?;
+            let literal = Pattern::escape(member) == member;
+            let mut matched = false;
+            // 与 cargo 一致，glob 只展开目录，展开出的目录都必须有 Cargo.toml
+            for dir in paths
+                .filter_map(|path| path.ok())
+                .filter(|path| path.is_dir())
+            {
+                matched = true;
+                if !dir.join("Cargo.toml").is_file() {
+                    return Err(error::validation(format!(
+                        "workspace 成员 {} 缺少 Cargo.toml",
+                        dir.display()
+                    )));
+                }
+                member_dirs.push(dir);
+            }
+            if !matched && literal {
+                return Err(error::validation(format!(
+                    "workspace 成员 {} 不存在",
+                    pattern.display()
+                )));
+            }
+        }
+        member_dirs.sort();
+
+        for dir in member_dirs {
+            let canonical = fs::canonicalize(&dir)?;
+            if excluded
+                .iter()
+                .any(|excluded| canonical.starts_with(excluded))
+            {
+                debug!("⏭️  跳过 workspace.exclude 中的目录: {}", dir.display());
+                continue;
+            }
+            if Self::is_submodule(&submodules, &dir) {
+                continue;
+            }
+            if visited.insert(canonical.join("Cargo.toml")) {
+                cargo_files.push(dir.join("Cargo.toml"));
+            }
+        }
+
+        Ok(Some(cargo_files))
+    }
+
+    /// 需要跳过的子模块目录（绝对路径），--include-submodules 或不在 git 仓库中时为空
+    fn submodule_dirs(&self) -> Result<Vec<PathBuf>> {
+        if self.global.include_submodules || !self.git.is_repository() {
+            return Ok(Vec::new());
+        }
+        let toplevel = self.git.toplevel()?;
+        let submodules: Vec<PathBuf> = self
+            .git
+            .submodule_paths()?
+            .into_iter()
+            .map(|path| toplevel.join(path))
+            .collect();
+        for submodule in &submodules {
+            debug!("⏭️  跳过子模块: {}", submodule.display());
+        }
+        Ok(submodules)
+    }
+
+    fn is_submodule(submodules: &[PathBuf], dir: &Path) -> bool {
+        !submodules.is_empty() && fs::canonicalize(dir).is_ok_and(|dir| submodules.contains(&dir))
+    }
+
+    /// 从 cargo metadata 的输出中取出 workspace 成员的清单，路径与遍历目录时的形式一致
+    fn metadata_manifests(output: &std::process::Output, root: &Path) -> Result<Vec<PathBuf>> {
+        if !output.status.success() {
+            return Err(error::validation(format!(
+                "cargo metadata 执行失败:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let invalid = || anyhow!("无法解析 cargo metadata 的输出");
+        let workspace_root = metadata["workspace_root"].as_str().ok_or_else(invalid)?;
+        let members: HashSet<&str> = metadata["workspace_members"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .filter_map(|id| id.as_str())
+            .collect();
+
+        let mut cargo_files: Vec<PathBuf> = metadata["packages"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .filter(|package| {
+                package["id"]
+                    .as_str()
+                    .is_some_and(|id| members.contains(id))
+            })
+            .filter_map(|package| package["manifest_path"].as_str())
+            .map(|manifest| {
+                let relative = Path::new(manifest)
+                    .strip_prefix(workspace_root)
+                    .unwrap_or(Path::new(manifest));
+                root.join(relative)
+            })
+            .collect();
+        cargo_files.sort();
+        Ok(cargo_files)
+    }
+
+    /// 更新 workspace.package.version，返回根 Cargo.toml 是否设置了该字段
+    fn update_root_workspace_version(&mut self) -> Result<bool> {
+        let root_cargo_path = root_file(&self.root, "Cargo.toml");
+        let content = fs::read_to_string(&root_cargo_path)?;
+        let mut document: DocumentMut = content.parse()?;
+
+        // 更新 workspace.package.version，保留其余内容的格式和注释
+        let old_version = document
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("package"))
+            .and_then(|package| package.get_mut("version"))
+            .and_then(|version| replace_toml_string(version, &self.version));
+
+        if let Some(old_version) = old_version {
+            self.workspace_version = Some(old_version.clone());
+            let new_content = document.to_string();
+            self.stage_change(
+                &root_cargo_path,
+                Some(content),
+                new_content,
+                Some(&old_version),
+            );
+            info!(
+                "✅ 更新 workspace 版本: {} -> {}",
+                old_version, self.version
+            );
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn update_single_crate(&mut self, cargo_path: &Path) -> Result<()> {
+        let mut updates = vec![Self::plan_crate_update(
+            &self.global,
+            &self.args,
+            &self.version,
+            &self.root,
+            None,
+            cargo_path,
+        )?];
+        for extra_path in self.extra_manifests(&[cargo_path.to_path_buf()])? {
+            updates.push(Self::plan_crate_update(
+                &self.global,
+                &self.args,
+                &self.version,
+                &self.root,
+                None,
+                &extra_path,
+            )?);
+        }
+
+        for update in self.skip_unchanged(updates)? {
+            self.apply_crate_update(update)?;
+        }
+        Ok(())
+    }
+
+    /// --changed-only 时跳过自上一个标签以来目录下没有改动的 crate
+    ///
+    /// 按 crate 分别打标签时比较的是该 crate 自己的上一个标签，没有历史标签的 crate 总是会被更新
+    fn skip_unchanged(&self, updates: Vec<CrateUpdate>) -> Result<Vec<CrateUpdate>> {
+        if !self.args.changed_only {
+            return Ok(updates);
+        }
+
+        let inherited: Vec<&str> = updates
+            .iter()
+            .filter_map(|update| match update {
+                CrateUpdate::Inherited(name, _) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !inherited.is_empty() {
+            return Err(error::validation(format!(
+                "--changed-only 需要各 crate 使用独立的版本号，以下 crate 继承 workspace 版本: {}",
+                inherited.join(", ")
+            )));
+        }
+
+        let toplevel = self.git.toplevel()?;
+        let mut changed_paths: Vec<(String, Vec<PathBuf>)> = Vec::new();
+        let mut result = Vec::with_capacity(updates.len());
+        for update in updates {
+            let CrateUpdate::Updated { name, change, .. } = &update else {
+                result.push(update);
+                continue;
+            };
+
+            let prefix = if self.per_crate_tags() {
+                self.crate_tag_prefix(name)
+            } else {
+                self.global.tag_prefix.clone()
+            };
+            let Some(tag) = self.previous_tag(&prefix)? else {
+                debug!("🔍 {} 没有历史标签，视为有改动", name);
+                result.push(update);
+                continue;
+            };
+            if !changed_paths.iter().any(|(known, _)| *known == tag) {
+                let paths = self
+                    .git
+                    .changed_paths(&tag, self.head())?
+                    .into_iter()
+                    .map(|path| toplevel.join(path))
+                    .collect();
+                changed_paths.push((tag.clone(), paths));
+            }
+            let paths = changed_paths
+                .iter()
+                .find(|(known, _)| *known == tag)
+                .map(|(_, paths)| paths.as_slice())
+                .unwrap_or_default();
+
+            let dir = change
+                .path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let dir = fs::canonicalize(dir)?;
+            if paths.iter().any(|path| path.starts_with(&dir)) {
+                result.push(update);
+            } else {
+                let name = name.clone();
+                result.push(CrateUpdate::Skipped(name, SkipReason::Unchanged(tag)));
+            }
+        }
+        Ok(result)
+    }
+
+    /// --extra-manifest 指定的清单，路径与遍历目录时的形式一致，已在 `discovered` 中的会被忽略
+    fn extra_manifests(&self, discovered: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut visited = discovered
+            .iter()
+            .map(fs::canonicalize)
+            .collect::<std::io::Result<HashSet<_>>>()?;
+
+        let mut manifests = Vec::new();
+        for path in &self.global.extra_manifest {
+            let mut manifest = Path::new(".").join(path);
+            if manifest.is_dir() {
+                manifest.push("Cargo.toml");
+            }
+            let canonical = fs::canonicalize(&manifest).map_err(|_| {
+                error::validation(format!(
+                    "--extra-manifest 指定的清单不存在: {}",
+                    path.display()
+                ))
+            })?;
+            if visited.insert(canonical) {
+                manifests.push(manifest);
+            } else {
+                debug!("⏭️  {} 已在查找结果中", manifest.display());
+            }
+        }
+        Ok(manifests)
+    }
+
+    /// 未指定任何过滤条件和 --all 时，使用 workspace.default-members 作为默认的更新范围
+    fn default_members(&self) -> Result<Option<Vec<Pattern>>> {
+        let args = &self.global;
+        let filtered = !args.only.is_empty()
+            || !args.exclude.is_empty()
+            || !args.only_path.is_empty()
+            || !args.exclude_path.is_empty();
+        if args.all || filtered {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(root_file(&self.root, "Cargo.toml"))?;
+        let cargo: CargoToml = toml::from_str(&content)?;
+        let Some(members) = cargo
+            .workspace
+            .and_then(|workspace| workspace.default_members)
+        else {
+            return Ok(None);
+        };
+
+        info!(
+            "🔍 只更新 workspace.default-members: {}",
+            members.join(", ")
+        );
+        let patterns = members
+            .iter()
+            .map(|member| {
+                let member = member.trim_start_matches("./").trim_end_matches('/');
+                Pattern::new(member).map_err(|error| {
+                    anyhow!(
+                        "workspace.default-members 中的路径无效: {}: {}",
+                        member,
+                        error
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some(patterns))
+    }
+
+    /// 计算单个 Cargo.toml 的改动，不修改任何状态，可以并行执行
+    fn plan_crate_update(
+        global: &GlobalArgs,
+        args: &ReleaseArgs,
+        version: &str,
+        root: &Path,
+        default_members: Option<&[Pattern]>,
+        cargo_path: &Path,
+    ) -> Result<CrateUpdate> {
+        let content = fs::read_to_string(cargo_path)?;
+        let cargo: CargoToml = toml::from_str(&content)?;
+
+        // 检查是否需要跳过此 crate
+        let Some(ref package) = cargo.package else {
+            return Ok(CrateUpdate::NotPackage);
+        };
+        let name = package.name.clone();
+
+        // 按 crate 所在目录（相对于仓库根目录）过滤
+        let dir = cargo_path.parent().unwrap_or(Path::new(""));
+        let dir = dir.strip_prefix(".").unwrap_or(dir);
+        if global
+            .exclude_path
+            .iter()
+            .any(|pattern| pattern.matches_path(dir))
+        {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::PathExcluded));
+        }
+        if !global.only_path.is_empty()
+            && !global
+                .only_path
+                .iter()
+                .any(|pattern| pattern.matches_path(dir))
+        {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::NotInOnlyPath));
+        }
+        // workspace.default-members 中的路径相对于所在 workspace 的根目录
+        let member_dir = cargo_path
+            .parent()
+            .and_then(|dir| dir.strip_prefix(root).ok())
+            .unwrap_or(dir);
+        if let Some(default_members) = default_members
+            && !default_members
+                .iter()
+                .any(|pattern| pattern.matches_path(member_dir))
+        {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::NotDefaultMember));
+        }
+
+        // 先按 only 列表选择，再从中排除
+        if !global.only.is_empty() && !global.only.contains(&name) {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::NotInOnly));
+        }
+        if global.exclude.contains(&name) {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::Excluded));
+        }
+
+        // 与 cargo 一致，不会发布的 crate 不需要新版本号
+        if !global.include_unpublished && Self::publish_disabled(package, root)? {
+            return Ok(CrateUpdate::Skipped(name, SkipReason::PublishDisabled));
+        }
+
+        // 检查 crates.io 上是否已发布
+        let mut unpublished = false;
+        if args.only_if_version_greater_on_registry {
+            if registry::is_published(&name, version)? {
+                if args.skip_published {
+                    return Ok(CrateUpdate::Skipped(name, SkipReason::Published));
+                }
+                return Err(error::validation(format!(
+                    "crate {} 的版本 {} 已发布到 crates.io，使用 --skip-published 跳过已发布的 crate",
+                    name, version
+                )));
+            }
+            unpublished = true;
+        }
+
+        // 继承 workspace 版本的 crate 随根 Cargo.toml 一起更新
+        let PackageVersion::Literal(old_version) = package.version.clone() else {
+            return Ok(CrateUpdate::Inherited(name, cargo_path.to_path_buf()));
+        };
+
+        let updated = Self::set_package_version(cargo_path, &content, version)?;
+
+        Ok(CrateUpdate::Updated {
+            change: FileChange {
+                path: cargo_path.to_path_buf(),
+                original: Some(content),
+                updated,
+                old_version: Some(old_version.clone()),
+            },
+            name,
+            old_version,
+            unpublished,
+        })
+    }
+
+    /// crate 是否设置了 publish = false 或 publish = []，支持 publish.workspace = true
+    fn publish_disabled(package: &CargoPackage, root: &Path) -> Result<bool> {
+        let publish = match package.other.get("publish") {
+            Some(toml::Value::Table(table))
+                if table.get("workspace") == Some(&toml::Value::Boolean(true)) =>
+            {
+                let root: CargoToml =
+                    toml::from_str(&fs::read_to_string(root_file(root, "Cargo.toml"))?)?;
+                root.workspace
+                    .and_then(|workspace| workspace.package)
+                    .and_then(|package| package.other.get("publish").cloned())
+            }
+            publish => publish.cloned(),
+        };
+        Ok(match publish {
+            Some(toml::Value::Boolean(publish)) => !publish,
+            Some(toml::Value::Array(registries)) => registries.is_empty(),
+            _ => false,
+        })
+    }
+
+    /// 记录 crate 的改动并输出结果，按 crate 的顺序串行执行以保证日志稳定
+    fn apply_crate_update(&mut self, update: CrateUpdate) -> Result<()> {
+        match update {
+            CrateUpdate::NotPackage => {}
+            CrateUpdate::Inherited(name, path) => {
+                debug!("🔗 {} 继承 workspace 版本", name);
+                if self.args.materialize_versions {
+                    self.materialize_version(&name, &path)?;
+                }
+                if let Some(old_version) = self.workspace_version.clone() {
+                    self.record_crate_version(&name, &path, old_version);
+                }
+                self.crate_names.push(name);
+            }
+            CrateUpdate::Skipped(name, reason) => {
+                match reason {
+                    SkipReason::Excluded => debug!("⏭️  跳过 crate: {}", name),
+                    SkipReason::NotInOnly => {
+                        debug!("⏭️  跳过 crate (不在 --only 列表中): {}", name)
+                    }
+                    SkipReason::PathExcluded => {
+                        debug!("⏭️  跳过 crate (目录匹配 --exclude-path): {}", name)
+                    }
+                    SkipReason::NotInOnlyPath => {
+                        debug!("⏭️  跳过 crate (目录不匹配 --only-path): {}", name)
+                    }
+                    SkipReason::NotDefaultMember => debug!(
+                        "⏭️  跳过 crate (不在 default-members 中，使用 --all 更新): {}",
+                        name
+                    ),
+                    SkipReason::Published => debug!(
+                        "⏭️  跳过 crate (crates.io 已发布 {}): {}",
+                        self.version, name
+                    ),
+                    SkipReason::PublishDisabled => debug!(
+                        "⏭️  跳过 crate (publish = false，使用 --include-unpublished 更新): {}",
+                        name
+                    ),
+                    SkipReason::Unchanged(tag) => {
+                        debug!("⏭️  跳过 crate (自 {} 以来没有改动): {}", tag, name)
+                    }
+                }
+                self.crate_names.push(name);
+            }
+            CrateUpdate::Updated {
+                name,
+                old_version,
+                unpublished,
+                change,
+            } => {
+                if unpublished {
+                    info!("🔎 crates.io 未发布: {}@{}", name, self.version);
+                }
+                let relative_path = change.path.strip_prefix(".").unwrap_or(&change.path);
+                info!(
+                    "✅ 更新 {} ({}): {} -> {}",
+                    relative_path.display(),
+                    name,
+                    old_version,
+                    self.version
+                );
+                self.record_crate_version(&name, &change.path, old_version);
+                self.changes.push(change);
+                self.crate_names.push(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// 同步 workspace 中对本次发布的 crate 的依赖版本要求
+    fn update_dependency_requirements(&mut self, cargo_toml_files: &[PathBuf]) -> Result<()> {
+        self.update_manifest_dependencies(&root_file(&self.root, "Cargo.toml"), true, &[])?;
+
+        let tables: Vec<&str> = self
+            .args
+            .update_deps
+            .iter()
+            .filter_map(|table| table.key())
+            .collect();
+        if tables.is_empty() {
+            return Ok(());
+        }
+        for path in cargo_toml_files {
+            self.update_manifest_dependencies(path, false, &tables)?;
+        }
+        Ok(())
+    }
+
+    /// 改写单个清单中的依赖表，包括 [target.*] 下的同名表
+    fn update_manifest_dependencies(
+        &mut self,
+        path: &Path,
+        workspace: bool,
+        tables: &[&str],
+    ) -> Result<()> {
+        // 清单可能已经因为版本号或其他依赖表被修改过
+        let relative_path = path.strip_prefix(".").unwrap_or(path);
+        let staged = self.changes.iter().position(|change| {
+            change.path.strip_prefix(".").unwrap_or(&change.path) == relative_path
+        });
+        let content = match staged {
+            Some(index) => self.changes[index].updated.clone(),
+            None => fs::read_to_string(path)?,
+        };
+        let mut document: DocumentMut = content.parse()?;
+
+        let mut updated_any = false;
+        if workspace
+            && let Some(dependencies) = document
+                .get_mut("workspace")
+                .and_then(|workspace| workspace.get_mut("dependencies"))
+                .and_then(|dependencies| dependencies.as_table_like_mut())
+        {
+            updated_any |= self.rewrite_requirements(dependencies, "[workspace.dependencies]");
+        }
+        for table in tables {
+            let label = format!("{} [{}]", relative_path.display(), table);
+            if let Some(dependencies) = document
+                .get_mut(table)
+                .and_then(|dependencies| dependencies.as_table_like_mut())
+            {
+                updated_any |= self.rewrite_requirements(dependencies, &label);
+            }
+            let targets = document
+                .get_mut("target")
+                .and_then(|targets| targets.as_table_like_mut());
+            for (_, target) in targets.into_iter().flat_map(|targets| targets.iter_mut()) {
+                if let Some(dependencies) = target
+                    .get_mut(table)
+                    .and_then(|dependencies| dependencies.as_table_like_mut())
+                {
+                    updated_any |= self.rewrite_requirements(dependencies, &label);
+                }
+            }
+        }
+
+        if !updated_any {
+            return Ok(());
+        }
+        match staged {
+            Some(index) => self.changes[index].updated = document.to_string(),
+            None => self.stage_change(path, Some(content), document.to_string(), None),
+        }
+        Ok(())
+    }
+
+    /// 将依赖表中指向本次发布的 crate 的版本要求改为新版本，返回是否有改动
+    fn rewrite_requirements(
+        &self,
+        dependencies: &mut dyn toml_edit::TableLike,
+        label: &str,
+    ) -> bool {
+        let mut updated_any = false;
+        for (key, item) in dependencies.iter_mut() {
+            // 重命名的依赖通过 package 指定真实的 crate 名
+            let name = item
+                .get("package")
+                .and_then(|package| package.as_str())
+                .unwrap_or(key.get())
+                .to_string();
+            if !self.crate_versions.iter().any(|krate| krate.name == name) {
+                continue;
+            }
+
+            let version = if item.is_str() {
+                item
+            } else {
+                match item.get_mut("version") {
+                    Some(version) => version,
+                    None => continue,
+                }
+            };
+            let Some(requirement) = version.as_str() else {
+                continue;
+            };
+            if requirement.contains(',') {
+                warn!(
+                    "⚠️  {} 中 {} 的版本要求 {} 包含多个条件，跳过",
+                    label,
+                    key.get(),
+                    requirement
+                );
+                continue;
+            }
+            // 保留 =、^、~ 等运算符
+            let operator_len = requirement
+                .find(|c: char| !matches!(c, '=' | '^' | '~' | '<' | '>' | ' '))
+                .unwrap_or(requirement.len());
+            let new_requirement = format!("{}{}", &requirement[..operator_len], self.version);
+            if let Some(old) = replace_toml_string(version, &new_requirement)
+                && old != new_requirement
+            {
+                info!(
+                    "✅ 更新 {} {}: {} -> {}",
+                    label,
+                    key.get(),
+                    old,
+                    new_requirement
+                );
+                updated_any = true;
+            }
+        }
+        updated_any
+    }
+
+    /// 将成员清单中继承的 package.version 替换为新版本号，保留其余内容的格式
+    fn materialize_version(&mut self, name: &str, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        let mut document: DocumentMut = content
+            .parse()
+            .map_err(|error| anyhow!("解析 {} 失败: {}", path.display(), error))?;
+        let Some(package) = document
+            .get_mut("package")
+            .and_then(|package| package.as_table_like_mut())
+        else {
+            return Ok(());
+        };
+        package.insert("version", toml_edit::value(self.version.as_str()));
+
+        let relative_path = path.strip_prefix(".").unwrap_or(path);
+        info!(
+            "✅ 将 {} ({}) 的继承版本改写为 {}",
+            relative_path.display(),
+            name,
+            self.version
+        );
+        let old_version = self.workspace_version.clone();
+        self.stage_change(
+            path,
+            Some(content),
+            document.to_string(),
+            old_version.as_deref(),
+        );
+        Ok(())
+    }
+
+    fn record_crate_version(&mut self, name: &str, path: &Path, old_version: String) {
+        self.crate_versions.push(CrateVersion {
+            name: name.to_string(),
+            path: path.strip_prefix(".").unwrap_or(path).to_path_buf(),
+            old_version,
+            new_version: self.version.clone(),
+        });
+    }
+
+    /// 只替换 package.version 的值，保留清单中的注释、键顺序和格式
+    fn set_package_version(path: &Path, content: &str, version: &str) -> Result<String> {
+        let mut document: DocumentMut = content
+            .parse()
+            .map_err(|error| anyhow!("解析 {} 失败: {}", path.display(), error))?;
+        let item = document
+            .get_mut("package")
+            .and_then(|package| package.as_table_like_mut())
+            .and_then(|package| package.get_mut("version"));
+        if item
+            .and_then(|item| replace_toml_string(item, version))
+            .is_none()
+        {
+            return Err(anyhow!(
+                "{} 中的 package.version 不是字符串",
+                path.display()
+            ));
+        }
+        Ok(document.to_string())
+    }
+
+    /// 内置的版本文件条目，对应的文件不存在时会被跳过
+    fn default_sinks(&self) -> Result<Vec<VersionSink>> {
+        let builtin = |name: &str, path: PathBuf, format: SinkFormat| VersionSink {
+            name: Some(name.to_string()),
+            path,
+            format,
+            builtin: true,
+        };
+        let keys = |keys: &[&[&str]]| -> Vec<Vec<String>> {
+            keys.iter()
+                .map(|key| key.iter().map(|part| part.to_string()).collect())
+                .collect()
+        };
+
+        // 默认位置只更新第一个存在的 tauri.conf.json，--tauri-config 指定的文件全部更新
+        let mut tauri_paths: Vec<PathBuf> = Vec::new();
+        if !self.args.no_tauri_search_paths {
+            let default_path = ["tauri.conf.json", "src-tauri/tauri.conf.json"]
+                .into_iter()
+                .find(|path| Path::new(path).exists())
+                .unwrap_or("tauri.conf.json");
+            tauri_paths.push(default_path.into());
+        }
+        for path in &self.args.tauri_config {
+            let path = path.strip_prefix(".").unwrap_or(path);
+            if !path.is_file() {
+                warn!("⚠️  未找到 {}，跳过", path.display());
+            } else if !tauri_paths.iter().any(|existing| existing == path) {
+                tauri_paths.push(path.to_path_buf());
+            }
+        }
+        // Tauri 1 的版本号在 package.version 中
+        let mut sinks: Vec<VersionSink> = tauri_paths
+            .into_iter()
+            .map(|path| {
+                builtin(
+                    TAURI_SINK,
+                    path,
+                    SinkFormat::Json(keys(&[&["version"], &["package", "version"]])),
+                )
+            })
+            .collect();
+
+        for path in &self.args.package_json {
+            sinks.push(builtin(
+                PACKAGE_JSON_SINK,
+                path.clone(),
+                SinkFormat::Json(keys(&[&["version"]])),
+            ));
+            if self.args.package_lock {
+                sinks.push(builtin(
+                    PACKAGE_LOCK_SINK,
+                    path.with_file_name("package-lock.json"),
+                    SinkFormat::Json(keys(&[&["version"], &["packages", "", "version"]])),
+                ));
+            }
+        }
+
+        // PEP 621 的 [project] 和 Poetry 的 [tool.poetry]
+        sinks.push(builtin(
+            PYPROJECT_SINK,
+            "pyproject.toml".into(),
+            SinkFormat::Toml(keys(&[
+                &["project", "version"],
+                &["tool", "poetry", "version"],
+            ])),
+        ));
+        sinks.push(builtin(VERSION_SINK, "VERSION".into(), SinkFormat::Plain));
+
+        for path in &self.args.readme {
+            sinks.extend(self.readme_sinks(path)?);
+        }
+
+        // 在每个 workspace 根目录的默认位置查找，只更新存在的 Chart.yaml，
+        // --helm-chart 指定的文件不存在时警告
+        let mut chart_paths: Vec<PathBuf> = Vec::new();
+        for root in workspace_roots(&self.global.manifest_path) {
+            for pattern in HELM_CHART_PATHS {
+                let pattern = root.join(pattern);
+                for path in glob::glob(&pattern.to_string_lossy())?.flatten() {
+                    let path = path.strip_prefix(".").unwrap_or(&path).to_path_buf();
+                    if path.is_file() && !chart_paths.contains(&path) {
+                        chart_paths.push(path);
+                    }
+                }
+            }
+        }
+        for path in &self.args.helm_chart {
+            let path = path.strip_prefix(".").unwrap_or(path);
+            if !path.is_file() {
+                warn!("⚠️  未找到 {}，跳过", path.display());
+            } else if !chart_paths.iter().any(|existing| existing == path) {
+                chart_paths.push(path.to_path_buf());
+            }
+        }
+        for path in chart_paths {
+            sinks.push(builtin(
+                HELM_SINK,
+                path,
+                SinkFormat::Yaml(keys(&[&["appVersion"]])),
+            ));
+        }
+        Ok(sinks)
+    }
+
+    /// --readme 的安装命令和徽章条目
+    ///
+    /// 安装命令只匹配本次发布的 crate，避免改动 README 中其他工具的安装命令
+    fn readme_sinks(&self, path: &Path) -> Result<Vec<VersionSink>> {
+        let mut sinks = Vec::new();
+        let names: Vec<String> = self
+            .crate_versions
+            .iter()
+            .map(|krate| regex::escape(&krate.name))
+            .collect();
+        if !names.is_empty() {
+            let names = names.join("|");
+            // cargo install demo --version 1.2.3、--version=1.2.3、--version "1.2.3"、
+            // cargo install demo@1.2.3 和 cargo add demo@1.2.3
+            let command = format!(
+                r#"(cargo (?:install|add)\b[^\n`]*?\s(?:{names})(?:@|\s[^\n`]*?--version(?:\s+|=)["']?)){semver}"#,
+                semver = SEMVER_PATTERN,
+            );
+            // 行首的 demo = "1.2.3" 和 demo = { version = "1.2.3", ... }，保留 ^、~ 和 =
+            let dependency = format!(
+                r#"(?m)(^[ \t]*(?:{names})[ \t]*=[ \t]*(?:\{{[^}}\n]*?\bversion[ \t]*=[ \t]*)?"[=^~]?){semver}"#,
+                semver = SEMVER_PATTERN,
+            );
+            for pattern in [command, dependency] {
+                sinks.push(VersionSink {
+                    name: Some(README_INSTALL_SINK.to_string()),
+                    path: path.to_path_buf(),
+                    format: SinkFormat::Regex {
+                        pattern: Regex::new(&pattern)?,
+                        replacement: "${1}{version}".to_string(),
+                        extensions: Vec::new(),
+                        max_matches: None,
+                    },
+                    builtin: true,
+                });
+            }
+        }
+
+        // 徽章路径中的 - 和 _ 需要写成 -- 和 __，+ 需要编码
+        let escaped = self
+            .version
+            .replace('-', "--")
+            .replace('_', "__")
+            .replace('+', "%2B");
+        let pattern = r#"(img\.shields\.io/badge/[^-/\s)"']*(?i:version|release)[^-/\s)"']*-v?)[0-9]+\.[0-9]+\.[0-9]+(?:--[0-9A-Za-z.]+)*(?:%2B[0-9A-Za-z.]+)?(-)"#;
+        sinks.push(VersionSink {
+            name: Some(README_BADGE_SINK.to_string()),
+            path: path.to_path_buf(),
+            format: SinkFormat::Regex {
+                pattern: Regex::new(pattern)?,
+                replacement: format!("${{1}}{}${{2}}", escaped),
+                extensions: Vec::new(),
+                max_matches: None,
+            },
+            builtin: true,
+        });
+        Ok(sinks)
+    }
+
+    /// 按条目更新文件中的版本号，同一个文件的多个条目在已计算的改动上继续更新
+    fn update_sink(&mut self, sink: &VersionSink) -> Result<()> {
+        let path = &sink.path;
+        let regex = matches!(sink.format, SinkFormat::Regex { .. });
+        if let SinkFormat::Regex { extensions, .. } = &sink.format
+            && !extensions.is_empty()
+        {
+            let extension = path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+            if !extension.is_some_and(|extension| extensions.contains(&extension)) {
+                info!("⏭️  {} 的扩展名不在 extensions 中，跳过", path.display());
+                return Ok(());
+            }
+        }
+
+        let staged = self.changes.iter().position(|change| change.path == *path);
+        let content = match staged {
+            Some(index) => self.changes[index].updated.clone(),
+            // 正则可能匹配到任意文件，不修改看起来是二进制的文件
+            None if path.is_file() && regex => match text_content(&fs::read(path)?) {
+                Some(content) => content,
+                None => {
+                    warn!("⚠️  {} 看起来是二进制文件，跳过", path.display());
+                    return Ok(());
+                }
+            },
+            None if path.is_file() => fs::read_to_string(path)?,
+            None => {
+                if sink.builtin {
+                    debug!("⚠️  未找到 {}，跳过", path.display());
+                } else {
+                    warn!("⚠️  未找到 {}，跳过", path.display());
+                }
+                return Ok(());
+            }
+        };
+
+        let updated = match &sink.format {
+            SinkFormat::Json(keys) => self.update_json_sink(sink, &content, keys)?,
+            SinkFormat::Toml(keys) => self.update_toml_sink(path, &content, keys)?,
+            SinkFormat::Yaml(keys) => self.update_yaml_sink(sink, &content, keys)?,
+            SinkFormat::Regex {
+                pattern,
+                replacement,
+                max_matches,
+                ..
+            } => self.update_regex_sink(path, &content, pattern, replacement, *max_matches)?,
+            SinkFormat::Plain => self.update_plain_sink(path, &content),
+            SinkFormat::Markdown(languages) => {
+                self.update_markdown_sink(path, &content, languages)?
+            }
+        };
+        let Some((updated, old_version)) = updated else {
+            return Ok(());
+        };
+
+        match staged {
+            Some(index) => {
+                let change = &mut self.changes[index];
+                change.updated = updated;
+                if change.old_version.is_none() {
+                    change.old_version = old_version;
+                }
+            }
+            None => self.stage_change(path, Some(content), updated, old_version.as_deref()),
+        }
+        Ok(())
+    }
+
+    /// 替换 JSON 中指定键路径上的版本号，只改动对应的值，不重新序列化，保留原有的键顺序和格式
+    fn update_json_sink(
+        &self,
+        sink: &VersionSink,
+        content: &str,
+        keys: &[Vec<String>],
+    ) -> Result<Option<(String, Option<String>)>> {
+        let path = sink.path.display();
+        serde_json::from_str::<serde_json::Value>(content)
+            .map_err(|error| anyhow!("解析 {} 失败: {}", path, error))?;
+        let tauri = sink.name.as_deref() == Some(TAURI_SINK);
+
+        let mut updated = content.to_string();
+        let mut old_version = None;
+        for key in keys {
+            let key: Vec<&str> = key.iter().map(String::as_str).collect();
+            if let Some((old, new_content)) =
+                json_edit::replace_string(&updated, &key, &self.version)
+            {
+                old_version.get_or_insert(old);
+                updated = new_content;
+            }
+        }
+
+        match &old_version {
+            Some(old_version) => {
+                // Tauri 只接受 semver 版本号（或指向 package.json 的路径），--force 写入的其他格式会在构建时报错
+                if tauri && version::parse(&self.version).is_err() {
+                    warn!(
+                        "⚠️  {} 不是有效的 semver 版本号，Tauri 构建时会拒绝 {} 中的 version",
+                        self.version, path
+                    );
+                }
+                info!("✅ 更新 {}: {} -> {}", path, old_version, self.version);
+            }
+            // 没有设置 version（或为 null）时 Tauri 使用 Cargo.toml 中的版本号
+            None if tauri => warn!(
+                "⚠️  {} 没有设置 {}，Tauri 将使用 Cargo.toml 中的版本号，跳过",
+                path,
+                key_names(keys)
+            ),
+            None => warn!("⚠️  {} 中没有 {} 字段，跳过", path, key_names(keys)),
+        }
+
+        if tauri && self.args.mobile {
+            updated = self.bump_mobile_versions(&updated)?;
+        }
+        if old_version.is_none() && updated == content {
+            return Ok(None);
+        }
+        Ok(Some((updated, old_version)))
+    }
+
+    /// 替换 TOML 中指定键路径上的版本号，保留原有的注释和格式
+    fn update_toml_sink(
+        &self,
+        path: &Path,
+        content: &str,
+        keys: &[Vec<String>],
+    ) -> Result<Option<(String, Option<String>)>> {
+        let mut document: DocumentMut = content
+            .parse()
+            .map_err(|error| anyhow!("解析 {} 失败: {}", path.display(), error))?;
+
+        let mut old_version = None;
+        for key in keys {
+            // Item::get_mut 会插入不存在的键，通过 TableLike 查找以免改动文件
+            let mut item = Some(document.as_item_mut());
+            for part in key {
+                item = item
+                    .and_then(|item| item.as_table_like_mut())
+                    .and_then(|table| table.get_mut(part));
+            }
+            if let Some(item) = item
+                && let Some(old) = replace_toml_string(item, &self.version)
+            {
+                old_version.get_or_insert(old);
+            }
+        }
+
+        match old_version {
+            Some(old_version) => {
+                info!(
+                    "✅ 更新 {}: {} -> {}",
+                    path.display(),
+                    old_version,
+                    self.version
+                );
+                Ok(Some((document.to_string(), Some(old_version))))
+            }
+            None => {
+                warn!(
+                    "⚠️  {} 中没有 {} 字段，跳过",
+                    path.display(),
+                    key_names(keys)
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// 替换 YAML 中指定键路径上的版本号，保留原有的注释、缩进和引号
+    fn update_yaml_sink(
+        &self,
+        sink: &VersionSink,
+        content: &str,
+        keys: &[Vec<String>],
+    ) -> Result<Option<(String, Option<String>)>> {
+        let path = &sink.path;
+        let mut updated = content.to_string();
+        let mut old_version = None;
+        for key in keys {
+            let key: Vec<&str> = key.iter().map(String::as_str).collect();
+            if let Some((old, new_content)) =
+                yaml_edit::replace_scalar(&updated, &key, &self.version)
+            {
+                old_version.get_or_insert(old);
+                updated = new_content;
+            }
+        }
+
+        let helm = sink.name.as_deref() == Some(HELM_SINK);
+        match &old_version {
+            Some(old_version) => info!(
+                "✅ 更新 {}: {} -> {}",
+                path.display(),
+                old_version,
+                self.version
+            ),
+            None if helm && self.args.helm_chart_version.is_some() => warn!(
+                "⚠️  {} 中没有 {} 字段，只更新 chart 版本",
+                path.display(),
+                key_names(keys)
+            ),
+            None => warn!(
+                "⚠️  {} 中没有 {} 字段，跳过",
+                path.display(),
+                key_names(keys)
+            ),
+        }
+
+        // chart 版本与 appVersion 分开处理，appVersion 已是目标版本时说明已经发布过，不再递增
+        if helm && let Some(mode) = self.args.helm_chart_version {
+            if old_version.as_deref() == Some(self.version.as_str()) {
+                info!(
+                    "⏭️  {} 的 appVersion 已是 {}，不更新 chart 版本",
+                    path.display(),
+                    self.version
+                );
+            } else {
+                updated = self.bump_chart_version(path, &updated, mode)?;
+            }
+        }
+
+        if old_version.is_none() && updated == content {
+            return Ok(None);
+        }
+        Ok(Some((updated, old_version)))
+    }
+
+    /// --helm-chart-version：更新 Chart.yaml 中 chart 自身的 version
+    fn bump_chart_version(&self, path: &Path, content: &str, mode: ChartVersion) -> Result<String> {
+        let Some(span) = yaml_edit::find_scalar(content, &["version"]) else {
+            warn!(
+                "⚠️  {} 中没有 version 字段，不更新 chart 版本",
+                path.display()
+            );
+            return Ok(content.to_string());
+        };
+        let old = &content[span];
+        let bump = match mode {
+            ChartVersion::Release => None,
+            ChartVersion::Patch => Some(Bump::Patch),
+            ChartVersion::Minor => Some(Bump::Minor),
+            ChartVersion::Major => Some(Bump::Major),
+        };
+        let new = match bump {
+            Some(bump) => {
+                let current = version::parse(old).map_err(|_| {
+                    error::invalid_version(format!(
+                        "{} 中的 chart 版本 {} 不是有效的 semver 版本号",
+                        path.display(),
+                        old
+                    ))
+                })?;
+                bump.apply(&current).to_string()
+            }
+            None => self.version.clone(),
+        };
+
+        let Some((old, updated)) = yaml_edit::replace_scalar(content, &["version"], &new) else {
+            return Ok(content.to_string());
+        };
+        info!(
+            "✅ 更新 {} 的 chart 版本: {} -> {}",
+            path.display(),
+            old,
+            new
+        );
+        Ok(updated)
+    }
+
+    fn update_regex_sink(
+        &self,
+        path: &Path,
+        content: &str,
+        pattern: &Regex,
+        replacement: &str,
+        max_matches: Option<usize>,
+    ) -> Result<Option<(String, Option<String>)>> {
+        let matches = pattern.find_iter(content).count();
+        if matches == 0 {
+            warn!(
+                "⚠️  {} 中没有匹配 {} 的内容，跳过",
+                path.display(),
+                pattern.as_str()
+            );
+            return Ok(None);
+        }
+        if let Some(max_matches) = max_matches
+            && matches > max_matches
+        {
+            return Err(error::validation(format!(
+                "{} 中有 {} 处匹配 {}，超过了 max-matches = {}，请检查正则是否过于宽泛",
+                path.display(),
+                matches,
+                pattern.as_str(),
+                max_matches
+            )));
+        }
+
+        let replacement = replacement.replace("{version}", &self.version);
+        let updated = pattern
+            .replace_all(content, replacement.as_str())
+            .into_owned();
+        info!("✅ 更新 {}: {} 处匹配", path.display(), matches);
+        Ok(Some((updated, None)))
+    }
+
+    /// 更新 Markdown 围栏代码块中本次发布的 crate 的版本号，正文保持不变
+    ///
+    /// toml 代码块中替换依赖声明里的版本号，其他代码块中替换 cargo add/install 命令里的版本号，
+    /// 版本要求前的 ^、~ 和 = 会被保留
+    fn update_markdown_sink(
+        &self,
+        path: &Path,
+        content: &str,
+        languages: &[String],
+    ) -> Result<Option<(String, Option<String>)>> {
+        if self.crate_versions.is_empty() {
+            debug!("⚠️  没有更新版本号的 crate，跳过 {}", path.display());
+            return Ok(None);
+        }
+        let names = self
+            .crate_versions
+            .iter()
+            .map(|krate| regex::escape(&krate.name))
+            .collect::<Vec<_>>()
+            .join("|");
+        let new_version = |name: &str| {
+            self.crate_versions
+                .iter()
+                .find(|krate| krate.name == name)
+                .map_or(self.version.as_str(), |krate| krate.new_version.as_str())
+        };
+
+        // mycrate = "1.2.3" 和 mycrate = { version = "1.2.3", ... }
+        let inline = Regex::new(&format!(
+            r#"^(?P<prefix>\s*(?P<name>{names})\s*=\s*(?:\{{[^}}\n]*?\bversion\s*=\s*)?["'][=^~]?){SEMVER_PATTERN}"#
+        ))?;
+        // [dependencies.mycrate] 下的 version = "1.2.3"
+        let table = Regex::new(
+            r#"^\s*\[(?:[^\]]*\.)?(?:dev-|build-)?dependencies\.["']?(?P<name>[^\]"'\s]+)["']?\s*\]"#,
+        )?;
+        let table_version = Regex::new(&format!(
+            r#"^(?P<prefix>\s*version\s*=\s*["'][=^~]?){SEMVER_PATTERN}"#
+        ))?;
+        // cargo add mycrate@1.2.3 和 cargo install mycrate --version 1.2.3
+        let command = Regex::new(&format!(
+            r#"(?P<prefix>cargo (?:add|install)\b[^\n]*?\s(?P<name>{names})(?:@|\s[^\n]*?--vers(?:ion)?(?:\s+|=)["']?)){SEMVER_PATTERN}"#
+        ))?;
+
+        let mut updated = content.to_string();
+        let mut matches = 0;
+        // 从后往前替换，前面代码块的位置不受影响
+        for block in markdown::code_blocks(content).into_iter().rev() {
+            if !languages.contains(&block.language) {
+                continue;
+            }
+            let code = &content[block.content.clone()];
+            let replaced = if block.language == "toml" {
+                let mut dependency: Option<String> = None;
+                let mut lines = String::with_capacity(code.len());
+                for line in code.split_inclusive('\n') {
+                    if line.trim_start().starts_with('[') {
+                        dependency = table
+                            .captures(line)
+                            .map(|captures| captures["name"].to_string())
+                            .filter(|name| {
+                                self.crate_versions.iter().any(|krate| krate.name == *name)
+                            });
+                    }
+                    let line = match &dependency {
+                        Some(name) if table_version.is_match(line) => {
+                            matches += 1;
+                            table_version
+                                .replace(line, |captures: &regex::Captures| {
+                                    format!("{}{}", &captures["prefix"], new_version(name))
+                                })
+                                .into_owned()
+                        }
+                        _ if inline.is_match(line) => {
+                            matches += 1;
+                            inline
+                                .replace(line, |captures: &regex::Captures| {
+                                    format!(
+                                        "{}{}",
+                                        &captures["prefix"],
+                                        new_version(&captures["name"])
+                                    )
+                                })
+                                .into_owned()
+                        }
+                        _ => line.to_string(),
+                    };
+                    lines.push_str(&line);
+                }
+                lines
+            } else {
+                matches += command.find_iter(code).count();
+                command
+                    .replace_all(code, |captures: &regex::Captures| {
+                        format!("{}{}", &captures["prefix"], new_version(&captures["name"]))
+                    })
+                    .into_owned()
+            };
+            updated.replace_range(block.content, &replaced);
+        }
+
+        if matches == 0 {
+            warn!(
+                "⚠️  {} 的代码块中没有本次发布的 crate 的版本号，跳过",
+                path.display()
+            );
+            return Ok(None);
+        }
+        info!("✅ 更新 {}: {} 处代码块中的版本号", path.display(), matches);
+        Ok(Some((updated, None)))
+    }
+
+    /// 更新只包含版本号的文件，保留原有的行尾换行
+    fn update_plain_sink(&self, path: &Path, content: &str) -> Option<(String, Option<String>)> {
+        let old_version = content.trim();
+        if old_version.is_empty() {
+            warn!("⚠️  {} 为空，跳过", path.display());
+            return None;
+        }
+        if old_version.lines().count() > 1 {
+            warn!("⚠️  {} 包含多行内容，跳过", path.display());
+            return None;
+        }
+
+        let line_ending = if content.ends_with("\r\n") {
+            "\r\n"
+        } else if content.ends_with('\n') {
+            "\n"
+        } else {
+            ""
+        };
+        info!(
+            "✅ 更新 {}: {} -> {}",
+            path.display(),
+            old_version,
+            self.version
+        );
+        Some((
+            format!("{}{}", self.version, line_ending),
+            Some(old_version.to_string()),
+        ))
+    }
+
+    /// 递增 Android versionCode 和 iOS bundleVersion（CFBundleVersion）
+    fn bump_mobile_versions(&self, content: &str) -> Result<String> {
+        let next_code = |old: u64| -> Result<u64> {
+            match self.args.mobile_version_code {
+                MobileVersionCode::Increment => Ok(old + 1),
+                MobileVersionCode::Semver => {
+                    let version = version::parse(&self.version)?;
+                    Ok(version.major * 1_000_000 + version.minor * 1_000 + version.patch)
+                }
+            }
+        };
+
+        let mut updated = content.to_string();
+
+        let android_path = ["bundle", "android", "versionCode"];
+        if let Some(span) = json_edit::find_value(&updated, &android_path) {
+            let old: u64 = updated[span]
+                .parse()
+                .map_err(|_| anyhow!("bundle.android.versionCode 不是整数"))?;
+            let new = next_code(old)?;
+            if let Some((_, new_content)) =
+                json_edit::replace_raw(&updated, &android_path, &new.to_string())
+            {
+                info!("✅ 更新 Android versionCode: {} -> {}", old, new);
+                updated = new_content;
+            }
+        }
+
+        let ios_path = ["bundle", "iOS", "bundleVersion"];
+        if let Some(old) = json_edit::get_string(&updated, &ios_path) {
+            let old_code: u64 = old
+                .parse()
+                .map_err(|_| anyhow!("bundle.iOS.bundleVersion 不是整数: {}", old))?;
+            let new = next_code(old_code)?.to_string();
+            if let Some((_, new_content)) = json_edit::replace_string(&updated, &ios_path, &new) {
+                info!("✅ 更新 iOS bundleVersion: {} -> {}", old, new);
+                updated = new_content;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    fn update_changelog(&mut self) -> Result<()> {
+        if self.global.format == OutputFormat::Json && self.args.changelog_to_stdout() {
+            return Err(error::validation(
+                "--format json 时 stdout 只输出 JSON，不能同时使用 --changelog-output -",
+            ));
+        }
+        if self.args.keep_changelog_unreleased && self.release_unreleased_section()? {
+            return Ok(());
+        }
+
+        let since = match &self.args.since {
+            Some(since) => {
+                self.git
+                    .resolve_commit(since)
+                    .map_err(|_| error::validation(format!("无法解析 --since: {}", since)))?;
+                Some(since.clone())
+            }
+            None => self.previous_tag(&self.global.tag_prefix)?,
+        };
+        let commits = self.git.commits_since(since.as_deref(), self.head())?;
+        if commits.is_empty() {
+            match &since {
+                Some(since) => warn!("⚠️  自 {} 以来没有新的提交，跳过更新日志", since),
+                None => warn!("⚠️  没有任何提交，跳过更新日志"),
+            }
+            return Ok(());
+        }
+
+        let mut notes = changelog::render_notes(&commits, self.args.commit_link.as_deref());
+        if let Some(since) = &since {
+            match self.git.remote_url("origin")? {
+                Some(url) => match changelog::compare_url(&url, since, &self.tag_name()) {
+                    Some(compare) => notes = changelog::append_compare_link(&notes, &compare),
+                    None => debug!(
+                        "⏭️  无法识别远程仓库 {} 的比较页面地址，不添加比较链接",
+                        url
+                    ),
+                },
+                None => debug!("⏭️  没有 origin 远程仓库，不添加比较链接"),
+            }
+        }
+        if self.args.no_changelog_file {
+            info!("✅ 生成更新日志: {} 个提交", commits.len());
+            self.changelog = Some(notes);
+            return Ok(());
+        }
+        let changelog_path = Path::new("CHANGELOG.md");
+        let original = read_existing(changelog_path)?;
+        let updated = changelog::prepend(
+            original.as_deref().unwrap_or_default(),
+            &self.version,
+            &notes,
+        );
+        self.stage_change(changelog_path, original, updated, None);
+        info!("✅ 更新 CHANGELOG.md: {} 个提交", commits.len());
+
+        self.changelog = Some(notes);
+        Ok(())
+    }
+
+    /// --keep-changelog-unreleased：发布 CHANGELOG.md 中的 Unreleased 部分，没有可发布的内容时返回 `false`
+    fn release_unreleased_section(&mut self) -> Result<bool> {
+        let changelog_path = Path::new("CHANGELOG.md");
+        let Some(original) = read_existing(changelog_path)? else {
+            warn!("⚠️  CHANGELOG.md 中没有 ## [Unreleased] 部分，根据提交记录生成更新日志");
+            return Ok(false);
+        };
+
+        let section = match changelog::find_unreleased(&original) {
+            Some(section) if !section.notes(&original).is_empty() => section,
+            Some(_) => {
+                warn!("⚠️  CHANGELOG.md 中的 Unreleased 部分为空，根据提交记录生成更新日志");
+                return Ok(false);
+            }
+            None => {
+                warn!("⚠️  CHANGELOG.md 中没有 ## [Unreleased] 部分，根据提交记录生成更新日志");
+                return Ok(false);
+            }
+        };
+
+        let notes = section.notes(&original).to_string();
+        let updated =
+            changelog::release_unreleased(&original, &section, &self.version, &self.tag_name());
+        self.stage_change(changelog_path, Some(original), updated, None);
+        info!("✅ 更新 CHANGELOG.md: Unreleased -> {}", self.version);
+
+        self.changelog = Some(notes);
+        Ok(true)
+    }
+
+    fn commit_changes(&self) -> Result<()> {
+        info!("💾 提交更改...");
+
+        if self.global.dry_run {
+            let commit_message = self.commit_message();
+            let mut args = if self.uses_message_file() {
+                vec!["commit", "-F", "-"]
+            } else {
+                vec!["commit", "-m", &commit_message]
+            };
+            let gpg_sign = self.gpg_sign_arg();
+            if let Some(gpg_sign) = &gpg_sign {
+                args.push(gpg_sign);
+            }
+            if self.args.no_verify {
+                args.push("--no-verify");
+            }
+            for stage in self.stage_args() {
+                let stage: Vec<&str> = stage.iter().map(String::as_str).collect();
+                self.plan_git(&stage);
+            }
+            self.plan_git(&args);
+            return Ok(());
+        }
+
+        // 添加所有更改的文件
+        for stage in self.stage_args() {
+            git::run(
+                self.git_command().args(&stage),
+                self.global.quiet_git,
+                || "暂存更改失败".to_string(),
+            )?;
+        }
+
+        // 版本号未变化（例如重新发布）时没有可提交的内容
+        if self.is_working_tree_clean()? {
+            info!("✅ 没有需要提交的更改");
+            return Ok(());
+        }
+
+        // 生成提交信息
+        let commit_message = self.commit_message();
+
+        // 提交
+        let mut commit = self.git_command();
+        commit.arg("commit");
+        if let Some(gpg_sign) = self.gpg_sign_arg() {
+            commit.arg(gpg_sign);
+        }
+        if self.args.no_verify {
+            commit.arg("--no-verify");
+        }
+        let input = if self.uses_message_file() {
+            // 多行提交信息通过标准输入传给 git commit -F -
+            commit.arg("-F").arg("-");
+            Some(commit_message.as_str())
+        } else {
+            commit.arg("-m").arg(&commit_message);
+            None
+        };
+        git::run_with_input(&mut commit, self.global.quiet_git, input, || {
+            format!("提交失败: {}", self.commit_summary())
+        })?;
+
+        info!("✅ 提交完成: {}", self.commit_summary());
+        Ok(())
+    }
+
+    /// --at、--tag-only 和 --finalize 模式只创建标签，不更新版本文件也不提交
+    fn skips_commit(&self) -> bool {
+        self.args.at.is_some() || self.args.tag_only || self.args.finalize || self.already_released
+    }
+
+    /// --idempotent 时判断本次发布的提交和标签是否已经完成
+    ///
+    /// 标签不存在时返回 `false`；标签存在但指向其他提交，或版本文件不是目标版本时报错，
+    /// 避免掩盖真正的冲突
+    fn is_already_released(&self) -> Result<bool> {
+        let tag_name = self.tag_name();
+        if !self.git.tag_exists(&tag_name)? {
+            return Ok(false);
+        }
+
+        let tagged = self
+            .git
+            .resolve_commit(&format!("refs/tags/{}", tag_name))?;
+        let target = self.git.resolve_commit(self.tag_target())?;
+        if tagged != target {
+            return Err(error::tag_exists(format!(
+                "标签 {} 已存在，但指向 {} 而不是 {} ({})\n--idempotent 不会覆盖已有的标签，使用 --re-publish 重新发布",
+                tag_name,
+                tagged,
+                self.tag_target(),
+                target
+            )));
+        }
+
+        // 只打标签的模式不修改版本文件
+        if !self.skips_commit() {
+            match self.current_version() {
+                Ok(current) if current != self.version => {
+                    return Err(error::validation(format!(
+                        "标签 {} 已指向 {}，但 Cargo.toml 中的版本号为 {}",
+                        tag_name,
+                        self.tag_target(),
+                        current
+                    )));
+                }
+                Ok(_) => {}
+                // workspace 的根 Cargo.toml 可能没有版本号，此时只检查标签
+                Err(error) => debug!("⏭️  无法读取当前版本号 ({})，只检查标签", error),
+            }
+        }
+        Ok(true)
+    }
+
+    /// --at 和 --finalize 模式下提交已在远程仓库中，只推送标签
+    fn pushes_tags_only(&self) -> bool {
+        self.args.at.is_some() || self.args.finalize
+    }
+
+    /// --prepare 模式的发布分支
+    fn prepare_branch(&self) -> String {
+        self.args.prepare_branch.replace("{version}", &self.version)
+    }
+
+    /// 计算发布时视为 HEAD 的提交，--dry-run --at 时为预览的提交
+    fn head(&self) -> &str {
+        self.preview.as_deref().unwrap_or("HEAD")
+    }
+
+    /// 标签指向的提交，默认为 HEAD
+    fn tag_target(&self) -> &str {
+        self.args.at.as_deref().unwrap_or("HEAD")
+    }
+
+    fn tag_name(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.global.tag_prefix,
+            self.version,
+            self.tag_suffix()
+        )
+    }
+
+    fn tag_suffix(&self) -> &str {
+        self.global.tag_suffix.as_deref().unwrap_or_default()
+    }
+
+    /// 指定 --tag-per-crate 或标签前缀包含 {crate} 时按 crate 分别打标签
+    fn per_crate_tags(&self) -> bool {
+        self.global.tag_per_crate || self.global.tag_prefix.contains(CRATE_PLACEHOLDER)
+    }
+
+    /// 单个 crate 的标签前缀：优先使用配置文件 [tag-prefixes] 中的前缀，
+    /// 否则按 --tag-prefix 计算，前缀中没有 {crate} 时以 <crate>- 开头
+    fn crate_tag_prefix(&self, name: &str) -> String {
+        if let Some((_, prefix)) = self
+            .args
+            .tag_prefixes
+            .iter()
+            .find(|(crate_name, _)| crate_name == name)
+        {
+            return prefix.replace(CRATE_PLACEHOLDER, name);
+        }
+        if self.global.tag_prefix.contains(CRATE_PLACEHOLDER) {
+            self.global.tag_prefix.replace(CRATE_PLACEHOLDER, name)
+        } else {
+            format!("{}-{}", name, self.global.tag_prefix)
+        }
+    }
+
+    fn crate_tag_name(&self, name: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.crate_tag_prefix(name),
+            self.version,
+            self.tag_suffix()
+        )
+    }
+
+    /// 本次发布的全部标签，按 crate 分别打标签时每个更新了版本号的 crate 一个
+    fn tag_names(&self) -> Vec<String> {
+        if !self.per_crate_tags() {
+            return vec![self.tag_name()];
+        }
+        self.crate_versions
+            .iter()
+            .map(|krate| self.crate_tag_name(&krate.name))
+            .collect()
+    }
+
+    /// 按 crate 分别打标签时，依赖单个标签的选项无法使用
+    fn check_per_crate_tags(&self) -> Result<()> {
+        let unsupported = [
+            (self.args.auto, "--auto"),
+            (self.args.changelog, "--changelog"),
+            (self.args.github_release, "--github-release"),
+            (self.args.attest.is_some(), "--attest"),
+            (self.args.check_remote_tags, "--check-remote-tags"),
+            (self.args.at.is_some(), "--at"),
+            (self.args.tag_only, "--tag-only"),
+            (self.args.finalize, "--finalize"),
+            (self.args.workspace_version_only, "--workspace-version-only"),
+            (self.args.idempotent, "--idempotent"),
+        ];
+        if let Some((_, option)) = unsupported.iter().find(|(enabled, _)| *enabled) {
+            return Err(error::validation(format!(
+                "按 crate 分别打标签时不支持 {}",
+                option
+            )));
+        }
+        Ok(())
+    }
+
+    fn commit_message(&self) -> String {
+        if let Some(message) = &self.commit_message_override {
+            return message.clone();
+        }
+        let message = self
+            .args
+            .message
+            .replace(&self.args.version_token, &self.version)
+            .replace("{version}", &self.version);
+        if !self.args.detailed_commit {
+            return message;
+        }
+        format!("{}\n\n{}", message.trim_end(), self.commit_details())
+    }
+
+    /// --detailed-commit 时附在提交信息后的内容
+    fn commit_details(&self) -> String {
+        let mut details = String::new();
+        if !self.crate_versions.is_empty() {
+            details.push_str("Crates:\n");
+            for krate in &self.crate_versions {
+                details.push_str(&format!(
+                    "- {}: {} -> {}\n",
+                    krate.name, krate.old_version, krate.new_version
+                ));
+            }
+        }
+
+        // 干运行时文件尚未写入，只有内容确实变化的文件才会出现在提交中
+        let mut files: Vec<&Path> = self
+            .changes
+            .iter()
+            .filter(|change| !change.is_unchanged())
+            .map(|change| change.path.as_path())
+            .collect();
+        for path in &self.updated_files {
+            if !self.changes.iter().any(|change| &change.path == path) {
+                files.push(path);
+            }
+        }
+        if !files.is_empty() {
+            if !details.is_empty() {
+                details.push('\n');
+            }
+            details.push_str("Files:\n");
+            for path in files {
+                let path = path.strip_prefix(".").unwrap_or(path);
+                details.push_str(&format!("- {}\n", path.display()));
+            }
+        }
+        details
+    }
+
+    /// 提交信息有多行时通过 git commit -F 提交
+    fn uses_message_file(&self) -> bool {
+        self.args.message_file.is_some() || self.commit_message().contains('\n')
+    }
+
+    /// 提交信息的第一行
+    fn commit_summary(&self) -> String {
+        let message = self.commit_message();
+        message.lines().next().unwrap_or_default().to_string()
+    }
+
+    /// 附注标签的信息，生成了更新日志时将其附在标题之后，便于通过 git show 查看
+    fn tag_message(&self) -> Result<String> {
+        let mut title = self
+            .args
+            .tag_message
+            .replace(&self.args.version_token, &self.version)
+            .replace("{version}", &self.version)
+            .replace("{date}", &changelog::today());
+        for (placeholder, key) in [("{author}", "user.name"), ("{email}", "user.email")] {
+            if !title.contains(placeholder) {
+                continue;
+            }
+            let value = self.git.config(key)?.ok_or_else(|| {
+                error::validation(format!(
+                    "标签信息模板使用了 {}，但 git config {} 未设置",
+                    placeholder, key
+                ))
+            })?;
+            title = title.replace(placeholder, &value);
+        }
+
+        Ok(match &self.changelog {
+            Some(notes) if !notes.trim().is_empty() => format!("{}\n\n{}", title, notes.trim_end()),
+            _ => title,
+        })
+    }
+
+    /// 签名提交时传给 git commit 的参数
+    fn gpg_sign_arg(&self) -> Option<String> {
+        if !self.args.sign {
+            return None;
+        }
+        Some(match &self.args.signing_key {
+            Some(key) => format!("--gpg-sign={}", key),
+            None => "--gpg-sign".to_string(),
+        })
+    }
+
+    fn git_command(&self) -> StdCommand {
+        StdCommand::new(self.global.git_bin())
+    }
+
+    /// 干运行时输出将要执行的 git 命令
+    fn plan_git(&self, args: &[&str]) {
+        info!("   $ {}", git::render_command(args));
+    }
+
+    /// 创建标签，返回本次新创建的标签
+    fn handle_tag(&self) -> Result<Vec<String>> {
+        if !self.per_crate_tags() {
+            let tag_name = self.tag_name();
+            let created = self.create_release_tag(&tag_name, self.tag_message()?)?;
+            return Ok(created.then_some(tag_name).into_iter().collect());
+        }
+
+        let mut created_tags = Vec::new();
+        for krate in &self.crate_versions {
+            let tag_name = self.crate_tag_name(&krate.name);
+            git::check_tag_name(&tag_name)?;
+            let message = self.tag_message()?.replace(CRATE_PLACEHOLDER, &krate.name);
+            if self.create_release_tag(&tag_name, message)? {
+                created_tags.push(tag_name);
+            }
+        }
+        Ok(created_tags)
+    }
+
+    /// 创建单个标签，返回本次是否创建了新标签
+    fn create_release_tag(&self, tag_name: &str, mut tag_message: String) -> Result<bool> {
+        let tag_name = tag_name.to_string();
+        let mut replaced = false;
+
+        // 检查标签是否已存在
+        if self.git.tag_exists(&tag_name)? {
+            if self.args.re_publish {
+                let old_target = self
+                    .git
+                    .resolve_commit(&format!("refs/tags/{}", tag_name))?;
+                let new_target = self.git.resolve_commit(self.tag_target())?;
+                if old_target == new_target {
+                    info!("✅ 标签 {} 已指向 {}，无需重新创建", tag_name, new_target);
+                    return Ok(false);
+                }
+
+                info!("🔄 重新发布版本，删除旧标签...");
+                info!("   {}: {} -> {}", tag_name, old_target, new_target);
+
+                // 删除本地标签
+                if self.global.dry_run {
+                    self.plan_git(&["tag", "-d", &tag_name]);
+                } else {
+                    self.git.delete_tag(&tag_name)?;
+                }
+
+                // 删除所有远程仓库的标签
+                self.delete_remote_tags(&tag_name)?;
+                replaced = true;
+            } else {
+                return Err(error::tag_exists(format!(
+                    "标签 {} 已存在，使用 --re-publish 重新发布",
+                    tag_name
+                )));
+            }
+        } else if !self.remote_tags.is_empty() {
+            // 本地没有但远程已有的标签（--check-remote-tags 时已确认指定了 --re-publish）
+            info!("🔄 重新发布版本，删除远程仓库上的旧标签...");
+            for remote in &self.remote_tags {
+                info!("🗑️  删除远程标签 {}/{}", remote, tag_name);
+                if self.global.dry_run {
+                    self.plan_git(&[
+                        "push",
+                        remote,
+                        "--delete",
+                        &format!("refs/tags/{}", tag_name),
+                    ]);
+                    continue;
+                }
+                self.delete_remote_tag(remote, &tag_name)?;
+            }
+            replaced = true;
+        }
+
+        // 重新创建的标签在标题后注明，留下标签被移动过的记录
+        if replaced && let Some(note) = &self.args.re_publish_note {
+            let note = note.replace("{date}", &changelog::today());
+            tag_message = match tag_message.split_once('\n') {
+                Some((title, rest)) => format!("{} {}\n{}", title, note, rest),
+                None => format!("{} {}", tag_message, note),
+            };
+            info!(
+                "📝 标签信息: {}",
+                tag_message.lines().next().unwrap_or_default()
+            );
+        }
+
+        // 创建新标签
+        info!("🏷️  创建标签: {}", tag_name);
+        let request = TagRequest {
+            name: tag_name.clone(),
+            target: self.args.at.clone(),
+            message: (!self.args.lightweight).then_some(tag_message),
+            sign: self.args.sign,
+            signing_key: self.args.signing_key.clone(),
+        };
+        if self.global.dry_run {
+            self.plan_git(&request.command_args());
+            if self.args.sign {
+                self.plan_git(&["tag", "-v", &tag_name]);
+            }
+            return Ok(false);
+        }
+        let expected = self.git.resolve_commit(self.tag_target())?;
+        self.git.create_tag(&request)?;
+        self.check_tag_target(&tag_name, &expected)?;
+
+        // 签名有问题的标签不应被推送到远程仓库
+        if self.args.sign {
+            self.verify_tag_signature(&tag_name)?;
+        }
+        if self.args.verify_signature {
+            self.verify_commit_signature()?;
+        }
+
+        Ok(true)
+    }
+
+    fn run_hooks(&self, point: HookPoint) -> Result<()> {
+        for hook in self.args.run.iter().filter(|hook| hook.point == point) {
+            info!("🪝  执行 {} 钩子: {}", point, hook.command);
+
+            let status = hooks::shell_command(&hook.command)
+                .envs(self.release_env()?)
+                .env("RELEASE_HOOK", point.name())
+                .status()?;
+            if !status.success() {
+                return Err(anyhow!("{} 钩子执行失败: {}", point, hook.command));
+            }
+        }
+        Ok(())
+    }
+
+    /// 传递给外部命令的发布信息环境变量
+    fn release_env(&self) -> Result<Vec<(&'static str, String)>> {
+        let mut env = vec![
+            ("RELEASE_VERSION", self.version.clone()),
+            ("RELEASE_TAG", self.tag_names().join(" ")),
+            (
+                "RELEASE_COMMIT",
+                self.git.resolve_commit(self.tag_target())?,
+            ),
+        ];
+        if let Some(plan_file) = &self.plan_file {
+            env.push(("RELEASE_PLAN_FILE", plan_file.path().display().to_string()));
+        }
+        Ok(env)
+    }
+
+    /// 推送成功后向 --notify-webhook 发送通知，失败时只警告
+    fn send_notifications(&self, report: &ReleaseReport) {
+        if self.args.notify_webhook.is_empty() {
+            return;
+        }
+
+        let payload = self.notification_payload(report);
+        for url in &self.args.notify_webhook {
+            info!("🔔 发送通知到 {}", notify::redact(url));
+            match notify::send(url, &payload) {
+                Ok(()) => info!("✅ 通知已发送"),
+                Err(error) => warn!("⚠️  {}", error),
+            }
+        }
+    }
+
+    fn notification_payload(&self, report: &ReleaseReport) -> serde_json::Value {
+        /// 通知中附带的更新日志的最大长度（字符数）
+        const CHANGELOG_EXCERPT: usize = 1500;
+
+        let changelog = self.changelog.as_deref().unwrap_or_default().trim();
+        let changelog = if changelog.chars().count() > CHANGELOG_EXCERPT {
+            // 在行尾截断，避免截断 Markdown 链接
+            let cut: String = changelog.chars().take(CHANGELOG_EXCERPT).collect();
+            let cut = cut.rfind('\n').map_or(cut.as_str(), |end| &cut[..end]);
+            format!("{}\n…", cut)
+        } else {
+            changelog.to_string()
+        };
+        let remotes: Vec<&str> = report
+            .pushes
+            .iter()
+            .filter(|push| push.pushed)
+            .map(|push| push.remote.as_str())
+            .collect();
+        let crates = report
+            .crates
+            .iter()
+            .map(|krate| format!("{}@{}", krate.name, krate.new_version))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let message = self
+            .args
+            .notify_template
+            .replace("{version}", &report.version)
+            .replace("{tag}", &report.tags.join(" "))
+            .replace("{crates}", &crates)
+            .replace("{remotes}", &remotes.join(", "))
+            .replace("{changelog}", &changelog);
+
+        serde_json::json!({
+            // Slack 使用 text，Discord 使用 content
+            "text": message,
+            "content": message,
+            "version": report.version,
+            "tag": report.tag,
+            "tags": report.tags,
+            "crates": report.crates,
+            "remotes": remotes,
+            "changelog": changelog,
+        })
+    }
+
+    fn run_attestation(&self, command: &str) -> Result<()> {
+        info!("🔏 生成来源证明...");
+
+        let tag_name = self.tag_name();
+        let status = hooks::shell_command(command)
+            .envs(self.release_env()?)
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("来源证明命令执行失败: {}", command));
+        }
+
+        let attest_file = &self.args.attest_file;
+        if !attest_file.exists() {
+            warn!("⚠️  未找到来源证明文件 {}，跳过提交", attest_file.display());
+            return Ok(());
+        }
+
+        git::run(
+            self.git_command().arg("add").arg("--").arg(attest_file),
+            self.global.quiet_git,
+            || format!("暂存来源证明失败: {}", attest_file.display()),
+        )?;
+        let mut commit = self.git_command();
+        commit
+            .arg("commit")
+            .arg("-m")
+            .arg(format!("Add provenance for {}", tag_name));
+        if self.args.no_verify {
+            commit.arg("--no-verify");
+        }
+        git::run(&mut commit, self.global.quiet_git, || {
+            format!("提交来源证明失败: {}", attest_file.display())
+        })?;
+
+        info!("✅ 来源证明已提交: {}", attest_file.display());
+        Ok(())
+    }
+
+    /// 确认签名密钥存在，避免在提交之后才发现无法签名
+    fn check_signing_key(&self) -> Result<()> {
+        let key = match &self.args.signing_key {
+            Some(key) => Some(key.clone()),
+            None => self.git.config("user.signingkey")?,
+        };
+        let format = self
+            .git
+            .config("gpg.format")?
+            .unwrap_or_else(|| "openpgp".to_string());
+
+        match format.as_str() {
+            "openpgp" => {
+                let program = match self.git.config("gpg.openpgp.program")? {
+                    Some(program) => program,
+                    None => self
+                        .git
+                        .config("gpg.program")?
+                        .unwrap_or_else(|| "gpg".to_string()),
+                };
+                // 未指定密钥时 gpg 使用默认私钥，只要求存在任意私钥
+                let output = StdCommand::new(&program)
+                    .arg("--list-secret-keys")
+                    .args(key.as_deref())
+                    .stdin(Stdio::null())
+                    .output()
+                    .map_err(|error| {
+                        error::validation(format!("无法执行 {}: {}", program, error))
+                    })?;
+                if !output.status.success() || output.stdout.trim_ascii().is_empty() {
+                    return Err(error::validation(match &key {
+                        Some(key) => format!(
+                            "未找到签名密钥 {} 的私钥\n请使用 {} --list-secret-keys 确认密钥 ID",
+                            key, program
+                        ),
+                        None => format!(
+                            "{} 中没有可用于签名的私钥\n请使用 --signing-key 或 git config user.signingkey 指定密钥",
+                            program
+                        ),
+                    }));
+                }
+            }
+            "ssh" => {
+                let Some(key) = &key else {
+                    return Err(error::validation(
+                        "SSH 签名需要指定密钥\n请使用 --signing-key 或 git config user.signingkey 指定密钥文件",
+                    ));
+                };
+                // key:: 开头的是公钥字面量，私钥由 ssh-agent 提供，无法提前确认
+                if !key.starts_with("key::") {
+                    let path = match key.strip_prefix("~/") {
+                        Some(rest) => std::env::var_os("HOME")
+                            .map(|home| PathBuf::from(home).join(rest))
+                            .unwrap_or_else(|| PathBuf::from(key)),
+                        None => PathBuf::from(key),
+                    };
+                    if !path.is_file() {
+                        return Err(error::validation(format!(
+                            "SSH 签名密钥文件不存在: {}",
+                            path.display()
+                        )));
+                    }
+                }
+            }
+            _ => debug!("⚠️  不检查 {} 格式的签名密钥", format),
+        }
+
+        info!(
+            "🔑 签名密钥: {}",
+            key.as_deref().unwrap_or("(gpg 默认私钥)")
+        );
+        Ok(())
+    }
+
+    /// 检查上一个标签到发布目标之间每个提交的签名，列出所有未通过的提交
+    fn check_signed_commits(&self) -> Result<()> {
+        let last_tag = self.previous_tag(&self.global.tag_prefix)?;
+        let range = match &last_tag {
+            Some(tag) => format!("{}..{}", tag, self.tag_target()),
+            None => self.tag_target().to_string(),
+        };
+        info!("🔐 检查 {} 中提交的签名...", range);
+
+        let output = self
+            .git_command()
+            .args(["log", "--format=%H%x00%G?%x00%s", &range])
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(error::git(format!(
+                "读取 {} 的提交签名失败: {}",
+                range,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut count = 0;
+        let mut failures = Vec::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut fields = line.splitn(3, '\0');
+            let (Some(id), Some(status), subject) = (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            count += 1;
+            let problem = match status {
+                "G" | "U" => continue,
+                "N" => "未签名",
+                "B" => "签名无效",
+                "X" => "签名已过期",
+                "Y" => "签名密钥已过期",
+                "R" => "签名密钥已吊销",
+                "E" => "无法验证签名（缺少公钥？）",
+                _ => "签名状态未知",
+            };
+            failures.push(format!(
+                "{} {}: {}",
+                &id[..id.len().min(7)],
+                subject.unwrap_or_default(),
+                problem
+            ));
+        }
+
+        if failures.is_empty() {
+            info!("✅ {} 个提交的签名验证通过", count);
+            return Ok(());
+        }
+        let message = format!(
+            "{} 中有 {} 个提交没有通过签名验证:\n   {}",
+            range,
+            failures.len(),
+            failures.join("\n   ")
+        );
+        if self.global.force {
+            warn!("⚠️  {}", message);
+            return Ok(());
+        }
+        Err(error::validation(format!("{}\n使用 --force 忽略", message)))
+    }
+
+    fn verify_commit_signature(&self) -> Result<()> {
+        info!("🔐 验证发布提交的签名...");
+
+        git::run(
+            self.git_command()
+                .arg("verify-commit")
+                .arg(self.tag_target()),
+            self.global.quiet_git,
+            || "发布提交的签名验证失败".to_string(),
+        )?;
+
+        info!("✅ 提交签名验证通过");
+        Ok(())
+    }
+
+    /// 确认新标签指向发布提交（相当于 git rev-list -n 1 <tag>），否则中止推送
+    fn check_tag_target(&self, tag_name: &str, expected: &str) -> Result<()> {
+        let actual = self.git.resolve_commit(tag_name)?;
+        if actual != expected {
+            return Err(error::git(format!(
+                "标签 {} 指向 {}，而发布提交为 {}，已中止推送\n请确认没有其他进程同时修改仓库，本地标签可使用 git tag -d {} 删除",
+                tag_name, actual, expected, tag_name
+            )));
+        }
+        debug!("🔎 标签 {} 指向发布提交 {}", tag_name, actual);
+        Ok(())
+    }
+
+    /// 执行 git tag -v，只输出签名相关的几行，验证失败时中止发布
+    fn verify_tag_signature(&self, tag_name: &str) -> Result<()> {
+        info!("🔐 验证标签 {} 的签名...", tag_name);
+
+        let output = self
+            .git_command()
+            .args(["tag", "-v", tag_name])
+            .stdin(Stdio::null())
+            .output()?;
+        // 签名信息由 gpg/ssh-keygen 写到 stderr，stdout 是标签内容
+        let details = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            for line in details.lines().filter(|line| !line.trim().is_empty()) {
+                warn!("   {}", line.trim());
+            }
+            return Err(error::git(format!(
+                "标签 {} 的签名验证失败，已中止推送\n请检查签名密钥配置，本地标签可使用 git tag -d {} 删除",
+                tag_name, tag_name
+            )));
+        }
+
+        for line in details.lines().filter(|line| is_signature_summary(line)) {
+            info!("   {}", line.trim());
+        }
+        info!("✅ 标签签名验证通过");
+        Ok(())
+    }
+
+    fn delete_remote_tags(&self, tag_name: &str) -> Result<()> {
+        for remote in &self.remotes()? {
+            info!("🗑️  删除远程标签 {}/{}", remote, tag_name);
+            if self.global.dry_run {
+                self.plan_git(&[
+                    "push",
+                    remote,
+                    "--delete",
+                    &format!("refs/tags/{}", tag_name),
+                ]);
+                continue;
+            }
+            self.delete_remote_tag(remote, tag_name)?;
+        }
+
+        Ok(())
+    }
+
+    /// 删除单个远程标签：标签不存在时跳过，被保护规则拒绝时报错（--skip-protected 时只警告）
+    ///
+    /// 返回标签是否确实被删除
+    fn delete_remote_tag(&self, remote: &str, tag_name: &str) -> Result<bool> {
+        let _spinner = self.spinner(format!("正在删除 {}/{}", remote, tag_name));
+        let Err(error) = self.retry_push(|| self.git.delete_remote_tag(remote, tag_name)) else {
+            return Ok(true);
+        };
+
+        match error.downcast_ref::<PushError>().map(|error| error.kind) {
+            Some(PushErrorKind::MissingRef) => {
+                debug!("⏭️  {} 上没有标签 {}，跳过", remote, tag_name);
+                Ok(false)
+            }
+            Some(PushErrorKind::Protected) if self.args.skip_protected => {
+                warn!("⚠️  {} 上的标签 {} 受保护，跳过删除", remote, tag_name);
+                Ok(false)
+            }
+            Some(PushErrorKind::Protected) => Err(error::git(format!(
+                "{} 上的标签 {} 受保护，无法删除\n使用 --skip-protected 跳过受保护的标签",
+                remote, tag_name
+            ))),
+            _ => {
+                warn!("⚠️  删除 {}/{} 失败: {}", remote, tag_name, error);
+                Ok(false)
+            }
+        }
+    }
+
+    /// --revert：删除版本对应的本地和远程标签，--revert-commit 时还原发布提交并推送
+    fn revert_release(&mut self, version: &str, report: &mut ReleaseReport) -> Result<()> {
+        self.version = version.to_string();
+        if self.per_crate_tags() {
+            return Err(error::validation("按 crate 分别打标签时不支持 --revert"));
+        }
+        let tag_name = self.tag_name();
+        git::check_tag_name(&tag_name)?;
+        report.version = self.version.clone();
+        report.tag = tag_name.clone();
+
+        info!("⏪ 撤销发布: {}", tag_name);
+        self.check_git_repo()?;
+        self.check_expected_branch()?;
+
+        let local = self.git.tag_exists(&tag_name)?;
+        let mut remotes = Vec::new();
+        for remote in self.remotes()? {
+            if self.git.remote_tag_exists(&remote, &tag_name)? {
+                remotes.push(remote);
+            }
+        }
+        if !local && remotes.is_empty() {
+            if !self.global.force {
+                return Err(error::validation(format!(
+                    "标签 {} 在本地和远程仓库中都不存在\n使用 --force 忽略",
+                    tag_name
+                )));
+            }
+            warn!("⚠️  标签 {} 不存在，已使用 --force 继续", tag_name);
+            return Ok(());
+        }
+
+        // 删除本地标签前先解析出发布提交
+        let release_commit = if self.args.revert_commit {
+            if !local {
+                return Err(error::validation(format!(
+                    "本地没有标签 {}，无法确定要还原的提交\n使用 --fetch 或 git fetch --tags 拉取标签",
+                    tag_name
+                )));
+            }
+            if !self.is_working_tree_clean()? {
+                return Err(error::dirty_tree(
+                    "工作区有未提交的更改，还原提交前请先提交或暂存更改",
+                ));
+            }
+            report.branch = self.release_branch()?;
+            Some(
+                self.git
+                    .resolve_commit(&format!("refs/tags/{}", tag_name))?,
+            )
+        } else {
+            None
+        };
+
+        if let Some(commit) = &release_commit {
+            info!("↩️  还原发布提交 {}", commit);
+            if self.global.dry_run {
+                self.plan_git(&["revert", "--no-edit", commit]);
+            } else {
+                git::run(
+                    self.git_command().args(["revert", "--no-edit", commit]),
+                    self.global.quiet_git,
+                    || format!("还原提交 {} 失败，请解决冲突后手动完成 git revert", commit),
+                )?;
+            }
+            report.reverted_commit = Some(commit.clone());
+        }
+
+        if local {
+            info!("🗑️  删除本地标签 {}", tag_name);
+            if self.global.dry_run {
+                self.plan_git(&["tag", "-d", &tag_name]);
+            } else {
+                self.git.delete_tag(&tag_name)?;
+            }
+            report.removed_tags.push(tag_name.clone());
+        }
+
+        for remote in &remotes {
+            info!("🗑️  删除远程标签 {}/{}", remote, tag_name);
+            let deleted = if self.global.dry_run {
+                self.plan_git(&[
+                    "push",
+                    remote,
+                    "--delete",
+                    &format!("refs/tags/{}", tag_name),
+                ]);
+                true
+            } else {
+                self.delete_remote_tag(remote, &tag_name)?
+            };
+            if deleted {
+                report.removed_tags.push(format!("{}/{}", remote, tag_name));
+            }
+        }
+
+        if release_commit.is_some() {
+            let branch = report.branch.clone();
+            report.pushes = self.push_each(|remote| {
+                info!("⬆️  推送还原提交到 {}", remote);
+                self.push(remote, &[&branch])?;
+                Ok(RemotePush::default())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// 解析 GitHub API 的 token 和目标仓库，未指定 --github-release 和 --open-pr 时返回 `None`
+    fn github_target(&self) -> Result<Option<(String, String, String)>> {
+        let option = match (self.args.github_release, self.args.open_pr) {
+            (true, _) => "--github-release",
+            (_, true) => "--open-pr",
+            _ => return Ok(None),
+        };
+
+        let token = std::env::var("GITHUB_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| anyhow!("{} 需要设置 GITHUB_TOKEN 环境变量", option))?;
+        let url = self
+            .git
+            .remote_url("origin")?
+            .ok_or_else(|| anyhow!("{} 需要名为 origin 的远程仓库", option))?;
+        let (owner, repo) = github::parse_repo(&url)
+            .ok_or_else(|| anyhow!("无法从 origin 地址识别 GitHub 仓库: {}", url))?;
+        Ok(Some((token, owner, repo)))
+    }
+
+    fn create_github_release(&self, token: &str, owner: &str, repo: &str) -> Result<()> {
+        info!("🏷️  创建 GitHub Release {}/{}...", owner, repo);
+        let tag_name = self.tag_name();
+        let release = github::Release {
+            tag: &tag_name,
+            name: &tag_name,
+            body: self.changelog.as_deref().unwrap_or_default(),
+            draft: self.args.draft,
+            prerelease: self.args.prerelease,
+        };
+        let url = github::create_release(token, owner, repo, &release)?;
+        info!("✅ GitHub Release 已创建: {}", url);
+        Ok(())
+    }
+
+    /// --prepare 模式只推送发布分支
+    fn push_prepare_branch(&self) -> Result<Vec<PushOutcome>> {
+        let branch = self.prepare_branch();
+        self.push_each(|remote| {
+            info!("⬆️  推送发布分支到 {}", remote);
+            self.push(remote, &["-u", &branch])?;
+            Ok(RemotePush::default())
+        })
+    }
+
+    /// --at 和 --finalize 模式下没有新的提交，只推送新标签
+    fn push_tag_only(&self) -> Result<Vec<PushOutcome>> {
+        let tag_refspec = format!("refs/tags/{}", self.tag_name());
+        self.push_each(|remote| {
+            info!("⬆️  推送标签到 {}", remote);
+            self.push(remote, &[&tag_refspec])?;
+            Ok(RemotePush {
+                atomic: false,
+                refs: self.push_extra_refs(remote),
+            })
+        })
+    }
+
+    /// 依次推送到每个远程仓库，某个远程仓库失败时继续推送其余的，最后汇总失败的远程仓库
+    ///
+    /// `push_remote` 返回是否使用了原子推送和额外引用的推送结果
+    fn push_each(
+        &self,
+        push_remote: impl Fn(&str) -> Result<RemotePush>,
+    ) -> Result<Vec<PushOutcome>> {
+        let mut outcomes = Vec::new();
+        let mut failures = Vec::new();
+        for remote in self.remotes()? {
+            let result = push_remote(&remote);
+            let (atomic, refs) = match &result {
+                Ok(push) => (push.atomic, push.refs.clone()),
+                Err(_) => (false, Vec::new()),
+            };
+            outcomes.push(PushOutcome {
+                remote: remote.clone(),
+                pushed: !self.global.dry_run && result.is_ok(),
+                atomic,
+                error: result.as_ref().err().map(|error| error.to_string()),
+                refs,
+            });
+            if let Err(error) = result {
+                failures.push((remote, error));
+            }
+        }
+
+        if failures.is_empty() {
+            return Ok(outcomes);
+        }
+        if outcomes.len() > 1 {
+            info!("📋 推送结果:");
+            for outcome in &outcomes {
+                match &outcome.error {
+                    Some(error) => info!("   ❌ {}: {}", outcome.remote, error),
+                    None => info!("   ✅ {}", outcome.remote),
+                }
+            }
+        }
+        if failures.len() == 1 {
+            return Err(failures.remove(0).1);
+        }
+
+        // 全部被拒绝时保留拒绝的类型，以便决定是否撤销发布
+        let kinds: Vec<Option<PushErrorKind>> = failures
+            .iter()
+            .map(|(_, error)| error.downcast_ref::<PushError>().map(|error| error.kind))
+            .collect();
+        let kind = match kinds.first() {
+            Some(Some(kind)) if kinds.iter().all(|other| *other == Some(*kind)) => *kind,
+            _ => PushErrorKind::Other,
+        };
+        Err(PushError {
+            remote: failures
+                .iter()
+                .map(|(remote, _)| remote.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            kind,
+            detail: failures
+                .iter()
+                .map(|(remote, error)| format!("{}: {}", remote, error))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+        .into())
+    }
+
+    /// 推送到远程仓库，干运行时只输出命令
+    fn push(&self, remote: &str, args: &[&str]) -> Result<()> {
+        let args = self.push_args(args);
+        if self.global.dry_run {
+            self.plan_git(&[&["push", remote], &args[..]].concat());
+            return Ok(());
+        }
+        let _spinner = self.spinner(format!("正在推送到 {}", remote));
+        self.retry_push(|| self.git.push(remote, &args))?;
+        self.pushed_any.set(true);
+        Ok(())
+    }
+
+    /// 是否在网络操作期间显示进度指示器
+    fn shows_progress(&self) -> bool {
+        !self.global.quiet
+            && self.global.format == OutputFormat::Text
+            && !self.global.dry_run
+            && progress::is_terminal()
+    }
+
+    fn spinner(&self, message: String) -> progress::Spinner {
+        progress::Spinner::start(self.shows_progress(), message)
+    }
+
+    /// 逐个推送 --push-ref 指定的引用，失败时只警告
+    fn push_extra_refs(&self, remote: &str) -> Vec<RefPushOutcome> {
+        self.args
+            .push_ref
+            .iter()
+            .map(|refspec| {
+                let result = self.push(remote, &[refspec]);
+                if let Err(error) = &result {
+                    warn!("⚠️  推送 {} 到 {} 失败: {}", refspec, remote, error);
+                }
+                RefPushOutcome {
+                    refspec: refspec.clone(),
+                    pushed: !self.global.dry_run && result.is_ok(),
+                    error: result.err().map(|error| error.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// 附加 --no-verify 等通用推送参数
+    fn push_args<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        let mut push_args = Vec::with_capacity(args.len() + 1);
+        if self.args.no_verify {
+            push_args.push("--no-verify");
+        }
+        push_args.extend_from_slice(args);
+        push_args
+    }
+
+    /// 执行推送操作，临时错误按 --push-retries 重试，被远程拒绝时直接返回
+    fn retry_push<T>(&self, push: impl Fn() -> Result<T>) -> Result<T> {
+        let mut delay = Duration::from_secs(1);
+        let mut attempt = 0;
+        loop {
+            match push() {
+                Err(error)
+                    if attempt < self.args.push_retries
+                        && !error
+                            .downcast_ref::<PushError>()
+                            .is_some_and(PushError::is_rejected) =>
+                {
+                    attempt += 1;
+                    warn!(
+                        "🔄 {}，{} 秒后重试 ({}/{})",
+                        error,
+                        delay.as_secs(),
+                        attempt,
+                        self.args.push_retries
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 发布涉及的远程仓库，指定 --remote 时只使用这些远程仓库
+    fn remotes(&self) -> Result<Vec<String>> {
+        let remotes = self.git.remotes()?;
+        if self.global.remote.is_empty() {
+            return Ok(remotes);
+        }
+
+        for remote in &self.global.remote {
+            if !remotes.contains(remote) {
+                return Err(error::validation(format!(
+                    "远程仓库 {} 不存在，可用的远程仓库: {}",
+                    remote,
+                    remotes.join(", ")
+                )));
+            }
+        }
+        Ok(self.global.remote.clone())
+    }
+
+    /// 确认当前分支匹配 --release-branch
+    fn check_release_branch(&self) -> Result<()> {
+        if self.args.release_branch.is_empty() {
+            return Ok(());
+        }
 
-    /// 重新发布版本（如果标签已存在则删除重新创建）
-    #[arg(long, short = 'r')]
-    re_publish: bool,
+        let current = self.git.current_branch()?;
+        let allowed = current.as_deref().is_some_and(|branch| {
+            self.args
+                .release_branch
+                .iter()
+                .any(|pattern| pattern.matches(branch))
+        });
+        if allowed {
+            return Ok(());
+        }
 
-    /// 跳过版本号格式验证
-    #[arg(long, short = 'f')]
-    force: bool,
+        let patterns: Vec<&str> = self
+            .args
+            .release_branch
+            .iter()
+            .map(Pattern::as_str)
+            .collect();
+        let current = current.as_deref().unwrap_or("(HEAD 分离)");
+        if self.global.force {
+            warn!(
+                "⚠️  当前分支 {} 不在允许发布的分支中，已使用 --force 继续",
+                current
+            );
+            return Ok(());
+        }
+        Err(error::validation(format!(
+            "当前分支 {} 不允许发布，允许的分支: {}\n使用 --force 跳过此检查",
+            current,
+            patterns.join(", ")
+        )))
+    }
 
-    /// 提交信息模板，{version} 会被替换为实际版本号
-    #[arg(
-        long,
-        short = 'm',
-        default_value = "Release version {version}",
-        value_name = "MESSAGE"
-    )]
-    message: String,
+    /// 确认当前分支与 --expect-branch 一致，不受 --force 影响
+    fn check_expected_branch(&self) -> Result<()> {
+        let Some(expected) = &self.args.expect_branch else {
+            return Ok(());
+        };
 
-    /// 标签前缀，默认为 'v'
-    #[arg(long, default_value = "v", value_name = "PREFIX")]
-    tag_prefix: String,
+        match self.git.current_branch()? {
+            Some(current) if &current == expected => Ok(()),
+            Some(current) => Err(error::validation(format!(
+                "当前分支为 {}，与 --expect-branch 指定的 {} 不一致，已中止发布",
+                current, expected
+            ))),
+            None => Err(error::validation(format!(
+                "HEAD 处于分离状态，与 --expect-branch 指定的 {} 不一致，已中止发布\n请先执行 git checkout {}",
+                expected, expected
+            ))),
+        }
+    }
 
-    /// 只更新版本号，不执行 Git 操作
-    #[arg(long)]
-    dry_run: bool,
+    /// 要推送的分支，优先使用 --branch 指定的分支
+    fn release_branch(&self) -> Result<String> {
+        if let Some(branch) = &self.args.branch {
+            return Ok(branch.clone());
+        }
 
-    /// 排除更新的 crate 名称（可多次使用）
-    #[arg(long, value_name = "CRATE")]
-    exclude: Vec<String>,
+        self.git.current_branch()?.ok_or_else(|| {
+            error::validation(
+                "HEAD 处于分离状态，无法确定要推送的分支\n使用 --branch 指定要推送的分支",
+            )
+        })
+    }
 
-    /// 只更新指定的 crate（可多次使用），默认更新所有
-    #[arg(long, value_name = "CRATE")]
-    only: Vec<String>,
-}
+    fn push_to_remotes(&self) -> Result<Vec<PushOutcome>> {
+        info!("📤 推送到远程仓库...");
+        if self.pushes_tags_only() {
+            return self.push_tag_only();
+        }
+        if self.args.prepare {
+            return self.push_prepare_branch();
+        }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CargoToml {
-    package: Option<CargoPackage>,
-    workspace: Option<CargoWorkspace>,
-    #[serde(flatten)]
-    other: toml::Value,
-}
+        let branch = self.release_branch()?;
+        // 指定 --branch 时将当前提交推送到该分支
+        let branch_refspec = match (&self.args.push_refspec, &self.args.branch) {
+            (Some(template), _) => template.replace("{branch}", &branch),
+            (None, Some(_)) => format!("HEAD:refs/heads/{}", branch),
+            (None, None) => branch,
+        };
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CargoPackage {
-    name: String,
-    version: String,
-    #[serde(flatten)]
-    other: toml::Value,
-}
+        self.push_each(|remote| {
+            info!("⬆️  推送到 {}", remote);
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CargoWorkspace {
-    members: Option<Vec<String>>,
-    package: Option<WorkspacePackage>,
-    #[serde(flatten)]
-    other: toml::Value,
-}
+            if self.args.atomic {
+                let tag_refspecs: Vec<String> = self
+                    .tag_names()
+                    .iter()
+                    .map(|tag_name| format!("refs/tags/{}", tag_name))
+                    .collect();
+                let mut refspecs = vec![branch_refspec.as_str()];
+                refspecs.extend(tag_refspecs.iter().map(String::as_str));
+                refspecs.extend(self.args.push_ref.iter().map(String::as_str));
+                let refspecs = self.push_args(&refspecs);
+                let pushed = if self.global.dry_run {
+                    self.plan_git(&[&["push", "--atomic", remote], &refspecs[..]].concat());
+                    true
+                } else {
+                    let _spinner = self.spinner(format!("正在推送到 {}", remote));
+                    self.retry_push(|| self.git.push_atomic(remote, &refspecs))?
+                };
+                if pushed {
+                    self.pushed_any.set(true);
+                    let refs = self
+                        .args
+                        .push_ref
+                        .iter()
+                        .map(|refspec| RefPushOutcome {
+                            refspec: refspec.clone(),
+                            pushed: !self.global.dry_run,
+                            error: None,
+                        })
+                        .collect();
+                    return Ok(RemotePush { atomic: true, refs });
+                }
+                warn!("⚠️  {} 不支持原子推送，改为分别推送提交和标签", remote);
+            }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct WorkspacePackage {
-    version: Option<String>,
-    #[serde(flatten)]
-    other: toml::Value,
-}
+            // 推送提交
+            self.push(remote, &[&branch_refspec])?;
 
-#[derive(Debug, Deserialize, Serialize)]
-struct TauriConfig {
-    #[serde(flatten)]
-    other: serde_json::Value,
-    version: String,
+            // 推送标签
+            self.push(remote, &["--tags"])?;
+            Ok(RemotePush {
+                atomic: false,
+                refs: self.push_extra_refs(remote),
+            })
+        })
+    }
 }
 
-pub struct ReleaseTool {
-    args: Cli,
-    updated_files: Vec<PathBuf>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{CurrentDir, FakeGit, TempDir};
 
-impl ReleaseTool {
-    pub fn new(args: Cli) -> Self {
-        Self {
-            args,
-            updated_files: Vec::new(),
-        }
+    fn tool(args: &[&str], git: &FakeGit) -> ReleaseTool {
+        let args = std::iter::once("git-release").chain(args.iter().copied());
+        ReleaseTool::with_git(Cli::parse_from(args), Box::new(git.clone()))
     }
 
-    pub fn run(&mut self) -> Result<()> {
-        println!("🚀 开始发布版本: {}", self.args.version);
+    #[test]
+    fn re_publish_moves_existing_tag_to_head() {
+        let git = FakeGit::new(Path::new("/repo"), "new-head")
+            .with_tag("v1.2.3", "old-head")
+            .with_remote("origin");
+        let tool = tool(&["1.2.3", "--re-publish"], &git);
 
-        // 验证版本号格式
-        if !self.args.force {
-            self.validate_version_format()?;
-        }
+        let created = tool
+            .create_release_tag("v1.2.3", "Version 1.2.3".to_string())
+            .unwrap();
 
-        // 1. 检查是否是 git 仓库
-        self.check_git_repo()?;
+        assert!(created);
+        let state = git.state();
+        assert_eq!(state.deleted_tags, ["v1.2.3"]);
+        assert_eq!(
+            state.deleted_remote_tags,
+            [("origin".to_string(), "v1.2.3".to_string())]
+        );
+        assert_eq!(state.tags, [("v1.2.3".to_string(), "new-head".to_string())]);
+        assert_eq!(
+            state.created_tags[0].message.as_deref(),
+            Some("Version 1.2.3")
+        );
+    }
 
-        // 2. 检查工作区是否干净
-        if !self.is_working_tree_clean()? {
-            return Err(anyhow!("工作区有未提交的更改，请先提交或暂存更改"));
-        }
+    #[test]
+    fn re_publish_keeps_tag_already_at_head() {
+        let git = FakeGit::new(Path::new("/repo"), "head")
+            .with_tag("v1.2.3", "head")
+            .with_remote("origin");
+        let tool = tool(&["1.2.3", "--re-publish"], &git);
 
-        // 3. 更新版本号
-        self.update_versions()?;
+        let created = tool
+            .create_release_tag("v1.2.3", "Version 1.2.3".to_string())
+            .unwrap();
 
-        if self.args.dry_run {
-            println!("✅ 干运行模式完成 - 更新了以下文件:");
-            for file in &self.updated_files {
-                println!("   - {}", file.display());
-            }
-            return Ok(());
-        }
+        assert!(!created);
+        let state = git.state();
+        assert!(state.deleted_tags.is_empty());
+        assert!(state.deleted_remote_tags.is_empty());
+        assert!(state.created_tags.is_empty());
+    }
 
-        // 4. 提交更改
-        self.commit_changes()?;
+    #[test]
+    fn existing_tag_requires_re_publish() {
+        let git = FakeGit::new(Path::new("/repo"), "new-head").with_tag("v1.2.3", "old-head");
+        let tool = tool(&["1.2.3"], &git);
 
-        // 5. 处理标签
-        self.handle_tag()?;
+        let error = tool
+            .create_release_tag("v1.2.3", "Version 1.2.3".to_string())
+            .unwrap_err();
 
-        // 6. 推送到所有远程仓库
-        self.push_to_remotes()?;
+        assert!(error.to_string().contains("--re-publish"));
+        assert!(git.state().created_tags.is_empty());
+    }
 
-        println!("✅ 版本发布成功: {}", self.args.version);
-        Ok(())
+    #[test]
+    fn already_released_only_when_tag_points_at_target() {
+        // --tag-only 不修改版本文件，不需要读取 Cargo.toml
+        let released = |git: &FakeGit| {
+            let mut tool = tool(&["--idempotent", "--tag-only"], git);
+            tool.version = "1.2.3".to_string();
+            tool.is_already_released()
+        };
+
+        let git = FakeGit::new(Path::new("/repo"), "head").with_tag("v1.2.3", "head");
+        assert!(released(&git).unwrap());
+
+        let git = FakeGit::new(Path::new("/repo"), "head").with_tag("v1.2.3", "other");
+        assert!(released(&git).is_err());
+
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        assert!(!released(&git).unwrap());
     }
 
-    fn validate_version_format(&self) -> Result<()> {
-        let version_re = Regex::new(r"^\d+\.\d+\.\d+(-[a-zA-Z0-9\.]+)?(\+[a-zA-Z0-9\.]+)?$")?;
-        if !version_re.is_match(&self.args.version) {
-            return Err(anyhow!(
-                "版本号格式不正确，请使用语义化版本号 (例如: 1.2.3, 2.0.0-beta.1)\n\
-                 使用 --force 跳过此验证"
-            ));
+    #[test]
+    fn workspace_dependency_pins_keep_formatting() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let mut tool = tool(&["1.3.0"], &git);
+        for name in ["core", "macros"] {
+            tool.crate_versions.push(CrateVersion {
+                name: name.to_string(),
+                path: PathBuf::from(format!("{}/Cargo.toml", name)),
+                old_version: "1.2.3".to_string(),
+                new_version: "1.3.0".to_string(),
+            });
         }
-        Ok(())
+        let content = r#"[workspace]
+members = ["core", "macros"]
+
+[workspace.dependencies]
+# 同一仓库中的 crate
+core = { path = "core", version = "=1.2.3" }   # 精确版本
+macros = "1.2.3"
+serde = { version = "1.2.3", features = ["derive"] }
+"#;
+        let mut document: DocumentMut = content.parse().unwrap();
+        let dependencies = document["workspace"]["dependencies"]
+            .as_table_like_mut()
+            .unwrap();
+
+        assert!(tool.rewrite_requirements(dependencies, "[workspace.dependencies]"));
+        assert_eq!(
+            document.to_string(),
+            r#"[workspace]
+members = ["core", "macros"]
+
+[workspace.dependencies]
+# 同一仓库中的 crate
+core = { path = "core", version = "=1.3.0" }   # 精确版本
+macros = "1.3.0"
+serde = { version = "1.2.3", features = ["derive"] }
+"#
+        );
     }
 
-    fn check_git_repo(&self) -> Result<()> {
-        let output = StdCommand::new("git")
-            .arg("rev-parse")
-            .arg("--is-inside-work-tree")
-            .output()?;
+    fn builtin_sink(tool: &ReleaseTool, name: &str) -> VersionSink {
+        tool.default_sinks()
+            .unwrap()
+            .into_iter()
+            .find(|sink| sink.name.as_deref() == Some(name))
+            .unwrap()
+    }
 
-        if !output.status.success() {
-            return Err(anyhow!("当前目录不是 git 仓库"));
+    /// 内置版本文件条目的键路径
+    fn builtin_keys(tool: &ReleaseTool, name: &str) -> Vec<Vec<String>> {
+        match builtin_sink(tool, name).format {
+            SinkFormat::Toml(keys) | SinkFormat::Json(keys) | SinkFormat::Yaml(keys) => keys,
+            _ => panic!("{} 没有键路径", name),
         }
-        Ok(())
     }
 
-    fn is_working_tree_clean(&self) -> Result<bool> {
-        let output = StdCommand::new("git")
-            .arg("status")
-            .arg("--porcelain")
-            .output()?;
+    #[test]
+    fn pyproject_pep621_version() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.3.0"], &git);
+        let keys = builtin_keys(&tool, PYPROJECT_SINK);
+        let content = r#"[project]
+name = "demo"
+version = "1.2.3"  # 与 Cargo.toml 同步
+dependencies = ["requests"]
+"#;
+
+        let (updated, old) = tool
+            .update_toml_sink(Path::new("pyproject.toml"), content, &keys)
+            .unwrap()
+            .unwrap();
 
-        Ok(output.stdout.is_empty())
+        assert_eq!(old.as_deref(), Some("1.2.3"));
+        assert_eq!(updated, content.replace("\"1.2.3\"", "\"1.3.0\""));
     }
 
-    fn update_versions(&mut self) -> Result<()> {
-        println!("📝 更新版本号...");
+    #[test]
+    fn pyproject_poetry_version() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.3.0"], &git);
+        let keys = builtin_keys(&tool, PYPROJECT_SINK);
+        let content = r#"[tool.poetry]
+name = "demo"
+version = "1.2.3"
 
-        // 检查是否是 workspace 项目
-        let root_cargo_path = Path::new("Cargo.toml");
-        if root_cargo_path.exists() {
-            let content = fs::read_to_string(root_cargo_path)?;
-            let cargo: CargoToml = toml::from_str(&content)?;
-
-            if cargo.workspace.is_some() {
-                println!("🔍 检测到 workspace 项目，更新所有成员...");
-                self.update_workspace_versions()?;
-            } else {
-                // 单个项目
-                self.update_single_crate(root_cargo_path)?;
-            }
-        } else {
-            return Err(anyhow!("未找到 Cargo.toml 文件"));
-        }
+[tool.poetry.dependencies]
+python = "^3.10"
+"#;
 
-        // 更新 tauri.conf.json
-        self.update_tauri_config()?;
-        Self::cargo_check()?;
+        let (updated, old) = tool
+            .update_toml_sink(Path::new("pyproject.toml"), content, &keys)
+            .unwrap()
+            .unwrap();
 
-        println!(
-            "✅ 版本号更新完成，共更新 {} 个文件",
-            self.updated_files.len()
-        );
-        Ok(())
+        assert_eq!(old.as_deref(), Some("1.2.3"));
+        assert_eq!(updated, content.replace("\"1.2.3\"", "\"1.3.0\""));
     }
 
-    fn cargo_check() -> Result<()> {
-        StdCommand::new("cargo").arg("check").status()?;
-        Ok(())
+    #[test]
+    fn pyproject_without_version_is_skipped() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.3.0"], &git);
+        let keys = builtin_keys(&tool, PYPROJECT_SINK);
+        let content = r#"[build-system]
+requires = ["maturin>=1.0"]
+
+[tool.maturin]
+features = ["python"]
+"#;
+
+        let updated = tool
+            .update_toml_sink(Path::new("pyproject.toml"), content, &keys)
+            .unwrap();
+
+        assert!(updated.is_none());
     }
 
-    fn update_workspace_versions(&mut self) -> Result<()> {
-        // 首先更新根 Cargo.toml 中的 workspace.package.version（如果存在）
-        self.update_root_workspace_version()?;
+    #[test]
+    fn symlink_loop_terminates_discovery() {
+        let dir = TempDir::new();
+        dir.write(
+            "Cargo.toml",
+            "[package]\nname = \"root\"\nversion = \"1.2.3\"\n",
+        );
+        dir.write(
+            "crates/a/Cargo.toml",
+            "[package]\nname = \"a\"\nversion = \"1.2.3\"\n",
+        );
+        // 指向上级目录的符号链接，跟随时会回到自身
+        std::os::unix::fs::symlink(dir.path().join("crates"), dir.path().join("crates/a/loop"))
+            .unwrap();
+        let git = FakeGit::new(dir.path(), "head");
 
-        // 查找并更新所有成员的 Cargo.toml
-        let cargo_toml_files = self.find_all_cargo_toml()?;
+        for args in [&["1.3.0"][..], &["1.3.0", "--follow-symlinks"]] {
+            let mut tool = tool(args, &git);
+            tool.root = dir.path().to_path_buf();
 
-        for cargo_path in cargo_toml_files {
-            self.update_single_crate(&cargo_path)?;
-        }
+            let manifests = tool.find_all_cargo_toml().unwrap();
 
-        Ok(())
+            assert_eq!(
+                manifests,
+                [
+                    dir.path().join("Cargo.toml"),
+                    dir.path().join("crates/a/Cargo.toml")
+                ]
+            );
+        }
     }
 
-    fn find_all_cargo_toml(&self) -> Result<Vec<PathBuf>> {
-        let mut cargo_files = Vec::new();
+    #[test]
+    fn unpublished_crates_are_skipped() {
+        let dir = TempDir::new();
+        dir.write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\npublish = false\n",
+        );
+        let manifests = [
+            ("app", ""),
+            ("internal", "publish = false\n"),
+            ("empty", "publish = []\n"),
+            ("inherited", "publish.workspace = true\n"),
+            ("private-registry", "publish = [\"company\"]\n"),
+        ];
+        for (name, publish) in manifests {
+            dir.write(
+                &format!("crates/{}/Cargo.toml", name),
+                &format!(
+                    "[package]\nname = \"{}\"\nversion = \"1.2.3\"\n{}",
+                    name, publish
+                ),
+            );
+        }
+        let plan = |args: &[&str], name: &str| {
+            let args = std::iter::once("git-release").chain(args.iter().copied());
+            let cargo_path = dir.path().join(format!("crates/{}/Cargo.toml", name));
+            let cli = Cli::parse_from(args);
+            ReleaseTool::plan_crate_update(
+                &cli.global,
+                &cli.release,
+                "1.3.0",
+                dir.path(),
+                None,
+                &cargo_path,
+            )
+            .unwrap()
+        };
 
-        for entry in WalkDir::new(".")
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.file_name().and_then(|s| s.to_str()) == Some("Cargo.toml") {
-                cargo_files.push(path.to_path_buf());
-            }
+        let updated: Vec<&str> = manifests
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| matches!(plan(&["1.3.0"], name), CrateUpdate::Updated { .. }))
+            .collect();
+        assert_eq!(updated, ["app", "private-registry"]);
+        for name in ["internal", "empty", "inherited"] {
+            assert!(matches!(
+                plan(&["1.3.0"], name),
+                CrateUpdate::Skipped(_, SkipReason::PublishDisabled)
+            ));
         }
 
-        Ok(cargo_files)
+        for (name, _) in manifests {
+            assert!(matches!(
+                plan(&["1.3.0", "--include-unpublished"], name),
+                CrateUpdate::Updated { .. }
+            ));
+        }
     }
 
-    fn update_root_workspace_version(&mut self) -> Result<()> {
-        let root_cargo_path = Path::new("Cargo.toml");
-        let content = fs::read_to_string(root_cargo_path)?;
-        let mut cargo: CargoToml = toml::from_str(&content)?;
+    #[test]
+    fn tauri_version_present() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.3.0"], &git);
+        let sink = builtin_sink(&tool, TAURI_SINK);
+        let keys = builtin_keys(&tool, TAURI_SINK);
 
-        // 更新 workspace.package.version
-        let mut old_version = None;
-        if let Some(ref mut workspace) = cargo.workspace
-            && let Some(ref mut workspace_package) = workspace.package
-            && let Some(ref mut version) = workspace_package.version
-        {
-            old_version = Some(version.clone());
-            *version = self.args.version.clone();
-        }
+        // Tauri 2 的顶层 version
+        let content = "{\n  \"productName\": \"demo\",\n  \"version\": \"1.2.3\"\n}\n";
+        let (updated, old) = tool
+            .update_json_sink(&sink, content, &keys)
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.as_deref(), Some("1.2.3"));
+        assert_eq!(updated, content.replace("1.2.3", "1.3.0"));
 
-        if let Some(old_version) = old_version {
-            let new_content = toml::to_string_pretty(&cargo)?;
-            fs::write(root_cargo_path, new_content)?;
-            self.updated_files.push(root_cargo_path.to_path_buf());
-            println!(
-                "✅ 更新 workspace 版本: {} -> {}",
-                old_version, self.args.version
+        // Tauri 1 的 package.version
+        let content =
+            "{\n  \"package\": { \"productName\": \"demo\", \"version\": \"1.2.3\" }\n}\n";
+        let (updated, old) = tool
+            .update_json_sink(&sink, content, &keys)
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.as_deref(), Some("1.2.3"));
+        assert_eq!(updated, content.replace("1.2.3", "1.3.0"));
+    }
+
+    #[test]
+    fn tauri_version_absent() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.3.0"], &git);
+        let sink = builtin_sink(&tool, TAURI_SINK);
+        let keys = builtin_keys(&tool, TAURI_SINK);
+
+        // 没有 version 或 version 为 null 时使用 Cargo.toml 中的版本号，跳过而不是报错
+        for content in [
+            "{\n  \"productName\": \"demo\"\n}\n",
+            "{\n  \"productName\": \"demo\",\n  \"version\": null\n}\n",
+        ] {
+            assert!(
+                tool.update_json_sink(&sink, content, &keys)
+                    .unwrap()
+                    .is_none()
             );
         }
-
-        Ok(())
     }
 
-    fn update_single_crate(&mut self, cargo_path: &Path) -> Result<()> {
-        let content = fs::read_to_string(cargo_path)?;
-        let cargo: CargoToml = toml::from_str(&content)?;
+    #[test]
+    fn submodule_manifests_are_not_discovered() {
+        let dir = TempDir::new();
+        let manifest =
+            |name: &str| format!("[package]\nname = \"{}\"\nversion = \"1.2.3\"\n", name);
+        dir.write("crates/a/Cargo.toml", &manifest("a"));
+        // 子模块中的 crate 由其他仓库管理版本
+        dir.write("vendor/upstream/Cargo.toml", &manifest("upstream"));
+        let git = FakeGit::new(dir.path(), "head").with_submodule("vendor/upstream");
+        let discover = |args: &[&str]| {
+            let mut tool = tool(args, &git);
+            tool.root = dir.path().to_path_buf();
+            tool.find_all_cargo_toml().unwrap()
+        };
+        let a = dir.path().join("crates/a/Cargo.toml");
+        let upstream = dir.path().join("vendor/upstream/Cargo.toml");
 
-        // 检查是否需要跳过此 crate
-        if let Some(ref package) = cargo.package {
-            let crate_name = &package.name;
+        // 遍历目录
+        dir.write("Cargo.toml", &manifest("root"));
+        let root = dir.path().join("Cargo.toml");
+        assert_eq!(discover(&["1.3.0"]), [root.clone(), a.clone()]);
+        assert_eq!(
+            discover(&["1.3.0", "--include-submodules"]),
+            [root.clone(), a.clone(), upstream.clone()]
+        );
 
-            // 检查排除列表
-            if !self.args.exclude.is_empty() && self.args.exclude.contains(crate_name) {
-                println!("⏭️  跳过 crate: {}", crate_name);
-                return Ok(());
-            }
+        // [workspace].members 中列出了子模块目录
+        dir.write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"vendor/*\"]\n",
+        );
+        assert_eq!(discover(&["1.3.0"]), std::slice::from_ref(&a));
+        assert_eq!(discover(&["1.3.0", "--include-submodules"]), [a, upstream]);
+    }
 
-            // 检查 only 列表
-            if !self.args.only.is_empty() && !self.args.only.contains(crate_name) {
-                println!("⏭️  跳过 crate (不在 --only 列表中): {}", crate_name);
-                return Ok(());
+    /// 依次应用 --readme 的全部条目
+    fn update_readme(args: &[&str], content: &str) -> String {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let mut tool = tool(args, &git);
+        tool.crate_versions.push(CrateVersion {
+            name: "demo".to_string(),
+            path: PathBuf::from("Cargo.toml"),
+            old_version: "1.2.3".to_string(),
+            new_version: tool.version.clone(),
+        });
+        let mut content = content.to_string();
+        for sink in tool.readme_sinks(Path::new("README.md")).unwrap() {
+            let SinkFormat::Regex {
+                pattern,
+                replacement,
+                max_matches,
+                ..
+            } = &sink.format
+            else {
+                panic!("README 条目应为正则");
+            };
+            if let Some((updated, _)) = tool
+                .update_regex_sink(&sink.path, &content, pattern, replacement, *max_matches)
+                .unwrap()
+            {
+                content = updated;
             }
+        }
+        content
+    }
 
-            let old_version = package.version.clone();
+    #[test]
+    fn readme_install_commands() {
+        let content = "\
+```sh
+cargo install demo --version 1.2.3
+cargo install demo --locked --version=\"1.2.3\"
+cargo install demo@1.2.3
+cargo add demo@1.2.3
+cargo install other --version 1.2.3
+cargo add other@1.2.3
+```
+";
+        assert_eq!(
+            update_readme(&["2.0.0"], content),
+            "\
+```sh
+cargo install demo --version 2.0.0
+cargo install demo --locked --version=\"2.0.0\"
+cargo install demo@2.0.0
+cargo add demo@2.0.0
+cargo install other --version 1.2.3
+cargo add other@1.2.3
+```
+"
+        );
+    }
 
-            // 创建新的 CargoToml 结构体来更新版本
-            let new_cargo_toml = self.create_updated_cargo_toml(&cargo)?;
+    #[test]
+    fn readme_dependency_lines() {
+        let content = "\
+```toml
+[dependencies]
+demo = \"1.2.3\"
+demo = { version = \"^1.2.3\", features = [\"full\"] }
+demo-extra = \"1.2.3\"
+```
 
-            let new_content = toml::to_string_pretty(&new_cargo_toml)?;
-            fs::write(cargo_path, new_content)?;
-            self.updated_files.push(cargo_path.to_path_buf());
+在正文中提到 demo = \"1.2.3\" 时不会被改动。
+";
+        assert_eq!(
+            update_readme(&["2.0.0"], content),
+            "\
+```toml
+[dependencies]
+demo = \"2.0.0\"
+demo = { version = \"^2.0.0\", features = [\"full\"] }
+demo-extra = \"1.2.3\"
+```
 
-            let relative_path = cargo_path.strip_prefix(".").unwrap_or(cargo_path);
-            println!(
-                "✅ 更新 {} ({}): {} -> {}",
-                relative_path.display(),
-                crate_name,
-                old_version,
-                self.args.version
-            );
-        }
+在正文中提到 demo = \"1.2.3\" 时不会被改动。
+"
+        );
+    }
 
-        Ok(())
+    #[test]
+    fn readme_version_badges() {
+        let content = "\
+![version](https://img.shields.io/badge/version-1.2.3-blue)
+![release](https://img.shields.io/badge/release-v1.2.3-green.svg)
+![rust](https://img.shields.io/badge/rust-1.80.0-orange)
+";
+        assert_eq!(
+            update_readme(&["2.0.0-rc.1+build.5"], content),
+            "\
+![version](https://img.shields.io/badge/version-2.0.0--rc.1%2Bbuild.5-blue)
+![release](https://img.shields.io/badge/release-v2.0.0--rc.1%2Bbuild.5-green.svg)
+![rust](https://img.shields.io/badge/rust-1.80.0-orange)
+"
+        );
     }
 
-    fn create_updated_cargo_toml(&self, cargo: &CargoToml) -> Result<CargoToml> {
-        let content = toml::to_string(cargo)?;
-        let mut updated: CargoToml = toml::from_str(&content)?;
+    fn package_version(manifest: &str) -> Result<PackageVersion, toml::de::Error> {
+        toml::from_str::<CargoToml>(manifest).map(|cargo| cargo.package.unwrap().version)
+    }
 
-        // 更新 package.version
-        if let Some(ref mut package) = updated.package {
-            package.version = self.args.version.clone();
+    #[test]
+    fn package_version_inherited_forms() {
+        for manifest in [
+            "[package]\nname = \"demo\"\nversion.workspace = true\n",
+            "[package]\nname = \"demo\"\nversion = { workspace = true }\n",
+        ] {
+            assert!(matches!(
+                package_version(manifest).unwrap(),
+                PackageVersion::Inherited { workspace: true }
+            ));
         }
+        assert_eq!(
+            package_version("[package]\nname = \"demo\"\nversion = \"1.2.3\"\n")
+                .unwrap()
+                .into_literal()
+                .as_deref(),
+            Some("1.2.3")
+        );
+    }
 
-        Ok(updated)
+    #[test]
+    fn package_version_rejects_workspace_false() {
+        let error =
+            package_version("[package]\nname = \"demo\"\nversion = { workspace = false }\n")
+                .unwrap_err();
+        assert!(error.to_string().contains("{ workspace = true }"));
+
+        assert!(package_version("[package]\nname = \"demo\"\nversion = 1\n").is_err());
     }
 
-    fn update_tauri_config(&mut self) -> Result<()> {
-        let tauri_paths = ["tauri.conf.json", "src-tauri/tauri.conf.json"];
+    #[test]
+    fn markdown_sink_only_edits_released_crates_in_fences() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let mut tool = tool(&["2.0.0"], &git);
+        tool.crate_versions.push(CrateVersion {
+            name: "mycrate".to_string(),
+            path: PathBuf::from("Cargo.toml"),
+            old_version: "1.2.3".to_string(),
+            new_version: "2.0.0".to_string(),
+        });
+        let content = "\
+# mycrate
 
-        for path in tauri_paths {
-            let tauri_path = Path::new(path);
-            if tauri_path.exists() {
-                let content = fs::read_to_string(tauri_path)?;
-                let mut tauri_config: TauriConfig = serde_json::from_str(&content)?;
+mycrate 1.2.3 需要 Rust 1.80.0，在 Cargo.toml 中写 mycrate = \"1.2.3\"。
 
-                let old_version = tauri_config.version.clone();
-                tauri_config.version = self.args.version.clone();
-                println!("✅ 更新 {}: {} -> {}", path, old_version, self.args.version);
+```toml
+[dependencies]
+mycrate = { version = \"~1.2.3\", features = [\"full\"] }
+serde = \"1.2.3\"
 
-                let new_content = serde_json::to_string_pretty(&tauri_config)?;
-                fs::write(tauri_path, new_content)?;
-                self.updated_files.push(tauri_path.to_path_buf());
-                return Ok(());
-            }
-        }
+[dependencies.mycrate]
+version = \"1.2.3\"
+```
 
-        println!("⚠️  未找到 tauri.conf.json，跳过");
-        Ok(())
+```bash
+cargo add mycrate@1.2.3
+cargo add othercrate@1.2.3
+```
+
+```text
+mycrate = \"1.2.3\"
+```
+";
+        let languages = ["toml".to_string(), "bash".to_string()];
+
+        let (updated, _) = tool
+            .update_markdown_sink(Path::new("docs/install.md"), content, &languages)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+# mycrate
+
+mycrate 1.2.3 需要 Rust 1.80.0，在 Cargo.toml 中写 mycrate = \"1.2.3\"。
+
+```toml
+[dependencies]
+mycrate = { version = \"~2.0.0\", features = [\"full\"] }
+serde = \"1.2.3\"
+
+[dependencies.mycrate]
+version = \"2.0.0\"
+```
+
+```bash
+cargo add mycrate@2.0.0
+cargo add othercrate@1.2.3
+```
+
+```text
+mycrate = \"1.2.3\"
+```
+"
+        );
     }
 
-    fn commit_changes(&self) -> Result<()> {
-        println!("💾 提交更改...");
+    #[test]
+    fn empty_tag_prefix_tag_name() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
+        let tool = tool(&["1.2.3", "--tag-prefix", ""], &git);
 
-        // 添加所有更改的文件
-        StdCommand::new("git").arg("add").arg("-A").status()?;
+        assert_eq!(tool.tag_name(), "1.2.3");
+        assert!(git::check_tag_name(&tool.tag_name()).is_ok());
+    }
 
-        // 生成提交信息
-        let commit_message = self.args.message.replace("{version}", &self.args.version);
+    #[test]
+    fn empty_tag_prefix_previous_tag() {
+        let dir = TempDir::new();
+        let (repo, commits) =
+            crate::test_support::init_repo(dir.path(), &["init", "fix: a", "feat: b"]);
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        for (name, commit) in [("1.2.2", commits[0]), ("1.2.3", commits[1])] {
+            let target = repo.find_object(commit, None).unwrap();
+            repo.tag(name, &target, &signature, name, false).unwrap();
+        }
+        // 与标签同名的分支指向更早的提交
+        repo.branch("1.2.3", &repo.find_commit(commits[0]).unwrap(), false)
+            .unwrap();
+        let backend = || Box::new(Git2Backend::new(dir.path()));
 
-        // 提交
-        StdCommand::new("git")
-            .arg("commit")
-            .arg("-m")
-            .arg(&commit_message)
-            .status()?;
+        let args = ["git-release", "1.2.4", "--tag-prefix", ""];
+        let tool = ReleaseTool::with_git(Cli::parse_from(args), backend());
+        assert_eq!(tool.previous_tag("").unwrap().as_deref(), Some("1.2.3"));
+        let commits_since = tool.git.commits_since(Some("1.2.3"), "HEAD").unwrap();
+        assert_eq!(commits_since.len(), 1);
+        assert_eq!(commits_since[0].summary(), "feat: b");
 
-        println!("✅ 提交完成: {}", commit_message);
-        Ok(())
+        // 标签按 refs/tags/ 解析，不会被同名分支混淆
+        let args = [
+            "git-release",
+            "--tag-prefix",
+            "",
+            "--tag-only",
+            "--idempotent",
+        ];
+        let mut tool = ReleaseTool::with_git(Cli::parse_from(args), backend());
+        tool.args.at = Some(commits[1].to_string());
+        tool.version = "1.2.3".to_string();
+        assert!(tool.is_already_released().unwrap());
     }
 
-    fn handle_tag(&self) -> Result<()> {
-        let tag_name = format!("{}{}", self.args.tag_prefix, self.args.version);
+    #[test]
+    fn plan_does_not_touch_the_workspace() {
+        let dir = TempDir::new();
+        let files = [
+            (
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\n# 所有 crate 共用\nversion = \"1.2.3\"\n",
+            ),
+            (
+                "crates/core/Cargo.toml",
+                "[package]\nname = \"core\"\nversion.workspace = true\nedition = \"2024\"\n",
+            ),
+            ("crates/core/src/lib.rs", ""),
+            (
+                "crates/cli/Cargo.toml",
+                "[package]\nname = \"cli\"\nversion.workspace = true\nedition = \"2024\"\n\n[dependencies]\ncore = { path = \"../core\", version = \"1.2.3\" }\n",
+            ),
+            ("crates/cli/src/lib.rs", ""),
+            ("VERSION", "1.2.3\n"),
+        ];
+        for (path, content) in files {
+            dir.write(path, content);
+        }
+        let _cwd = CurrentDir::enter(dir.path());
+        let git = FakeGit::new(dir.path(), "head").with_remote("origin");
+        let mut tool = tool(&["minor"], &git);
 
-        // 检查标签是否已存在
-        let tag_exists = !StdCommand::new("git")
-            .arg("tag")
-            .arg("-l")
-            .arg(&tag_name)
-            .output()?
-            .stdout
-            .is_empty();
-
-        if tag_exists {
-            if self.args.re_publish {
-                println!("🔄 重新发布版本，删除旧标签...");
+        let plan = tool.plan().unwrap().unwrap();
 
-                // 删除本地标签
-                StdCommand::new("git")
-                    .arg("tag")
-                    .arg("-d")
-                    .arg(&tag_name)
-                    .status()?;
+        assert_eq!(plan.version, "1.3.0");
+        assert_eq!(plan.tags, ["v1.3.0"]);
+        assert_eq!(
+            plan.commit_message.as_deref(),
+            Some("Release version 1.3.0")
+        );
+        assert_eq!(plan.remotes, ["origin"]);
+        let mut paths: Vec<&Path> = plan.files.iter().map(|file| file.path.as_path()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                Path::new("Cargo.toml"),
+                Path::new("VERSION"),
+                Path::new("crates/cli/Cargo.toml"),
+            ]
+        );
+        let cli = plan
+            .files
+            .iter()
+            .find(|file| file.path == Path::new("crates/cli/Cargo.toml"))
+            .unwrap();
+        assert_eq!(cli.content, files[3].1.replace("1.2.3", "1.3.0"));
 
-                // 删除所有远程仓库的标签
-                self.delete_remote_tags(&tag_name)?;
-            } else {
-                return Err(anyhow!(
-                    "标签 {} 已存在，使用 --re-publish 重新发布",
-                    tag_name
-                ));
-            }
+        // 计划只在内存中，磁盘上的文件和标签都没有变化
+        for (path, content) in files {
+            assert_eq!(dir.read(path), content);
         }
+        assert!(git.state().created_tags.is_empty());
+    }
 
-        // 创建新标签
-        println!("🏷️  创建标签: {}", tag_name);
-        StdCommand::new("git")
-            .arg("tag")
-            .arg("-a")
-            .arg(&tag_name)
-            .arg("-m")
-            .arg(format!("Version {}", self.args.version))
-            .status()?;
+    #[test]
+    fn plan_leaves_remote_checks_to_apply() {
+        let dir = TempDir::new();
+        let (repo, _) = crate::test_support::init_repo(dir.path(), &["init"]);
+        // 无法访问的远程仓库，任何 fetch 或 ls-remote 都会失败
+        let missing = dir.path().join("missing.git");
+        repo.remote("origin", missing.to_str().unwrap()).unwrap();
+        dir.write(
+            "Cargo.toml",
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+        );
+        dir.write("src/lib.rs", "");
+        let _cwd = CurrentDir::enter(dir.path());
+        let args = [
+            "git-release",
+            "patch",
+            "--allow-dirty",
+            "--fetch",
+            "--check-remote-tags",
+            "--verify-tag-absent-everywhere",
+        ];
+        let mut tool = ReleaseTool::with_git(
+            Cli::parse_from(args),
+            Box::new(Git2Backend::new(dir.path())),
+        );
 
-        Ok(())
+        let plan = tool.plan().unwrap().unwrap();
+        assert_eq!(plan.tags, ["v0.1.1"]);
+        assert_eq!(plan.remotes, ["origin"]);
+
+        let error = tool.preflight().unwrap_err();
+        assert!(error.to_string().contains("无法同步远程标签"), "{}", error);
+        assert_eq!(dir.read("Cargo.toml").matches("0.1.0").count(), 1);
     }
 
-    fn delete_remote_tags(&self, tag_name: &str) -> Result<()> {
-        let remotes_output = StdCommand::new("git").arg("remote").output()?;
+    #[test]
+    fn restoring_a_plan_removes_created_files() {
+        let dir = TempDir::new();
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2024\"\n";
+        dir.write("Cargo.toml", manifest);
+        dir.write("src/lib.rs", "");
+        fs::create_dir(dir.path().join("notes")).unwrap();
+        let _cwd = CurrentDir::enter(dir.path());
+        let git = FakeGit::new(dir.path(), "head");
+        let mut tool = tool(&["patch", "--quiet-git"], &git);
+        let mut plan = tool.plan().unwrap().unwrap();
 
-        let remotes = String::from_utf8(remotes_output.stdout)?;
+        // 计划中加入一个原本不存在的文件，发布失败时应当被删除
+        plan.files.push(PlannedFile {
+            path: PathBuf::from("RELEASE.md"),
+            old_version: None,
+            new_version: Some("0.1.1".to_string()),
+            content: "0.1.1\n".to_string(),
+        });
+        tool.install_plan(&plan).unwrap();
+        tool.write_changes().unwrap();
+        assert_eq!(dir.read("RELEASE.md"), "0.1.1\n");
 
-        for remote in remotes.lines() {
-            println!("🗑️  删除远程标签 {}/{}", remote, tag_name);
-            let _ = StdCommand::new("git")
-                .arg("push")
-                .arg(remote)
-                .arg("--delete")
-                .arg(tag_name)
-                .status();
-        }
+        tool.restore_backups();
+        assert!(!dir.path().join("RELEASE.md").exists());
+        assert_eq!(dir.read("Cargo.toml"), manifest);
 
-        Ok(())
+        // 无法读取的文件不会被当作新文件
+        plan.files.push(PlannedFile {
+            path: PathBuf::from("notes"),
+            old_version: None,
+            new_version: None,
+            content: String::new(),
+        });
+        let error = tool.install_plan(&plan).unwrap_err();
+        assert!(error.to_string().contains("读取 notes 失败"), "{}", error);
     }
 
-    fn push_to_remotes(&self) -> Result<()> {
-        println!("📤 推送到远程仓库...");
+    #[test]
+    fn verify_publish_checks_a_copy_of_the_workspace() {
+        let dir = TempDir::new();
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2024\"\ndescription = \"demo\"\nlicense = \"MIT\"\n";
+        dir.write("Cargo.toml", manifest);
+        dir.write("src/lib.rs", "");
+        dir.write("target/stale.txt", "");
+        let _cwd = CurrentDir::enter(dir.path());
+        let git = FakeGit::new(dir.path(), "head");
+        let mut tool = tool(&["patch", "--dry-run", "--verify-publish"], &git);
+        let plan = tool.plan().unwrap().unwrap();
+        tool.install_plan(&plan).unwrap();
 
-        let remotes_output = StdCommand::new("git").arg("remote").output()?;
+        let copy = tool.copy_worktree().unwrap();
+        assert!(copy.path().join("src/lib.rs").exists());
+        assert!(!copy.path().join("target").exists());
+        drop(copy);
 
-        let remotes = String::from_utf8(remotes_output.stdout)?;
+        let checks = tool.verify_publish().unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].error.is_none(), "{:?}", checks[0].error);
+        // 工作区中的清单和构建目录都没有被改动
+        assert_eq!(dir.read("Cargo.toml"), manifest);
+        assert!(!dir.path().join("Cargo.lock").exists());
+        assert!(!dir.path().join("target/package").exists());
+    }
 
-        for remote in remotes.lines() {
-            println!("⬆️  推送到 {}", remote);
+    fn os_args(args: &[&str]) -> Vec<OsString> {
+        std::iter::once("git-release")
+            .chain(args.iter().copied())
+            .map(OsString::from)
+            .collect()
+    }
 
-            // 推送提交
-            StdCommand::new("git")
-                .arg("push")
-                .arg(remote)
-                .arg("HEAD")
-                .status()?;
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
 
-            // 推送标签
-            StdCommand::new("git")
-                .arg("push")
-                .arg(remote)
-                .arg("--tags")
-                .status()?;
-        }
+    #[test]
+    fn subcommands_map_onto_release_options() {
+        let git = FakeGit::new(Path::new("/repo"), "head");
 
-        Ok(())
+        let bump = tool(&["bump", "--auto", "--tag-prefix", "bump"], &git);
+        assert!(bump.args.print_version && bump.args.auto);
+        assert_eq!(bump.global.tag_prefix, "bump");
+
+        let tag = tool(&["--dry-run", "tag", "--sign", "--remote", "origin"], &git);
+        assert!(tag.args.tag_only && tag.args.sign && tag.global.dry_run);
+        assert_eq!(tag.global.remote, ["origin"]);
+
+        let revert = tool(&["revert", "1.2.3", "--commit"], &git);
+        assert_eq!(revert.args.revert.as_deref(), Some("1.2.3"));
+        assert!(revert.args.revert_commit);
+
+        let list = tool(&["list", "--publish-order", "--exclude", "cli"], &git);
+        assert!(list.args.publish_order && !list.args.list_crates);
+        assert_eq!(list.global.exclude, ["cli"]);
+
+        let changelog = tool(&["changelog", "minor", "--since", "v1.0.0"], &git);
+        assert!(changelog.args.changelog && changelog.global.dry_run);
+        assert_eq!(changelog.args.version.as_deref(), Some("minor"));
+        assert_eq!(changelog.args.since.as_deref(), Some("v1.0.0"));
+
+        let release = tool(&["release", "-m", "bump {version}", "1.2.3"], &git);
+        assert_eq!(release.args.message, "bump {version}");
+        assert_eq!(release.version, "1.2.3");
+    }
+
+    #[test]
+    fn only_global_options_precede_subcommand() {
+        let name = Cli::subcommand_name(&os_args(&[
+            "-C",
+            ".",
+            "--tag-prefix",
+            "bump",
+            "bump",
+            "1.2.3",
+        ]));
+        assert_eq!(name.unwrap().as_deref(), Some("bump"));
+
+        // 选项的值与子命令同名时仍按选项解析
+        let error = Cli::subcommand_name(&os_args(&["-m", "bump", "bump", "1.2.3"])).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        assert!(error.to_string().contains("--message"));
+
+        assert_eq!(
+            Cli::subcommand_name(&os_args(&["-m", "bump", "1.2.3"])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn config_options_follow_subcommand() {
+        let dir = TempDir::new();
+        dir.write(
+            ".git-release.toml",
+            "tag-prefix = \"rel-\"\ntag-message = \"Tag {version}\"\nmessage = \"chore: {version}\"\n",
+        );
+        let _cwd = CurrentDir::enter(dir.path());
+
+        let args = config::args_with_config(os_args(&["tag"]), Some("tag")).unwrap();
+        let git = FakeGit::new(dir.path(), "head");
+        let tool = ReleaseTool::with_git(Cli::parse_from(args), Box::new(git));
+
+        assert!(tool.args.tag_only);
+        assert_eq!(tool.global.tag_prefix, "rel-");
+        // tag 有自己的 --tag-message，配置需要由子命令解析，否则会被子命令的默认值覆盖
+        assert_eq!(tool.args.tag_message, "Tag {version}");
+        assert_eq!(tool.args.message, "chore: {version}");
     }
 }
@@ -0,0 +1,85 @@
+use anstream::ColorChoice;
+use anstyle::{AnsiColor, Style};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// 简单的终端日志输出：进度信息写到 stdout，警告和错误写到 stderr
+///
+/// 成功的步骤显示为绿色，跳过和警告为黄色，错误为红色。
+/// 输出不是终端或设置了 NO_COLOR 时自动去掉颜色
+struct Logger;
+
+static LOGGER: Logger = Logger;
+
+const SUCCESS: Style = AnsiColor::Green.on_default();
+const SKIPPED: Style = AnsiColor::Yellow.on_default();
+const FAILURE: Style = AnsiColor::Red.on_default();
+const EMPHASIS: Style = Style::new().bold();
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // 依赖库的日志只保留警告和错误
+        metadata.level() <= log::max_level()
+            && (metadata.level() <= Level::Warn
+                || metadata.target().starts_with(env!("CARGO_CRATE_NAME")))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+        let style = match record.level() {
+            Level::Error => FAILURE,
+            Level::Warn => SKIPPED,
+            _ if message.starts_with('✅') => SUCCESS,
+            _ if message.starts_with('⏭') => SKIPPED,
+            _ => Style::new(),
+        };
+        crate::progress::suspend(|| match record.level() {
+            Level::Error | Level::Warn => {
+                anstream::eprintln!("{style}{message}{style:#}")
+            }
+            _ => anstream::println!("{style}{message}{style:#}"),
+        });
+    }
+
+    fn flush(&self) {
+        let _ = io::stdout().flush();
+    }
+}
+
+/// 初始化全局日志，重复调用时只更新日志级别，`color` 为 false 时不输出颜色
+pub fn init(level: LevelFilter, color: bool) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+    if !color {
+        ColorChoice::Never.write_global();
+    }
+}
+
+/// 输出中止程序的错误
+pub fn print_error(error: &dyn Display) {
+    crate::progress::suspend(|| anstream::eprintln!("{FAILURE}error:{FAILURE:#} {error}"));
+}
+
+/// 为 unified diff 着色：新增的行为绿色，删除的行为红色，文件头加粗
+pub fn paint_diff(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                EMPHASIS
+            } else if line.starts_with('+') {
+                SUCCESS
+            } else if line.starts_with('-') {
+                FAILURE
+            } else {
+                Style::new()
+            };
+            format!("{style}{line}{style:#}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,87 @@
+//! 查找 Markdown 中的围栏代码块，只修改代码块中的内容，不影响正文
+//!
+//! 支持 ``` 和 ~~~ 围栏（最多缩进 3 个空格），不处理缩进代码块和列表、引用中缩进更深的代码块
+
+use std::ops::Range;
+
+/// 一个围栏代码块
+pub struct CodeBlock {
+    /// 信息字符串开头的语言名，小写，没有时为空
+    pub language: String,
+    /// 代码块的内容（不包括围栏所在的行）在文本中的位置
+    pub content: Range<usize>,
+}
+
+/// 尚未闭合的围栏
+struct Fence {
+    marker: char,
+    length: usize,
+    language: String,
+    start: usize,
+}
+
+/// 按出现顺序返回文本中的所有围栏代码块，未闭合的代码块延续到文本末尾
+pub fn code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<Fence> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let text = line.trim_end_matches(['\n', '\r']);
+        let trimmed = text.trim_start_matches(' ');
+        if text.len() - trimmed.len() > 3 {
+            continue;
+        }
+        let Some(marker) = trimmed
+            .chars()
+            .next()
+            .filter(|char| matches!(char, '`' | '~'))
+        else {
+            continue;
+        };
+        let length = trimmed.len() - trimmed.trim_start_matches(marker).len();
+        if length < 3 {
+            continue;
+        }
+        let info = trimmed[length..].trim();
+
+        match open.take() {
+            // 闭合围栏使用相同的字符，长度不小于开始的围栏，后面不能有其他内容
+            Some(fence) if marker == fence.marker && length >= fence.length && info.is_empty() => {
+                blocks.push(CodeBlock {
+                    language: fence.language,
+                    content: fence.start..line_start,
+                });
+            }
+            Some(fence) => open = Some(fence),
+            // 反引号围栏的信息字符串中不能有反引号，否则是行内代码
+            None if marker == '`' && info.contains('`') => {}
+            None => {
+                // `toml,ignore` 和 `{.toml}` 等写法只取语言名
+                let language: String = info
+                    .trim_start_matches(['{', '.'])
+                    .chars()
+                    .take_while(|char| {
+                        char.is_ascii_alphanumeric() || matches!(char, '_' | '+' | '-')
+                    })
+                    .collect();
+                open = Some(Fence {
+                    marker,
+                    length,
+                    language: language.to_ascii_lowercase(),
+                    start: offset,
+                });
+            }
+        }
+    }
+
+    if let Some(fence) = open {
+        blocks.push(CodeBlock {
+            language: fence.language,
+            content: fence.start..content.len(),
+        });
+    }
+    blocks
+}
@@ -0,0 +1,58 @@
+//! 发布完成后向 webhook 发送通知（Slack、Discord 等）
+
+use anyhow::{Result, anyhow};
+use log::debug;
+use serde_json::Value;
+use std::thread;
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// 单次请求的超时时间
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 网络错误和服务端错误时最多尝试的次数
+const ATTEMPTS: u32 = 3;
+
+/// 以 JSON 向 webhook 发送 POST 请求，失败时按 1s、2s 的间隔重试
+///
+/// 4xx 响应说明地址或内容有误，不再重试
+pub fn send(url: &str, payload: &Value) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        let result = ureq::post(url)
+            .config()
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .header("User-Agent", USER_AGENT)
+            .send_json(payload);
+        let error = match result {
+            Ok(_) => return Ok(()),
+            Err(error) => error,
+        };
+
+        let retryable = !matches!(error, ureq::Error::StatusCode(400..=499));
+        if !retryable || attempt >= ATTEMPTS {
+            return Err(anyhow!("发送通知到 {} 失败: {}", redact(url), error));
+        }
+        debug!(
+            "⚠️  发送通知到 {} 失败（第 {} 次）: {}，稍后重试",
+            redact(url),
+            attempt,
+            error
+        );
+        thread::sleep(Duration::from_secs(1 << (attempt - 1)));
+        attempt += 1;
+    }
+}
+
+/// webhook 地址的路径中通常带有密钥，日志中只显示协议和主机名
+pub fn redact(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return "webhook".to_string();
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    // 去掉地址中的用户名和密码
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    format!("{}://{}/…", scheme, host)
+}
@@ -0,0 +1,103 @@
+//! 慢速网络操作的进度提示
+//!
+//! 推送、拉取等操作期间在终端显示一个带耗时的旋转指示器，结束后自动清除。
+//! 指示器存在时，日志和 git 的输出会先暂时隐藏指示器再写出，避免内容交错。
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static ACTIVE: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// 正在显示的指示器，离开作用域时清除
+pub struct Spinner {
+    enabled: bool,
+}
+
+impl Spinner {
+    /// 显示指示器，`enabled` 为 false 时不显示任何内容
+    pub fn start(enabled: bool, message: String) -> Self {
+        if enabled {
+            let spinner = ProgressBar::new_spinner().with_message(message);
+            if let Ok(style) = ProgressStyle::with_template("{spinner} {msg} ({elapsed})") {
+                spinner.set_style(style);
+            }
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            if let Ok(mut active) = ACTIVE.lock() {
+                *active = Some(spinner);
+            }
+        }
+        Self { enabled }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut active) = ACTIVE.lock()
+            && let Some(spinner) = active.take()
+        {
+            spinner.finish_and_clear();
+        }
+    }
+}
+
+/// 带计数的指示器，显示已处理的数量和总数，离开作用域时清除
+pub struct Counter {
+    bar: Option<ProgressBar>,
+}
+
+impl Counter {
+    /// 显示指示器，`enabled` 为 false 时不显示任何内容
+    pub fn start(enabled: bool, message: String, total: usize) -> Self {
+        if !enabled {
+            return Self { bar: None };
+        }
+        let bar = ProgressBar::new(total as u64).with_message(message);
+        if let Ok(style) = ProgressStyle::with_template("{spinner} {msg} {pos}/{len} ({elapsed})") {
+            bar.set_style(style);
+        }
+        bar.enable_steady_tick(Duration::from_millis(100));
+        if let Ok(mut active) = ACTIVE.lock() {
+            *active = Some(bar.clone());
+        }
+        Self { bar: Some(bar) }
+    }
+
+    /// 完成一项，可以在多个线程中同时调用
+    pub fn inc(&self) {
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        if self.bar.is_none() {
+            return;
+        }
+        if let Ok(mut active) = ACTIVE.lock()
+            && let Some(bar) = active.take()
+        {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// stdout 和 stderr 都是终端时才适合显示指示器
+pub fn is_terminal() -> bool {
+    std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// 暂时隐藏指示器并执行输出，没有指示器时直接执行
+pub fn suspend<T>(output: impl FnOnce() -> T) -> T {
+    let spinner = ACTIVE.lock().ok().and_then(|active| active.clone());
+    match spinner {
+        Some(spinner) => spinner.suspend(output),
+        None => output(),
+    }
+}
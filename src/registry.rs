@@ -0,0 +1,28 @@
+use anyhow::{Result, anyhow};
+
+const CRATES_IO_API: &str = "https://crates.io/api/v1/crates";
+
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+/// 查询 crates.io 上是否已存在指定 crate 的指定版本
+pub fn is_published(name: &str, version: &str) -> Result<bool> {
+    let url = format!("{}/{}/{}", CRATES_IO_API, name, version);
+
+    match ureq::get(&url).header("User-Agent", USER_AGENT).call() {
+        Ok(_) => Ok(true),
+        Err(ureq::Error::StatusCode(404)) => Ok(false),
+        Err(error) => Err(anyhow!(
+            "查询 crates.io 失败 ({}@{}): {}",
+            name,
+            version,
+            error
+        )),
+    }
+}
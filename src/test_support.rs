@@ -0,0 +1,293 @@
+//! 单元测试共用的临时目录、当前目录锁和 Git 测试替身
+
+use anyhow::{Result, anyhow};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::git::{CommitInfo, Git, TagRequest, Upstream};
+
+/// 测试结束时自动删除的临时目录
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "git-release-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        Self(fs::canonicalize(path).unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// 写入相对于临时目录的文件，自动创建上级目录
+    pub fn write(&self, path: &str, content: &str) -> PathBuf {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    pub fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.0.join(path)).unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 切换当前目录，结束时恢复。测试并行运行，持有锁期间其他依赖当前目录的测试会等待
+pub struct CurrentDir {
+    original: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl CurrentDir {
+    pub fn enter(path: &Path) -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        // 其他测试失败时锁会中毒，但当前目录仍会被恢复
+        let guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let original = std::env::current_dir().unwrap();
+        std::env::set_current_dir(path).unwrap();
+        Self {
+            original,
+            _guard: guard,
+        }
+    }
+}
+
+impl Drop for CurrentDir {
+    fn drop(&mut self) {
+        let _ = std::env::set_current_dir(&self.original);
+    }
+}
+
+/// 在目录中创建 git 仓库，依次创建空提交，返回仓库和各提交的 ID
+pub fn init_repo(path: &Path, messages: &[&str]) -> (git2::Repository, Vec<git2::Oid>) {
+    let repo = git2::Repository::init(path).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let mut commits = Vec::new();
+    {
+        let tree = repo.find_tree(tree_id).unwrap();
+        for message in messages {
+            let parent = commits.last().map(|id| repo.find_commit(*id).unwrap());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            let id = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            commits.push(id);
+        }
+    }
+    (repo, commits)
+}
+
+/// FakeGit 的状态，测试在交给 ReleaseTool 之后仍可以通过克隆的句柄查看
+#[derive(Debug, Default)]
+pub struct FakeState {
+    pub toplevel: PathBuf,
+    pub head: String,
+    /// 标签名和指向的提交，按创建顺序排列
+    pub tags: Vec<(String, String)>,
+    pub remotes: Vec<String>,
+    /// 子模块目录，相对于 toplevel
+    pub submodules: Vec<PathBuf>,
+    /// 从新到旧排列的提交
+    pub commits: Vec<CommitInfo>,
+    pub deleted_tags: Vec<String>,
+    pub deleted_remote_tags: Vec<(String, String)>,
+    pub created_tags: Vec<TagRequest>,
+}
+
+/// 只在内存中记录标签和提交的 Git 实现
+#[derive(Clone, Default)]
+pub struct FakeGit(pub Rc<RefCell<FakeState>>);
+
+impl FakeGit {
+    pub fn new(toplevel: &Path, head: &str) -> Self {
+        Self(Rc::new(RefCell::new(FakeState {
+            toplevel: toplevel.to_path_buf(),
+            head: head.to_string(),
+            ..FakeState::default()
+        })))
+    }
+
+    pub fn with_tag(self, name: &str, commit: &str) -> Self {
+        self.0
+            .borrow_mut()
+            .tags
+            .push((name.to_string(), commit.to_string()));
+        self
+    }
+
+    pub fn with_remote(self, name: &str) -> Self {
+        self.0.borrow_mut().remotes.push(name.to_string());
+        self
+    }
+
+    pub fn with_submodule(self, path: &str) -> Self {
+        self.0.borrow_mut().submodules.push(PathBuf::from(path));
+        self
+    }
+
+    pub fn state(&self) -> std::cell::Ref<'_, FakeState> {
+        self.0.borrow()
+    }
+
+    fn tag_commit(&self, name: &str) -> Option<String> {
+        let name = name.strip_prefix("refs/tags/").unwrap_or(name);
+        self.state()
+            .tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, commit)| commit.clone())
+    }
+}
+
+impl Git for FakeGit {
+    fn is_repository(&self) -> bool {
+        true
+    }
+
+    fn toplevel(&self) -> Result<PathBuf> {
+        Ok(self.state().toplevel.clone())
+    }
+
+    fn is_clean(&self, _untracked: bool) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.state().submodules.clone())
+    }
+
+    fn dirty_paths(&self, _untracked: bool) -> Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<String> {
+        if rev == "HEAD" {
+            return Ok(self.state().head.clone());
+        }
+        if let Some(commit) = self.tag_commit(rev) {
+            return Ok(commit);
+        }
+        let state = self.state();
+        if rev == state.head || state.commits.iter().any(|commit| commit.id == rev) {
+            return Ok(rev.to_string());
+        }
+        Err(anyhow!("无法解析提交: {}", rev))
+    }
+
+    fn current_branch(&self) -> Result<Option<String>> {
+        Ok(Some("main".to_string()))
+    }
+
+    fn upstream(&self) -> Result<Option<Upstream>> {
+        Ok(None)
+    }
+
+    fn ahead_behind(&self, _local: &str, _upstream: &str) -> Result<(usize, usize)> {
+        Ok((0, 0))
+    }
+
+    fn fetch(&self, _remote: &str, _args: &[&str]) -> Result<()> {
+        Ok(())
+    }
+
+    fn last_tag(&self, _rev: &str, prefix: &str) -> Result<Option<String>> {
+        Ok(self
+            .state()
+            .tags
+            .iter()
+            .rev()
+            .find(|(tag, _)| tag.starts_with(prefix))
+            .map(|(tag, _)| tag.clone()))
+    }
+
+    fn commits_since(&self, _since: Option<&str>, _rev: &str) -> Result<Vec<CommitInfo>> {
+        Ok(self.state().commits.clone())
+    }
+
+    fn changed_paths(&self, _since: &str, _rev: &str) -> Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    fn read_file(&self, _rev: &str, _path: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn tree_files(&self, _rev: &str) -> Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    fn tag_exists(&self, name: &str) -> Result<bool> {
+        Ok(self.tag_commit(name).is_some())
+    }
+
+    fn create_tag(&self, request: &TagRequest) -> Result<()> {
+        let target = self.resolve_commit(request.target.as_deref().unwrap_or("HEAD"))?;
+        let mut state = self.0.borrow_mut();
+        state.tags.push((request.name.clone(), target));
+        state.created_tags.push(request.clone());
+        Ok(())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<()> {
+        let mut state = self.0.borrow_mut();
+        state.tags.retain(|(tag, _)| tag != name);
+        state.deleted_tags.push(name.to_string());
+        Ok(())
+    }
+
+    fn remotes(&self) -> Result<Vec<String>> {
+        Ok(self.state().remotes.clone())
+    }
+
+    fn remote_url(&self, _name: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn config(&self, _key: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn push(&self, _remote: &str, _args: &[&str]) -> Result<()> {
+        Ok(())
+    }
+
+    fn push_atomic(&self, _remote: &str, _refspecs: &[&str]) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()> {
+        self.0
+            .borrow_mut()
+            .deleted_remote_tags
+            .push((remote.to_string(), name.to_string()));
+        Ok(())
+    }
+
+    fn remote_tag_exists(&self, _remote: &str, _name: &str) -> Result<bool> {
+        Ok(false)
+    }
+}
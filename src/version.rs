@@ -0,0 +1,134 @@
+//! 版本号计算
+
+use anyhow::{Result, anyhow};
+use semver::{BuildMetadata, Prerelease, Version};
+
+use crate::error;
+
+/// 版本递增的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// 解析命令行中的递增关键字
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "major" => Some(Bump::Major),
+            "minor" => Some(Bump::Minor),
+            "patch" => Some(Bump::Patch),
+            _ => None,
+        }
+    }
+
+    /// 在当前版本上递增，预发布和构建元数据会被清除
+    pub fn apply(self, current: &Version) -> Version {
+        let mut next = current.clone();
+        match self {
+            Bump::Major => {
+                next.major += 1;
+                next.minor = 0;
+                next.patch = 0;
+            }
+            Bump::Minor => {
+                next.minor += 1;
+                next.patch = 0;
+            }
+            Bump::Patch => next.patch += 1,
+        }
+        next.pre = Prerelease::EMPTY;
+        next.build = BuildMetadata::EMPTY;
+        next
+    }
+}
+
+/// 预发布版本的调整方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreStep {
+    /// 添加 `-rc.1`，例如 2.0.0 -> 2.0.0-rc.1
+    ToRc,
+    /// 递增预发布序号，例如 2.0.0-rc.1 -> 2.0.0-rc.2
+    BumpPre,
+    /// 去掉预发布部分作为正式版本，例如 2.0.0-rc.2 -> 2.0.0
+    Promote,
+}
+
+impl PreStep {
+    /// 在版本上执行调整，构建元数据会被清除
+    pub fn apply(self, version: &Version) -> Result<Version> {
+        let mut next = version.clone();
+        next.build = BuildMetadata::EMPTY;
+        match self {
+            PreStep::ToRc => {
+                if !version.pre.is_empty() {
+                    return Err(error::validation(format!(
+                        "{} 已经是预发布版本，使用 --bump-pre 递增预发布序号",
+                        version
+                    )));
+                }
+                next.pre = Prerelease::new("rc.1")?;
+            }
+            PreStep::BumpPre => {
+                if version.pre.is_empty() {
+                    return Err(error::validation(format!(
+                        "{} 不是预发布版本，使用 --to-rc 创建第一个预发布版本",
+                        version
+                    )));
+                }
+                next.pre = Prerelease::new(&bump_identifiers(version.pre.as_str()))?;
+            }
+            PreStep::Promote => {
+                if version.pre.is_empty() {
+                    return Err(error::validation(format!(
+                        "{} 不是预发布版本，没有可去掉的预发布部分",
+                        version
+                    )));
+                }
+                next.pre = Prerelease::EMPTY;
+            }
+        }
+        Ok(next)
+    }
+}
+
+/// 递增构建元数据中的序号，`value` 不为空时直接替换为该值，版本号本身保持不变
+pub fn bump_build(version: &Version, value: Option<&str>) -> Result<Version> {
+    let metadata = match value {
+        Some(value) => value.to_string(),
+        None if version.build.is_empty() => "build.1".to_string(),
+        None => bump_identifiers(version.build.as_str()),
+    };
+    let build = BuildMetadata::new(&metadata).map_err(|error| {
+        error::invalid_version(format!(
+            "构建元数据 {} 不符合 semver 规范: {}",
+            metadata, error
+        ))
+    })?;
+
+    let mut next = version.clone();
+    next.build = build;
+    if next == *version {
+        return Err(error::validation(format!("构建元数据未变化: {}", version)));
+    }
+    Ok(next)
+}
+
+/// 递增最后一个数字标识符，最后一个标识符不是数字时追加 `.1`（rc -> rc.1）
+fn bump_identifiers(pre: &str) -> String {
+    let (head, last) = match pre.rsplit_once('.') {
+        Some((head, last)) => (Some(head), last),
+        None => (None, pre),
+    };
+    match (head, last.parse::<u64>()) {
+        (Some(head), Ok(number)) => format!("{}.{}", head, number + 1),
+        (None, Ok(number)) => (number + 1).to_string(),
+        (_, Err(_)) => format!("{}.1", pre),
+    }
+}
+
+pub fn parse(version: &str) -> Result<Version> {
+    Version::parse(version).map_err(|error| anyhow!("无法解析版本号 {}: {}", version, error))
+}
@@ -0,0 +1,207 @@
+//! 对 YAML 文本做局部修改，只替换目标标量，保留注释、缩进和引号风格
+//!
+//! 只支持块格式的映射（`key: value`），不进入序列、流格式（`{a: 1}`）和多行标量
+
+use std::ops::Range;
+
+/// 查找指定键路径对应标量在文本中的位置，带引号的标量不包括引号
+pub fn find_scalar(content: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        lines.push((offset, line.trim_end_matches(['\n', '\r'])));
+        offset += line.len();
+    }
+
+    let mut start_line = 0;
+    let mut parent_indent = None;
+    for (depth, key) in path.iter().enumerate() {
+        let mut block_indent = None;
+        let mut found = None;
+        for (index, &(offset, line)) in lines.iter().enumerate().skip(start_line) {
+            let text = line.trim_start_matches(' ');
+            if text.is_empty() || text.starts_with('#') || text == "---" || text == "..." {
+                continue;
+            }
+            let indent = line.len() - text.len();
+            if parent_indent.is_some_and(|parent| indent <= parent) {
+                // 上一级映射到此结束
+                return None;
+            }
+            // 同一个映射中的键缩进相同，更深的行属于其他键的值
+            if *block_indent.get_or_insert(indent) != indent {
+                continue;
+            }
+
+            let Some((name, value_start)) = parse_key(text) else {
+                continue;
+            };
+            if name == *key {
+                found = Some((index, indent, offset + indent + value_start));
+                break;
+            }
+        }
+
+        let (index, indent, value_start) = found?;
+        if depth + 1 == path.len() {
+            let line_end = lines[index].0 + lines[index].1.len();
+            return scalar_span(content, value_start, line_end);
+        }
+        start_line = index + 1;
+        parent_indent = Some(indent);
+    }
+    None
+}
+
+/// 将指定键路径上的标量替换为新值，保留原有的引号，返回 (旧值, 新内容)
+///
+/// 目标不存在或不是单行标量时返回 `None`
+pub fn replace_scalar(content: &str, path: &[&str], value: &str) -> Option<(String, String)> {
+    let span = find_scalar(content, path)?;
+    let old = content[span.clone()].to_string();
+    let mut updated = String::with_capacity(content.len() + value.len());
+    updated.push_str(&content[..span.start]);
+    updated.push_str(value);
+    updated.push_str(&content[span.end..]);
+    Some((old, updated))
+}
+
+/// 解析一行开头的 `key:`，返回键名和值在该行（去掉缩进后）中的起始位置
+fn parse_key(text: &str) -> Option<(String, usize)> {
+    let (name, rest) = match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = text[1..].find(quote)? + 1;
+            (text[1..end].to_string(), end + 1)
+        }
+        // 序列项和流格式不是映射中的键
+        '-' | '[' | '{' => return None,
+        _ => {
+            let end = text
+                .match_indices(':')
+                .map(|(index, _)| index)
+                .find(|&index| {
+                    matches!(text.as_bytes().get(index + 1), None | Some(b' ' | b'\t'))
+                })?;
+            (text[..end].trim_end().to_string(), end)
+        }
+    };
+    text[rest..].starts_with(':').then_some((name, rest + 1))
+}
+
+/// 从 `start` 开始的单行标量的位置，`end` 为该行的行尾
+fn scalar_span(content: &str, start: usize, end: usize) -> Option<Range<usize>> {
+    let value = &content[start..end];
+    let leading = value.len() - value.trim_start_matches([' ', '\t']).len();
+    let start = start + leading;
+    let value = &content[start..end];
+
+    match value.chars().next()? {
+        '"' => {
+            let mut escaped = false;
+            for (index, char) in value.char_indices().skip(1) {
+                match char {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => return Some(start + 1..start + index),
+                    _ => escaped = false,
+                }
+            }
+            None
+        }
+        '\'' => {
+            let close = value[1..].find('\'')? + 1;
+            Some(start + 1..start + close)
+        }
+        // 注释、多行标量、流格式、锚点、别名和标签
+        '#' | '|' | '>' | '[' | '{' | '&' | '*' | '!' => None,
+        _ => {
+            let value = match value.find(" #") {
+                Some(comment) => &value[..comment],
+                None => value,
+            };
+            let value = value.trim_end();
+            (!value.is_empty()).then_some(start..start + value.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = "\
+# 发布流程
+name: release
+env:
+  # 与 Cargo.toml 同步
+  APP_VERSION: \"1.2.3\"
+  TOOL_VERSION: '1.2.3'
+  PLAIN_VERSION: 1.2.3 # 未加引号
+jobs:
+  build:
+    env:
+      APP_VERSION: 0.9.0
+    steps:
+      - run: echo done
+";
+
+    #[test]
+    fn double_quoted_scalar_keeps_quotes() {
+        let (old, updated) = replace_scalar(WORKFLOW, &["env", "APP_VERSION"], "1.3.0").unwrap();
+
+        assert_eq!(old, "1.2.3");
+        assert_eq!(
+            updated,
+            WORKFLOW.replace("APP_VERSION: \"1.2.3\"", "APP_VERSION: \"1.3.0\"")
+        );
+    }
+
+    #[test]
+    fn single_quoted_scalar_keeps_quotes() {
+        let (old, updated) = replace_scalar(WORKFLOW, &["env", "TOOL_VERSION"], "1.3.0").unwrap();
+
+        assert_eq!(old, "1.2.3");
+        assert_eq!(
+            updated,
+            WORKFLOW.replace("TOOL_VERSION: '1.2.3'", "TOOL_VERSION: '1.3.0'")
+        );
+    }
+
+    #[test]
+    fn unquoted_scalar_keeps_trailing_comment() {
+        let (old, updated) = replace_scalar(WORKFLOW, &["env", "PLAIN_VERSION"], "1.3.0").unwrap();
+
+        assert_eq!(old, "1.2.3");
+        assert_eq!(
+            updated,
+            WORKFLOW.replace(
+                "PLAIN_VERSION: 1.2.3 # 未加引号",
+                "PLAIN_VERSION: 1.3.0 # 未加引号"
+            )
+        );
+    }
+
+    #[test]
+    fn nested_key_only_matches_its_own_mapping() {
+        let (old, updated) =
+            replace_scalar(WORKFLOW, &["jobs", "build", "env", "APP_VERSION"], "1.0.0").unwrap();
+
+        assert_eq!(old, "0.9.0");
+        assert_eq!(
+            updated,
+            WORKFLOW.replace("APP_VERSION: 0.9.0", "APP_VERSION: 1.0.0")
+        );
+        // 上一级映射中没有的键不会从更深的映射中找到
+        assert!(find_scalar(WORKFLOW, &["jobs", "APP_VERSION"]).is_none());
+    }
+
+    #[test]
+    fn missing_key_and_comments_are_not_matched() {
+        assert!(replace_scalar(WORKFLOW, &["env", "MISSING"], "1.3.0").is_none());
+        assert!(replace_scalar(WORKFLOW, &["version"], "1.3.0").is_none());
+        // 注释中的 "APP_VERSION" 不是键
+        let commented = "env:\n  # APP_VERSION: 1.2.3\n  OTHER: x\n";
+        assert!(find_scalar(commented, &["env", "APP_VERSION"]).is_none());
+        // 序列不是标量
+        assert!(find_scalar(WORKFLOW, &["jobs", "build", "steps"]).is_none());
+    }
+}